@@ -81,5 +81,8 @@ impl Drop for ClientNode {
             "Storage cleanup failed! The expected path \"{}\" doesn't exist",
             db_path
         );
+
+        // Remove the peer book as well, so that the next node on this port starts afresh.
+        let _ = fs::remove_dir_all(format!("/tmp/snarkos-test-peers-{}", self.local_addr().port()));
     }
 }
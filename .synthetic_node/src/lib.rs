@@ -139,6 +139,7 @@ impl Handshake for SynthNode {
             own_ip.port(),
             self.state.local_nonce,
             0,
+            None,
         );
         trace!(parent: self.node().span(), "sending a challenge request to {}", peer_addr);
         let mut msg = Vec::new();
@@ -165,6 +166,7 @@ impl Handshake for SynthNode {
             peer_listening_port,
             peer_nonce,
             cumulative_weight,
+            _peer_pruned_block_height,
         )) = peer_request
         {
             // Don't reject peers due to the client version in order to keep track of non-compliant peers.
@@ -14,6 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+mod node_lists;
+pub use node_lists::NodeLists;
+
 mod node_type;
 pub use node_type::NodeType;
 
@@ -0,0 +1,107 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    collections::HashSet,
+    net::SocketAddr,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+/// The lists of beacon, sync and trusted nodes of the node server.
+/// They are initialized from the lists of the environment, and may be replaced at runtime.
+#[derive(Debug)]
+pub struct NodeLists {
+    beacon_nodes: RwLock<Arc<HashSet<SocketAddr>>>,
+    sync_nodes: RwLock<Arc<HashSet<SocketAddr>>>,
+    trusted_nodes: RwLock<Arc<HashSet<SocketAddr>>>,
+}
+
+impl NodeLists {
+    /// Initializes the node lists with the given IPs.
+    pub fn new(beacon_nodes: &[&str], sync_nodes: &[&str], trusted_nodes: &[&str]) -> Self {
+        Self {
+            beacon_nodes: RwLock::new(Arc::new(Self::parse(beacon_nodes))),
+            sync_nodes: RwLock::new(Arc::new(Self::parse(sync_nodes))),
+            trusted_nodes: RwLock::new(Arc::new(Self::parse(trusted_nodes))),
+        }
+    }
+
+    /// Parses the given list of IPs, which must be valid socket addresses.
+    pub fn parse(nodes: &[&str]) -> HashSet<SocketAddr> {
+        nodes.iter().map(|ip| ip.parse().unwrap()).collect()
+    }
+
+    /// Returns the beacon nodes.
+    pub fn beacon_nodes(&self) -> Arc<HashSet<SocketAddr>> {
+        Self::get(&self.beacon_nodes)
+    }
+
+    /// Returns the sync nodes.
+    pub fn sync_nodes(&self) -> Arc<HashSet<SocketAddr>> {
+        Self::get(&self.sync_nodes)
+    }
+
+    /// Returns the trusted nodes.
+    pub fn trusted_nodes(&self) -> Arc<HashSet<SocketAddr>> {
+        Self::get(&self.trusted_nodes)
+    }
+
+    /// Replaces the beacon nodes with the given ones.
+    pub fn set_beacon_nodes(&self, nodes: HashSet<SocketAddr>) {
+        Self::set(&self.beacon_nodes, nodes)
+    }
+
+    /// Replaces the sync nodes with the given ones.
+    pub fn set_sync_nodes(&self, nodes: HashSet<SocketAddr>) {
+        Self::set(&self.sync_nodes, nodes)
+    }
+
+    /// Replaces the trusted nodes with the given ones.
+    pub fn set_trusted_nodes(&self, nodes: HashSet<SocketAddr>) {
+        Self::set(&self.trusted_nodes, nodes)
+    }
+
+    fn get(list: &RwLock<Arc<HashSet<SocketAddr>>>) -> Arc<HashSet<SocketAddr>> {
+        // A list is replaced as a whole, so it remains valid even if a writer panicked.
+        list.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    fn set(list: &RwLock<Arc<HashSet<SocketAddr>>>, nodes: HashSet<SocketAddr>) {
+        *list.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(nodes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_lists() {
+        let node_lists = NodeLists::new(&[], &["127.0.0.1:4135"], &[]);
+        assert!(node_lists.beacon_nodes().is_empty());
+        assert!(node_lists.sync_nodes().contains(&"127.0.0.1:4135".parse().unwrap()));
+        assert!(node_lists.trusted_nodes().is_empty());
+
+        // A replaced list does not affect the lists handed out before.
+        let sync_nodes = node_lists.sync_nodes();
+        let trusted_node: SocketAddr = "127.0.0.1:4132".parse().unwrap();
+        node_lists.set_sync_nodes(HashSet::new());
+        node_lists.set_trusted_nodes([trusted_node].into_iter().collect());
+        assert_eq!(1, sync_nodes.len());
+        assert!(node_lists.sync_nodes().is_empty());
+        assert_eq!(vec![trusted_node], node_lists.trusted_nodes().iter().copied().collect::<Vec<_>>());
+    }
+}
@@ -21,7 +21,7 @@ extern crate tracing;
 
 pub mod helpers;
 
-use crate::helpers::{NodeType, Resources, Status};
+use crate::helpers::{NodeLists, NodeType, Resources, Status};
 use snarkvm::dpc::Network;
 
 use once_cell::sync::OnceCell;
@@ -44,6 +44,16 @@ pub trait Environment: 'static + Clone + Debug + Default + Send + Sync {
     const NODE_TYPE: NodeType;
     /// The version of the network protocol; it can be incremented in order to force users to update.
     const MESSAGE_VERSION: u32 = 12;
+    /// The message version from which peers support the encrypted transport, advertised by nodes with an identity key.
+    const NOISE_MESSAGE_VERSION: u32 = 13;
+    /// The message version from which peers relay unconfirmed blocks in compact form, advertised in `Ping` messages.
+    const COMPACT_BLOCK_MESSAGE_VERSION: u32 = 14;
+    /// The maximum number of blocks a compact block may be behind the latest block, for its transactions to be served.
+    const MAXIMUM_COMPACT_BLOCK_DEPTH: u32 = 10;
+    /// The duration in seconds after which a compact block whose missing transactions were not received is requested in full.
+    const PARTIAL_BLOCK_TIMEOUT_IN_SECS: u64 = 10;
+    /// The maximum number of compact blocks from a single peer whose missing transactions are awaited at a time.
+    const MAXIMUM_PARTIAL_BLOCKS_PER_PEER: usize = 4;
     /// If `true`, a mining node will craft public coinbase transactions.
     const COINBASE_IS_PUBLIC: bool = false;
 
@@ -52,18 +62,22 @@ pub trait Environment: 'static + Clone + Debug + Default + Send + Sync {
     /// The port for communicating with the RPC server.
     const DEFAULT_RPC_PORT: u16 = 3030 + Self::Network::NETWORK_ID;
 
-    /// The list of beacon nodes to bootstrap the node server with.
+    /// The default list of beacon nodes to bootstrap the node server with.
     const BEACON_NODES: &'static [&'static str] = &[];
-    /// The list of sync nodes to bootstrap the node server with.
+    /// The default list of sync nodes to bootstrap the node server with.
     const SYNC_NODES: &'static [&'static str] = &["127.0.0.1:4135"];
-    /// The list of nodes to attempt to maintain connections with.
+    /// The default list of nodes to attempt to maintain connections with.
     const TRUSTED_NODES: &'static [&'static str] = &[];
+    /// The default list of DNS seeds, whose A and AAAA records are peers to bootstrap the node server with.
+    const DNS_SEEDS: &'static [&'static str] = &[];
 
     /// The duration in seconds to sleep in between heartbeat executions.
     const HEARTBEAT_IN_SECS: u64 = 9;
     /// The maximum duration in seconds permitted for establishing a connection with a node,
     /// before dropping the connection; it should be no greater than the `HEARTBEAT_IN_SECS`.
     const CONNECTION_TIMEOUT_IN_MILLIS: u64 = 500;
+    /// The maximum duration in seconds permitted for a peer to complete the handshake, before dropping the connection.
+    const HANDSHAKE_TIMEOUT_IN_SECS: u64 = 10;
     /// The duration in seconds to sleep in between ping requests with a connected peer.
     const PING_SLEEP_IN_SECS: u64 = 60;
     /// The duration in seconds after which a connected peer is considered inactive or
@@ -78,32 +92,92 @@ pub trait Environment: 'static + Clone + Debug + Default + Send + Sync {
     const MAXIMUM_NUMBER_OF_PEERS: usize;
     /// The maximum number of connection failures permitted by an inbound connecting peer.
     const MAXIMUM_CONNECTION_FAILURES: u32 = 3;
+    /// The number of peer connections reserved for outbound connections, which inbound connections cannot take.
+    const RESERVED_OUTBOUND_PEERS: usize = 4;
+    /// The maximum number of inbound connections permitted from a single IP.
+    const MAXIMUM_INBOUND_PEERS_PER_IP: usize = 2;
+    /// The maximum number of inbound connections permitted from a single /24 IPv4 or /48 IPv6 subnet.
+    const MAXIMUM_INBOUND_PEERS_PER_SUBNET: usize = 4;
+    /// The maximum number of inbound connections permitted from a single /16 IPv4 subnet.
+    const MAXIMUM_INBOUND_PEERS_PER_IPV4_SLASH_16: usize = 8;
+    /// The number of inbound connections permitted beyond the limit per IP, shared by all IPs rather than granted
+    /// to each, as the provers of a pool may share an IP. These connections do not count towards the subnet limits.
+    const MAXIMUM_INBOUND_PROVERS_PER_IP: usize = 0;
     /// The maximum number of candidate peers permitted to be stored in the node.
     const MAXIMUM_CANDIDATE_PEERS: usize = 100;
+    /// The maximum number of peers permitted to be recorded in the peer book.
+    const MAXIMUM_PEER_BOOK_SIZE: usize = 1000;
 
     /// The maximum size of a message that can be transmitted in the network.
     const MAXIMUM_MESSAGE_SIZE: usize = 128 * 1024 * 1024; // 128 MiB
     /// The maximum number of blocks that may be fetched in one request.
     const MAXIMUM_BLOCK_REQUEST: u32 = 250;
-    /// The maximum number of failures tolerated before disconnecting from a peer.
-    const MAXIMUM_NUMBER_OF_FAILURES: usize = 1024;
+    /// The maximum number of bytes per second permitted to be received from a peer.
+    const MAXIMUM_BYTES_PER_SEC_PER_PEER: u64 = 32 * 1024 * 1024; // 32 MiB
+    /// The rate limits of messages received from a peer := (message_name, messages_per_sec, burst).
+    const MESSAGE_RATE_LIMITS: &'static [(&'static str, u32, u32)] = &[
+        ("BlockRequest", 10, 50),
+        ("BlockTransactions", 10, 50),
+        ("CompactBlock", 10, 50),
+        ("GetBlockTransactions", 10, 50),
+        ("PeerRequest", 1, 5),
+        ("Ping", 1, 5),
+        ("PoolRegister", 1, 5),
+        ("PoolResponse", 50, 500),
+        ("UnconfirmedBlock", 10, 50),
+        ("UnconfirmedTransaction", 100, 1000),
+    ];
+    /// The rate limits of messages received from a peer that replace those in `MESSAGE_RATE_LIMITS` for this node type.
+    const MESSAGE_RATE_LIMIT_OVERRIDES: &'static [(&'static str, u32, u32)] = &[];
+    /// The rate limit of the messages received from a peer that are not listed
+    /// in `MESSAGE_RATE_LIMITS` := (messages_per_sec, burst).
+    const DEFAULT_MESSAGE_RATE_LIMIT: (u32, u32) = (250, 2500);
+    /// The misbehavior score at which a peer is disconnected and banned.
+    const MAXIMUM_MISBEHAVIOR_SCORE: u32 = 100;
+    /// The duration in seconds after which the weight of a misbehavior is halved.
+    const MISBEHAVIOR_HALF_LIFE_IN_SECS: u64 = 1800; // 30 minutes
+    /// The duration in seconds of the first ban of a peer, which doubles with each repeat offense.
+    const BAN_DURATION_IN_SECS: u64 = 3600; // 1 hour
+    /// The maximum duration in seconds of a ban of a peer.
+    const MAXIMUM_BAN_DURATION_IN_SECS: u64 = 7 * 24 * 3600; // 1 week
+
+    /// The maximum total size of the unconfirmed transactions in the memory pool.
+    const MAXIMUM_MEMORY_POOL_SIZE: usize = 64 * 1024 * 1024; // 64 MiB
+    /// The maximum number of unconfirmed transactions in the memory pool.
+    const MAXIMUM_MEMORY_POOL_TRANSACTIONS: usize = 8192;
+    /// The duration in seconds after which an unconfirmed transaction expires from the memory pool.
+    const MEMORY_POOL_EXPIRY_IN_SECS: u64 = 3600; // 1 hour
+    /// The maximum total size of the unconfirmed transactions included in a block template.
+    const MAXIMUM_BLOCK_TRANSACTIONS_SIZE: usize = 2 * 1024 * 1024; // 2 MiB
+
+    /// Returns the lists of beacon, sync and trusted nodes, which may be replaced at runtime.
+    fn node_lists() -> &'static NodeLists {
+        static NODE_LISTS: OnceCell<NodeLists> = OnceCell::new();
+        NODE_LISTS.get_or_init(|| NodeLists::new(Self::BEACON_NODES, Self::SYNC_NODES, Self::TRUSTED_NODES))
+    }
 
     /// Returns the list of beacon nodes to bootstrap the node server with.
-    fn beacon_nodes() -> &'static HashSet<SocketAddr> {
-        static NODES: OnceCell<HashSet<SocketAddr>> = OnceCell::new();
-        NODES.get_or_init(|| Self::BEACON_NODES.iter().map(|ip| ip.parse().unwrap()).collect())
+    fn beacon_nodes() -> Arc<HashSet<SocketAddr>> {
+        Self::node_lists().beacon_nodes()
     }
 
     /// Returns the list of sync nodes to bootstrap the node server with.
-    fn sync_nodes() -> &'static HashSet<SocketAddr> {
-        static NODES: OnceCell<HashSet<SocketAddr>> = OnceCell::new();
-        NODES.get_or_init(|| Self::SYNC_NODES.iter().map(|ip| ip.parse().unwrap()).collect())
+    fn sync_nodes() -> Arc<HashSet<SocketAddr>> {
+        Self::node_lists().sync_nodes()
     }
 
     /// Returns the list of trusted nodes.
-    fn trusted_nodes() -> &'static HashSet<SocketAddr> {
-        static NODES: OnceCell<HashSet<SocketAddr>> = OnceCell::new();
-        NODES.get_or_init(|| Self::TRUSTED_NODES.iter().map(|ip| ip.parse().unwrap()).collect())
+    fn trusted_nodes() -> Arc<HashSet<SocketAddr>> {
+        Self::node_lists().trusted_nodes()
+    }
+
+    /// Returns the rate limit of the given message received from a peer := (messages_per_sec, burst), if it has a dedicated one.
+    fn message_rate_limit(message_name: &str) -> Option<(u32, u32)> {
+        Self::MESSAGE_RATE_LIMIT_OVERRIDES
+            .iter()
+            .chain(Self::MESSAGE_RATE_LIMITS)
+            .find(|(name, ..)| *name == message_name)
+            .map(|(_, messages_per_sec, burst)| (*messages_per_sec, *burst))
     }
 
     /// Returns the resource handler for the node.
@@ -176,6 +250,10 @@ impl<N: Network> Environment for Operator<N> {
     const COINBASE_IS_PUBLIC: bool = true;
     const MINIMUM_NUMBER_OF_PEERS: usize = 1;
     const MAXIMUM_NUMBER_OF_PEERS: usize = 50;
+    const MAXIMUM_INBOUND_PROVERS_PER_IP: usize = 64;
+    const MESSAGE_RATE_LIMIT_OVERRIDES: &'static [(&'static str, u32, u32)] = &[
+        ("PoolResponse", 200, 2000),
+    ];
 }
 
 #[derive(Clone, Debug, Default)]
@@ -200,6 +278,10 @@ impl<N: Network> Environment for SyncNode<N> {
     const MINIMUM_NUMBER_OF_PEERS: usize = 35;
     const MAXIMUM_NUMBER_OF_PEERS: usize = 1024;
     const HEARTBEAT_IN_SECS: u64 = 5;
+    const MESSAGE_RATE_LIMIT_OVERRIDES: &'static [(&'static str, u32, u32)] = &[
+        ("BlockRequest", 50, 250),
+        ("PeerRequest", 5, 25),
+    ];
 }
 
 #[derive(Clone, Debug, Default)]
@@ -250,6 +332,10 @@ impl<N: Network> Environment for OperatorTrial<N> {
     const MINIMUM_NUMBER_OF_PEERS: usize = 11;
     const MAXIMUM_NUMBER_OF_PEERS: usize = 21;
     const COINBASE_IS_PUBLIC: bool = true;
+    const MAXIMUM_INBOUND_PROVERS_PER_IP: usize = 64;
+    const MESSAGE_RATE_LIMIT_OVERRIDES: &'static [(&'static str, u32, u32)] = &[
+        ("PoolResponse", 200, 2000),
+    ];
 }
 
 #[derive(Clone, Debug, Default)]
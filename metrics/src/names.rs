@@ -14,14 +14,31 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-pub const GAUGE_NAMES: [&str; 4] = [blocks::HEIGHT, peers::CONNECTED, peers::CANDIDATE, peers::RESTRICTED];
+pub const GAUGE_NAMES: [&str; 7] = [
+    blocks::HEIGHT,
+    ledger::BLOCK_CACHE_HIT_RATE,
+    ledger::HEADER_CACHE_HIT_RATE,
+    ledger::BLOCK_BYTES_CACHE_HIT_RATE,
+    peers::CONNECTED,
+    peers::CANDIDATE,
+    peers::RESTRICTED,
+];
+
+pub const COUNTER_NAMES: [&str; 1] = [peers::RATE_LIMITED];
 
 pub mod blocks {
     pub const HEIGHT: &str = "snarkos_blocks_height_total";
 }
 
+pub mod ledger {
+    pub const BLOCK_CACHE_HIT_RATE: &str = "snarkos_ledger_block_cache_hit_rate";
+    pub const HEADER_CACHE_HIT_RATE: &str = "snarkos_ledger_header_cache_hit_rate";
+    pub const BLOCK_BYTES_CACHE_HIT_RATE: &str = "snarkos_ledger_block_bytes_cache_hit_rate";
+}
+
 pub mod peers {
     pub const CONNECTED: &str = "snarkos_peers_connected_total";
     pub const CANDIDATE: &str = "snarkos_peers_candidate_total";
     pub const RESTRICTED: &str = "snarkos_peers_restricted_total";
+    pub const RATE_LIMITED: &str = "snarkos_peers_rate_limited_total";
 }
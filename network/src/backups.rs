@@ -0,0 +1,146 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Ledger, Operator};
+use snarkos_environment::{helpers::NodeType, Environment};
+use snarkos_storage::storage::BackupInfo;
+use snarkvm::dpc::prelude::*;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+use tokio::{sync::Mutex, task};
+
+///
+/// The configuration of the backups of the ledger and operator storage.
+///
+#[derive(Clone, Debug)]
+pub struct BackupConfig {
+    /// The directory to create the backups in.
+    pub directory: PathBuf,
+    /// The interval between scheduled backups, if they are enabled.
+    pub interval: Option<Duration>,
+    /// The number of most recent backups to keep, if they are limited.
+    pub num_backups_to_keep: Option<usize>,
+}
+
+impl BackupConfig {
+    /// Returns the directory of the backups of the ledger storage.
+    pub fn ledger_directory(&self) -> PathBuf {
+        self.directory.join("ledger")
+    }
+
+    /// Returns the directory of the backups of the operator storage.
+    pub fn operator_directory(&self) -> PathBuf {
+        self.directory.join("operator")
+    }
+}
+
+///
+/// The backups created at the same time, of the ledger storage and, for an operator, the operator storage.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupReport {
+    pub ledger: BackupInfo,
+    pub operator: Option<BackupInfo>,
+}
+
+///
+/// Creates online backups of the ledger and operator storage, on request and on a schedule.
+///
+pub struct Backups<N: Network, E: Environment> {
+    /// The configuration of the backups.
+    config: BackupConfig,
+    /// The ledger of the node.
+    ledger: Arc<Ledger<N, E>>,
+    /// The operator of the node.
+    operator: Arc<Operator<N, E>>,
+    /// The lock to ensure a single backup is created at a time.
+    lock: Mutex<()>,
+}
+
+impl<N: Network, E: Environment> Backups<N, E> {
+    /// Initializes a new instance of the backups, and schedules them if an interval is configured.
+    pub async fn open(config: BackupConfig, ledger: Arc<Ledger<N, E>>, operator: Arc<Operator<N, E>>) -> Arc<Self> {
+        let backups = Arc::new(Self {
+            config,
+            ledger,
+            operator,
+            lock: Mutex::new(()),
+        });
+
+        if let Some(interval) = backups.config.interval {
+            let backups_clone = backups.clone();
+            E::resources().register_task(
+                None, // No need to provide an id, as the task will run indefinitely.
+                task::spawn(async move {
+                    loop {
+                        tokio::time::sleep(interval).await;
+                        if let Err(error) = backups_clone.backup().await {
+                            error!("Failed to create the scheduled backup: {}", error);
+                        }
+                    }
+                }),
+            );
+        }
+
+        backups
+    }
+
+    /// Returns the configuration of the backups.
+    pub fn config(&self) -> &BackupConfig {
+        &self.config
+    }
+
+    ///
+    /// Creates an incremental backup of the ledger storage and, for an operator, the operator storage,
+    /// while the node remains online.
+    ///
+    pub async fn backup(&self) -> Result<BackupReport> {
+        // Ensure the backups are not interleaved, as they would otherwise contend for the same directories.
+        let _lock = self.lock.lock().await;
+
+        let (ledger, operator, config) = (self.ledger.clone(), self.operator.clone(), self.config.clone());
+        let report = task::spawn_blocking(move || -> Result<BackupReport> {
+            let num_backups_to_keep = config.num_backups_to_keep;
+            Ok(BackupReport {
+                ledger: ledger.backup(config.ledger_directory(), num_backups_to_keep)?,
+                // The operator storage holds the shares of the provers, which only an operator records.
+                operator: match E::NODE_TYPE == NodeType::Operator {
+                    true => Some(operator.backup(config.operator_directory(), num_backups_to_keep)?),
+                    false => None,
+                },
+            })
+        })
+        .await
+        .map_err(|error| anyhow!("Failed to create a backup: {}", error))??;
+
+        match report.operator {
+            Some(operator) => info!(
+                "Created backup {} of the ledger and backup {} of the operator in {}",
+                report.ledger.backup_id,
+                operator.backup_id,
+                self.config.directory.display()
+            ),
+            None => info!(
+                "Created backup {} of the ledger in {}",
+                report.ledger.backup_id,
+                self.config.directory.display()
+            ),
+        }
+        Ok(report)
+    }
+}
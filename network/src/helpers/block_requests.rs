@@ -25,6 +25,7 @@ use std::net::SocketAddr;
 /// The maximum known block height and cumulative weight are tracked for the purposes of further operations.
 pub fn find_maximal_peer<N: Network, E: Environment>(
     peers_state: &PeersState<N>,
+    latest_block_height: u32,
     maximum_block_height: &mut u32,
     maximum_cumulative_weight: &mut u128,
 ) -> Option<(SocketAddr, bool, BlockLocators<N>)> {
@@ -42,7 +43,12 @@ pub fn find_maximal_peer<N: Network, E: Environment>(
         if !peers_contains_sync_node || E::sync_nodes().contains(peer_ip) {
             // Update the maximal peer state if the peer is ahead and the peer knows if you are a fork or not.
             // This accounts for (Case 1 and Case 2(a))
-            if let Some((_, _, is_on_fork, block_height, block_locators)) = peer_state {
+            if let Some((_, _, is_on_fork, block_height, block_locators, pruned_block_height)) = peer_state {
+                // Skip the peer if it has pruned the blocks that this node needs to request.
+                if matches!(pruned_block_height, Some(pruned_block_height) if *pruned_block_height > latest_block_height) {
+                    continue;
+                }
+
                 // Retrieve the cumulative weight, defaulting to the block height if it does not exist.
                 let cumulative_weight = match block_locators.get_cumulative_weight(*block_height) {
                     Some(cumulative_weight) => cumulative_weight,
@@ -0,0 +1,329 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkvm::dpc::prelude::*;
+
+use anyhow::{anyhow, ensure, Result};
+use serde::{Deserialize, Serialize};
+use siphasher::sip::SipHasher24;
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    hash::Hasher,
+    time::{Duration, Instant},
+};
+
+/// The short ID of a transaction in a compact block.
+pub type ShortTransactionId = [u8; 6];
+
+///
+/// A block whose transactions are given by their short IDs, so that a peer may reconstruct it from its memory pool.
+/// The transactions that are never in a memory pool, such as the coinbase transaction, are given in full.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CompactBlock<N: Network> {
+    /// The hash of the block.
+    pub block_hash: N::BlockHash,
+    /// The hash of the previous block.
+    pub previous_block_hash: N::BlockHash,
+    /// The header of the block.
+    pub header: BlockHeader<N>,
+    /// The salt of the short transaction IDs, which makes their collisions unpredictable.
+    pub salt: u64,
+    /// The short IDs of the transactions that are not prefilled, in the order of the block.
+    pub short_ids: Vec<ShortTransactionId>,
+    /// The transactions that are given in full := (index, transaction), in the order of the block.
+    pub prefilled_transactions: Vec<(u32, Transaction<N>)>,
+}
+
+impl<N: Network> CompactBlock<N> {
+    ///
+    /// Initializes a compact block from the given block, prefilling its coinbase transaction.
+    ///
+    pub fn new(block: &Block<N>, salt: u64) -> Result<Self> {
+        let coinbase_id = block.to_coinbase_transaction()?.transaction_id();
+
+        let mut compact_block = Self {
+            block_hash: block.hash(),
+            previous_block_hash: block.previous_block_hash(),
+            header: block.header().clone(),
+            salt,
+            short_ids: Vec::with_capacity(block.transactions().len()),
+            prefilled_transactions: vec![],
+        };
+        for (index, transaction) in block.transactions().iter().enumerate() {
+            match transaction.transaction_id() == coinbase_id {
+                true => compact_block.prefilled_transactions.push((index as u32, transaction.clone())),
+                false => {
+                    let short_id = compact_block.short_id(&transaction.transaction_id())?;
+                    compact_block.short_ids.push(short_id);
+                }
+            }
+        }
+        Ok(compact_block)
+    }
+
+    /// Returns the number of transactions in the block.
+    pub fn number_of_transactions(&self) -> usize {
+        self.short_ids.len() + self.prefilled_transactions.len()
+    }
+
+    ///
+    /// Returns the short ID of the given transaction ID, which is keyed by the block hash and the salt.
+    ///
+    pub fn short_id(&self, transaction_id: &N::TransactionID) -> Result<ShortTransactionId> {
+        let key = self.block_hash.to_bytes_le()?;
+        ensure!(key.len() >= 16, "The block hash is too short to key the short transaction IDs");
+        let k0 = u64::from_le_bytes(key[0..8].try_into()?) ^ self.salt;
+        let k1 = u64::from_le_bytes(key[8..16].try_into()?);
+
+        let mut hasher = SipHasher24::new_with_keys(k0, k1);
+        hasher.write(&transaction_id.to_bytes_le()?);
+
+        let mut short_id = ShortTransactionId::default();
+        short_id.copy_from_slice(&hasher.finish().to_le_bytes()[..short_id.len()]);
+        Ok(short_id)
+    }
+
+    ///
+    /// Returns the transactions of the block, taking those that are not prefilled from the given transactions,
+    /// with `None` in place of each transaction that is missing.
+    ///
+    pub fn fill<'a, I: IntoIterator<Item = &'a Transaction<N>>>(&self, transactions: I) -> Result<Vec<Option<Transaction<N>>>> {
+        // Index the given transactions by their short IDs.
+        let mut candidates = HashMap::new();
+        for transaction in transactions {
+            candidates.insert(self.short_id(&transaction.transaction_id())?, transaction);
+        }
+
+        let mut prefilled_transactions = self.prefilled_transactions.iter().peekable();
+        let mut short_ids = self.short_ids.iter();
+        let mut block_transactions = Vec::with_capacity(self.number_of_transactions());
+        for index in 0..self.number_of_transactions() {
+            match prefilled_transactions.next_if(|(prefilled_index, _)| *prefilled_index as usize == index) {
+                Some((_, transaction)) => block_transactions.push(Some(transaction.clone())),
+                None => {
+                    let short_id = short_ids
+                        .next()
+                        .ok_or_else(|| anyhow!("Malformed compact block {}", self.block_hash))?;
+                    block_transactions.push(candidates.get(short_id).map(|transaction| (*transaction).clone()));
+                }
+            }
+        }
+
+        // Ensure the prefilled transactions are in order, and within the block.
+        ensure!(
+            prefilled_transactions.next().is_none(),
+            "Malformed compact block {}",
+            self.block_hash
+        );
+        Ok(block_transactions)
+    }
+
+    ///
+    /// Returns the block with the given transactions, ensuring it matches the hash of the compact block.
+    ///
+    pub fn to_block(&self, transactions: Vec<Transaction<N>>) -> Result<Block<N>> {
+        let block = Block::from(self.previous_block_hash, self.header.clone(), Transactions::from(&transactions)?)?;
+        ensure!(
+            block.hash() == self.block_hash,
+            "The reconstructed block {} does not match the compact block {}",
+            block.hash(),
+            self.block_hash
+        );
+        Ok(block)
+    }
+}
+
+///
+/// A compact block that is being reconstructed, whose missing transactions were requested from the peer that relayed it.
+///
+#[derive(Clone, Debug)]
+pub struct PartialBlock<N: Network> {
+    /// The compact block.
+    compact_block: CompactBlock<N>,
+    /// The transactions of the block, with `None` in place of each transaction that is missing.
+    transactions: Vec<Option<Transaction<N>>>,
+    /// If `true`, some of the transactions were taken from the memory pool.
+    uses_memory_pool: bool,
+    /// The timestamp of the initialization of the partial block.
+    timestamp: Instant,
+}
+
+impl<N: Network> PartialBlock<N> {
+    ///
+    /// Initializes a partial block from the given compact block, filling its transactions from the given memory pool.
+    ///
+    pub fn new<'a, I: IntoIterator<Item = &'a Transaction<N>>>(compact_block: CompactBlock<N>, memory_pool: I) -> Result<Self> {
+        let transactions = compact_block.fill(memory_pool)?;
+        let uses_memory_pool = transactions.iter().flatten().count() > compact_block.prefilled_transactions.len();
+        Ok(Self {
+            compact_block,
+            transactions,
+            uses_memory_pool,
+            timestamp: Instant::now(),
+        })
+    }
+
+    /// Returns the hash of the block.
+    pub fn block_hash(&self) -> N::BlockHash {
+        self.compact_block.block_hash
+    }
+
+    /// Returns the height of the block.
+    pub fn block_height(&self) -> u32 {
+        self.compact_block.header.height()
+    }
+
+    /// Returns `true` if some of the transactions were taken from the memory pool.
+    pub fn uses_memory_pool(&self) -> bool {
+        self.uses_memory_pool
+    }
+
+    /// Returns `true` if the partial block was initialized longer ago than the given duration.
+    pub fn is_expired(&self, duration: Duration) -> bool {
+        self.timestamp.elapsed() > duration
+    }
+
+    /// Returns the indices of the missing transactions, in the order of the block.
+    pub fn missing_indices(&self) -> Vec<u32> {
+        self.transactions
+            .iter()
+            .enumerate()
+            .filter(|(_, transaction)| transaction.is_none())
+            .map(|(index, _)| index as u32)
+            .collect()
+    }
+
+    ///
+    /// Inserts the given transactions in place of the missing transactions, in the order of the block.
+    ///
+    pub fn insert_missing(&mut self, transactions: Vec<Transaction<N>>) -> Result<()> {
+        let missing_indices = self.missing_indices();
+        ensure!(
+            transactions.len() == missing_indices.len(),
+            "Received {} transactions for block {}, expected {}",
+            transactions.len(),
+            self.block_hash(),
+            missing_indices.len()
+        );
+        for (index, transaction) in missing_indices.into_iter().zip(transactions) {
+            self.transactions[index as usize] = Some(transaction);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Discards the transactions that were taken from the memory pool, so that they are requested from the peer instead.
+    /// This is a fallback for a collision of short transaction IDs in the memory pool, which yields the wrong block.
+    ///
+    pub fn discard_memory_pool(&mut self) {
+        let prefilled_indices = self
+            .compact_block
+            .prefilled_transactions
+            .iter()
+            .map(|(index, _)| *index as usize)
+            .collect::<Vec<_>>();
+        for (index, transaction) in self.transactions.iter_mut().enumerate() {
+            if !prefilled_indices.contains(&index) {
+                *transaction = None;
+            }
+        }
+        self.uses_memory_pool = false;
+    }
+
+    ///
+    /// Returns the reconstructed block, if none of its transactions are missing.
+    ///
+    pub fn to_block(&self) -> Result<Block<N>> {
+        let transactions = self
+            .transactions
+            .iter()
+            .cloned()
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| anyhow!("The block {} is missing transactions", self.block_hash()))?;
+        self.compact_block.to_block(transactions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_environment::CurrentNetwork;
+
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_compact_genesis_block() {
+        let genesis_block = CurrentNetwork::genesis_block();
+
+        // The coinbase transaction is prefilled, so the genesis block is reconstructed without a memory pool.
+        let compact_block = CompactBlock::new(genesis_block, 0).unwrap();
+        assert!(compact_block.short_ids.is_empty());
+        assert_eq!(genesis_block.transactions().len(), compact_block.number_of_transactions());
+
+        let partial_block = PartialBlock::new(compact_block, &[]).unwrap();
+        assert_eq!(genesis_block.height(), partial_block.block_height());
+        assert!(!partial_block.uses_memory_pool());
+        assert!(partial_block.missing_indices().is_empty());
+        assert_eq!(genesis_block, &partial_block.to_block().unwrap());
+    }
+
+    #[test]
+    fn test_partial_block() {
+        let rng = &mut thread_rng();
+        let address = Account::<CurrentNetwork>::new(rng).address();
+        let new_transaction = |rng: &mut _| {
+            Transaction::<CurrentNetwork>::new_coinbase(address, AleoAmount::ZERO, true, rng)
+                .unwrap()
+                .0
+        };
+        let (transaction_0, transaction_1, transaction_2) = (new_transaction(rng), new_transaction(rng), new_transaction(rng));
+
+        // Compact a block with two transactions, after a prefilled transaction.
+        let mut compact_block = CompactBlock::new(CurrentNetwork::genesis_block(), rng.gen()).unwrap();
+        for transaction in [&transaction_0, &transaction_1] {
+            let short_id = compact_block.short_id(&transaction.transaction_id()).unwrap();
+            compact_block.short_ids.push(short_id);
+        }
+        assert_eq!(3, compact_block.number_of_transactions());
+
+        // The short IDs are keyed by the salt.
+        let mut salted_block = compact_block.clone();
+        salted_block.salt = salted_block.salt.wrapping_add(1);
+        assert_ne!(
+            compact_block.short_id(&transaction_0.transaction_id()).unwrap(),
+            salted_block.short_id(&transaction_0.transaction_id()).unwrap()
+        );
+
+        // Fill the block from a memory pool that misses one of its transactions.
+        let mut partial_block = PartialBlock::new(compact_block, [&transaction_2, &transaction_1]).unwrap();
+        assert!(partial_block.uses_memory_pool());
+        assert_eq!(vec![1], partial_block.missing_indices());
+        assert!(partial_block.to_block().is_err());
+
+        // Ensure the number of missing transactions is checked, and that they are inserted in place.
+        assert!(partial_block.insert_missing(vec![]).is_err());
+        partial_block.insert_missing(vec![transaction_0]).unwrap();
+        assert!(partial_block.missing_indices().is_empty());
+
+        // Discarding the memory pool leaves only the prefilled transaction.
+        partial_block.discard_memory_pool();
+        assert!(!partial_block.uses_memory_pool());
+        assert_eq!(vec![1, 2], partial_block.missing_indices());
+    }
+}
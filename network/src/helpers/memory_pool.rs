@@ -0,0 +1,399 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkvm::{dpc::prelude::*, utilities::ToBytes};
+
+use anyhow::{anyhow, Result};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+
+///
+/// The priority of a transaction in the memory pool, which orders transactions
+/// by decreasing fee per byte, and then by increasing arrival order.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Priority {
+    /// The fee of the transaction.
+    fee: i64,
+    /// The size of the transaction in bytes.
+    size: usize,
+    /// The arrival order of the transaction.
+    sequence: u64,
+}
+
+impl Ord for Priority {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Compare the fees per byte without a division, by cross-multiplying the fees and sizes.
+        let fee_rate = self.fee as i128 * other.size as i128;
+        let other_fee_rate = other.fee as i128 * self.size as i128;
+        other_fee_rate.cmp(&fee_rate).then(self.sequence.cmp(&other.sequence))
+    }
+}
+
+impl PartialOrd for Priority {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Entry<N: Network> {
+    /// The unconfirmed transaction.
+    transaction: Transaction<N>,
+    /// The priority of the transaction.
+    priority: Priority,
+    /// The time the transaction was added to the memory pool.
+    timestamp: Instant,
+}
+
+///
+/// A pool of unconfirmed transactions, ordered by fee per byte.
+///
+/// The pool is bounded in both its total size in bytes and its number of transactions,
+/// evicting the transactions with the lowest fee per byte to make room for new ones.
+/// Transactions expire once they have been in the pool for longer than the expiry duration.
+///
+#[derive(Clone, Debug)]
+pub struct MemoryPool<N: Network> {
+    /// The unconfirmed transactions, keyed by transaction ID.
+    transactions: HashMap<N::TransactionID, Entry<N>>,
+    /// The transaction IDs, ordered by priority.
+    priorities: BTreeMap<Priority, N::TransactionID>,
+    /// The serial numbers of the unconfirmed transactions.
+    serial_numbers: HashMap<N::SerialNumber, N::TransactionID>,
+    /// The total size of the unconfirmed transactions in bytes.
+    size_in_bytes: usize,
+    /// The arrival order of the next transaction.
+    sequence: u64,
+    /// The maximum total size of the unconfirmed transactions in bytes.
+    maximum_size_in_bytes: usize,
+    /// The maximum number of unconfirmed transactions.
+    maximum_transactions: usize,
+    /// The duration after which an unconfirmed transaction expires.
+    expiry: Duration,
+}
+
+impl<N: Network> MemoryPool<N> {
+    /// Initializes a new instance of the memory pool with the given limits.
+    pub fn new(maximum_size_in_bytes: usize, maximum_transactions: usize, expiry: Duration) -> Self {
+        Self {
+            transactions: Default::default(),
+            priorities: Default::default(),
+            serial_numbers: Default::default(),
+            size_in_bytes: 0,
+            sequence: 0,
+            maximum_size_in_bytes,
+            maximum_transactions,
+            expiry,
+        }
+    }
+
+    /// Returns the number of unconfirmed transactions in the memory pool.
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// Returns `true` if the memory pool contains no unconfirmed transactions.
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// Returns the total size of the unconfirmed transactions in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        self.size_in_bytes
+    }
+
+    /// Returns `true` if the given transaction ID is in the memory pool.
+    pub fn contains_transaction(&self, transaction_id: &N::TransactionID) -> bool {
+        self.transactions.contains_key(transaction_id)
+    }
+
+    /// Returns the unexpired transactions in the memory pool, ordered by decreasing fee per byte.
+    pub fn transactions(&self) -> Vec<Transaction<N>> {
+        self.ordered_entries().map(|entry| entry.transaction.clone()).collect()
+    }
+
+    ///
+    /// Returns the unexpired transactions with the highest fee per byte, in decreasing order,
+    /// whose total size fits within the given number of bytes.
+    ///
+    pub fn select_transactions(&self, maximum_size_in_bytes: usize) -> Vec<Transaction<N>> {
+        let mut size_in_bytes = 0usize;
+        let mut transactions = Vec::new();
+        for entry in self.ordered_entries() {
+            // Stop at the first transaction that does not fit, so the selection remains a prefix in priority order.
+            size_in_bytes = size_in_bytes.saturating_add(entry.priority.size);
+            if size_in_bytes > maximum_size_in_bytes {
+                break;
+            }
+            transactions.push(entry.transaction.clone());
+        }
+        transactions
+    }
+
+    ///
+    /// Adds the given unconfirmed transaction to the memory pool, evicting the transactions
+    /// with the lowest fee per byte if the memory pool is full.
+    ///
+    pub fn add_transaction(&mut self, transaction: &Transaction<N>) -> Result<()> {
+        // Remove the expired transactions first, to make room for the new transaction.
+        self.remove_expired_transactions();
+
+        let transaction_id = transaction.transaction_id();
+        if self.transactions.contains_key(&transaction_id) {
+            return Err(anyhow!("Transaction {} already exists in the memory pool", transaction_id));
+        }
+
+        // Ensure the transaction pays a non-negative fee.
+        let fee = transaction.value_balance().0;
+        if fee < 0 {
+            return Err(anyhow!("Transaction {} has a negative fee", transaction_id));
+        }
+
+        // Ensure the transaction does not conflict with an unconfirmed transaction.
+        for serial_number in transaction.serial_numbers() {
+            if let Some(conflicting_transaction_id) = self.serial_numbers.get(serial_number) {
+                return Err(anyhow!(
+                    "Transaction {} conflicts with transaction {} on serial number {}",
+                    transaction_id,
+                    conflicting_transaction_id,
+                    serial_number
+                ));
+            }
+        }
+
+        let size = transaction.to_bytes_le()?.len();
+        if size > self.maximum_size_in_bytes {
+            return Err(anyhow!("Transaction {} exceeds the size of the memory pool", transaction_id));
+        }
+
+        let priority = Priority {
+            fee,
+            size,
+            sequence: self.sequence,
+        };
+
+        // Determine the transactions with the lowest fee per byte to evict, until the new transaction fits.
+        let mut evicted_transaction_ids = Vec::new();
+        let mut number_of_transactions = self.transactions.len();
+        let mut size_in_bytes = self.size_in_bytes;
+        let mut lowest_priorities = self.priorities.iter().rev();
+        while number_of_transactions >= self.maximum_transactions || size_in_bytes.saturating_add(size) > self.maximum_size_in_bytes {
+            let (lowest_priority, lowest_transaction_id) = match lowest_priorities.next() {
+                Some(lowest) => lowest,
+                None => return Err(anyhow!("Transaction {} does not fit in the memory pool", transaction_id)),
+            };
+            // Ensure the new transaction has a higher priority than the ones it would evict.
+            if priority > *lowest_priority {
+                return Err(anyhow!("Transaction {} has too low of a fee to enter the memory pool", transaction_id));
+            }
+            evicted_transaction_ids.push(*lowest_transaction_id);
+            number_of_transactions = number_of_transactions.saturating_sub(1);
+            size_in_bytes = size_in_bytes.saturating_sub(lowest_priority.size);
+        }
+
+        // Evict the transactions.
+        for evicted_transaction_id in evicted_transaction_ids {
+            debug!("Evicting transaction {} from the memory pool", evicted_transaction_id);
+            self.remove_transaction(&evicted_transaction_id);
+        }
+        self.sequence = self.sequence.wrapping_add(1);
+
+        // Insert the transaction.
+        for serial_number in transaction.serial_numbers() {
+            self.serial_numbers.insert(*serial_number, transaction_id);
+        }
+        self.priorities.insert(priority, transaction_id);
+        self.size_in_bytes = self.size_in_bytes.saturating_add(size);
+        self.transactions.insert(transaction_id, Entry {
+            transaction: transaction.clone(),
+            priority,
+            timestamp: Instant::now(),
+        });
+
+        Ok(())
+    }
+
+    ///
+    /// Removes the given confirmed transactions from the memory pool, along with any
+    /// unconfirmed transactions that spend the same serial numbers.
+    ///
+    pub fn remove_transactions(&mut self, transactions: &Transactions<N>) {
+        for transaction in transactions.iter() {
+            self.remove_transaction(&transaction.transaction_id());
+            for serial_number in transaction.serial_numbers() {
+                if let Some(conflicting_transaction_id) = self.serial_numbers.get(serial_number).copied() {
+                    self.remove_transaction(&conflicting_transaction_id);
+                }
+            }
+        }
+        self.remove_expired_transactions();
+    }
+
+    /// Removes all transactions from the memory pool.
+    pub fn clear(&mut self) {
+        self.transactions.clear();
+        self.priorities.clear();
+        self.serial_numbers.clear();
+        self.size_in_bytes = 0;
+    }
+
+    /// Removes the transactions that have been in the memory pool for longer than the expiry duration.
+    pub fn remove_expired_transactions(&mut self) {
+        let expired_transaction_ids: Vec<_> = self
+            .transactions
+            .iter()
+            .filter(|(_, entry)| self.is_expired(entry))
+            .map(|(transaction_id, _)| *transaction_id)
+            .collect();
+
+        for transaction_id in expired_transaction_ids {
+            debug!("Transaction {} has expired from the memory pool", transaction_id);
+            self.remove_transaction(&transaction_id);
+        }
+    }
+
+    /// Removes the given transaction ID from the memory pool.
+    fn remove_transaction(&mut self, transaction_id: &N::TransactionID) {
+        if let Some(entry) = self.transactions.remove(transaction_id) {
+            self.priorities.remove(&entry.priority);
+            for serial_number in entry.transaction.serial_numbers() {
+                self.serial_numbers.remove(serial_number);
+            }
+            self.size_in_bytes = self.size_in_bytes.saturating_sub(entry.priority.size);
+        }
+    }
+
+    /// Returns `true` if the given entry has expired.
+    fn is_expired(&self, entry: &Entry<N>) -> bool {
+        entry.timestamp.elapsed() > self.expiry
+    }
+
+    /// Returns the unexpired entries in the memory pool, ordered by decreasing fee per byte.
+    fn ordered_entries(&self) -> impl Iterator<Item = &Entry<N>> {
+        self.priorities
+            .values()
+            .filter_map(move |transaction_id| self.transactions.get(transaction_id))
+            .filter(move |entry| !self.is_expired(entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_environment::CurrentNetwork;
+
+    use rand::thread_rng;
+
+    #[test]
+    fn test_priority_ordering() {
+        let priority = |fee, size, sequence| Priority { fee, size, sequence };
+
+        // A higher fee per byte has a higher priority, regardless of the absolute fee.
+        assert!(priority(100, 10, 1) < priority(100, 20, 0));
+        assert!(priority(30, 10, 1) < priority(50, 20, 0));
+        assert!(priority(1, 1, 1) < priority(0, 1, 0));
+
+        // An equal fee per byte is ordered by arrival.
+        assert!(priority(10, 10, 0) < priority(20, 20, 1));
+        assert!(priority(20, 20, 0) < priority(10, 10, 1));
+
+        // The lowest priority is the last entry.
+        let mut priorities = BTreeMap::new();
+        priorities.insert(priority(50, 10, 0), 0);
+        priorities.insert(priority(10, 10, 1), 1);
+        priorities.insert(priority(90, 10, 2), 2);
+        assert_eq!(vec![2, 0, 1], priorities.values().copied().collect::<Vec<_>>());
+        assert_eq!(Some(&1), priorities.values().next_back());
+    }
+
+    #[test]
+    fn test_reject_negative_fee() {
+        let rng = &mut thread_rng();
+        let mut memory_pool = MemoryPool::<CurrentNetwork>::new(1024 * 1024, 16, Duration::from_secs(60));
+
+        // A coinbase transaction mints value, and therefore has a negative fee.
+        let address = Account::<CurrentNetwork>::new(rng).address();
+        let (transaction, _) = Transaction::<CurrentNetwork>::new_coinbase(address, AleoAmount::from_i64(1), true, rng).unwrap();
+        assert!(memory_pool.add_transaction(&transaction).is_err());
+        assert!(memory_pool.is_empty());
+        assert_eq!(0, memory_pool.size_in_bytes());
+        assert!(memory_pool.select_transactions(usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_limits_and_expiry() {
+        let rng = &mut thread_rng();
+        let address = Account::<CurrentNetwork>::new(rng).address();
+        let new_transaction = |rng: &mut _| Transaction::<CurrentNetwork>::new_coinbase(address, AleoAmount::ZERO, true, rng).unwrap().0;
+
+        // Initialize a memory pool that fits two transactions.
+        let mut memory_pool = MemoryPool::<CurrentNetwork>::new(1024 * 1024, 2, Duration::from_secs(60));
+        let transaction_0 = new_transaction(rng);
+        let transaction_1 = new_transaction(rng);
+        let transaction_2 = new_transaction(rng);
+        memory_pool.add_transaction(&transaction_0).unwrap();
+        memory_pool.add_transaction(&transaction_1).unwrap();
+        assert!(memory_pool.add_transaction(&transaction_0).is_err());
+        assert_eq!(transaction_0.to_bytes_le().unwrap().len() * 2, memory_pool.size_in_bytes());
+
+        // A transaction with an equal fee per byte arrived later, and does not evict the existing transactions.
+        assert!(memory_pool.add_transaction(&transaction_2).is_err());
+        assert_eq!(vec![transaction_0.clone(), transaction_1.clone()], memory_pool.transactions());
+
+        // Ensure the selection is a prefix that fits the given size.
+        let size = transaction_0.to_bytes_le().unwrap().len();
+        assert_eq!(vec![transaction_0.clone()], memory_pool.select_transactions(size + 1));
+        assert!(memory_pool.select_transactions(size - 1).is_empty());
+
+        // Ensure confirmed transactions are removed.
+        memory_pool.remove_transactions(&Transactions::from(&[transaction_0]).unwrap());
+        assert_eq!(vec![transaction_1], memory_pool.transactions());
+        memory_pool.clear();
+        assert!(memory_pool.is_empty());
+        assert_eq!(0, memory_pool.size_in_bytes());
+
+        // Ensure transactions expire.
+        let mut memory_pool = MemoryPool::<CurrentNetwork>::new(1024 * 1024, 2, Duration::ZERO);
+        memory_pool.add_transaction(&transaction_2).unwrap();
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(memory_pool.transactions().is_empty());
+        memory_pool.remove_expired_transactions();
+        assert!(memory_pool.is_empty());
+    }
+
+    #[test]
+    fn test_reject_oversized_transaction() {
+        let rng = &mut thread_rng();
+        let address = Account::<CurrentNetwork>::new(rng).address();
+        let (transaction, _) = Transaction::<CurrentNetwork>::new_coinbase(address, AleoAmount::ZERO, true, rng).unwrap();
+        let size = transaction.to_bytes_le().unwrap().len();
+
+        // Ensure a transaction that is larger than the entire memory pool is rejected.
+        let mut memory_pool = MemoryPool::<CurrentNetwork>::new(size - 1, 16, Duration::from_secs(60));
+        assert!(memory_pool.add_transaction(&transaction).is_err());
+        assert!(memory_pool.is_empty());
+        assert_eq!(0, memory_pool.size_in_bytes());
+
+        // Ensure a memory pool that fits no transactions remains empty.
+        let mut memory_pool = MemoryPool::<CurrentNetwork>::new(size, 0, Duration::from_secs(60));
+        assert!(memory_pool.add_transaction(&transaction).is_err());
+        assert!(memory_pool.is_empty());
+    }
+}
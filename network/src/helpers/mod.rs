@@ -22,3 +22,27 @@ pub use block_requests::*;
 
 pub mod circular_map;
 pub use circular_map::*;
+
+pub mod compact_block;
+pub use compact_block::*;
+
+pub mod memory_pool;
+pub use memory_pool::*;
+
+pub mod noise;
+pub use noise::*;
+
+pub mod peer_stats;
+pub use peer_stats::*;
+
+pub mod rate_limiter;
+pub use rate_limiter::*;
+
+pub mod reputation;
+pub use reputation::*;
+
+pub mod resolver;
+pub use resolver::*;
+
+pub mod subnet;
+pub use subnet::*;
@@ -0,0 +1,426 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::Message;
+use snarkos_environment::Environment;
+use snarkvm::dpc::prelude::*;
+
+use ::bytes::{Buf, Bytes, BytesMut};
+use anyhow::{anyhow, bail, Result};
+use futures::SinkExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{Read, Write},
+    path::Path,
+};
+use tokio::net::TcpStream;
+use tokio_stream::StreamExt;
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+/// The Noise protocol used to encrypt and authenticate the connections between peers.
+const NOISE_PARAMS: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+/// The maximum length of a Noise message, including its authentication tag.
+const NOISE_MAX_MESSAGE_LEN: usize = 65535;
+/// The length of the authentication tag of a Noise transport message.
+const NOISE_TAG_LEN: usize = 16;
+
+///
+/// The static public key a peer proves ownership of in the Noise handshake.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PeerIdentity(pub [u8; 32]);
+
+impl fmt::Display for PeerIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
+
+///
+/// The persistent X25519 keypair this node authenticates itself with in the Noise handshake.
+///
+#[derive(Clone)]
+pub struct NodeIdentity {
+    private_key: [u8; 32],
+    public_key: [u8; 32],
+}
+
+impl NodeIdentity {
+    /// Samples a new node identity.
+    pub fn new() -> Result<Self> {
+        let keypair = snow::Builder::new(NOISE_PARAMS.parse()?).generate_keypair()?;
+
+        let mut private_key = [0u8; 32];
+        private_key.copy_from_slice(&keypair.private);
+        let mut public_key = [0u8; 32];
+        public_key.copy_from_slice(&keypair.public);
+
+        Ok(Self { private_key, public_key })
+    }
+
+    ///
+    /// Loads the node identity from the given path, or samples a new one and stores it at the path if it does not exist.
+    ///
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+
+        if path.exists() {
+            let mut bytes = Vec::with_capacity(64);
+            File::open(path)?.read_to_end(&mut bytes)?;
+            if bytes.len() != 64 {
+                bail!("Invalid node identity at {}, expected 64 bytes and found {}", path.display(), bytes.len());
+            }
+
+            let mut private_key = [0u8; 32];
+            private_key.copy_from_slice(&bytes[..32]);
+            let mut public_key = [0u8; 32];
+            public_key.copy_from_slice(&bytes[32..]);
+
+            return Ok(Self { private_key, public_key });
+        }
+
+        let identity = Self::new()?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        // Ensure the private key is only readable by the owner.
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options.open(path)?;
+        file.write_all(&identity.private_key)?;
+        file.write_all(&identity.public_key)?;
+        file.sync_all()?;
+
+        Ok(identity)
+    }
+
+    /// Returns the public identity of this node.
+    pub fn public_key(&self) -> PeerIdentity {
+        PeerIdentity(self.public_key)
+    }
+}
+
+impl fmt::Debug for NodeIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Avoid leaking the key material into the logs.
+        write!(f, "NodeIdentity({})", self.public_key())
+    }
+}
+
+///
+/// The codec for the messages exchanged with a peer, which encrypts them
+/// once the Noise handshake with the peer has completed.
+///
+/// An encrypted message is split into chunks that fit in a Noise message,
+/// which are each prefixed with their length as a little-endian `u16`.
+///
+pub(crate) struct PeerCodec<N: Network, E: Environment> {
+    /// The codec for the plaintext messages.
+    codec: Message<N, E>,
+    /// The Noise transport, if the connection is encrypted.
+    transport: Option<Box<snow::TransportState>>,
+    /// The buffer to encrypt and decrypt the Noise messages with.
+    buffer: Vec<u8>,
+    /// The decrypted bytes that do not yet form a complete message.
+    plaintext: BytesMut,
+    /// The number of bytes received from the peer.
+    bytes_received: u64,
+    /// The number of bytes sent to the peer.
+    bytes_sent: u64,
+}
+
+impl<N: Network, E: Environment> PeerCodec<N, E> {
+    /// Initializes a new instance of `PeerCodec`, which starts out unencrypted.
+    pub(crate) fn new() -> Self {
+        Self {
+            codec: Message::PeerRequest,
+            transport: None,
+            buffer: Vec::new(),
+            plaintext: BytesMut::new(),
+            bytes_received: 0,
+            bytes_sent: 0,
+        }
+    }
+
+    /// Returns the number of bytes received from the peer, as they were on the wire.
+    pub(crate) fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
+    /// Returns the number of bytes sent to the peer, as they were on the wire.
+    pub(crate) fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
+    /// Encrypts all subsequent messages with the given Noise transport.
+    fn set_transport(&mut self, transport: snow::TransportState) {
+        self.transport = Some(Box::new(transport));
+        self.buffer = vec![0u8; NOISE_MAX_MESSAGE_LEN];
+    }
+
+    /// Encodes the given message, encrypting it if the connection is encrypted.
+    fn encode_message(&mut self, message: Message<N, E>, dst: &mut BytesMut) -> Result<()> {
+        let Self { codec, transport, buffer, .. } = self;
+
+        let transport = match transport {
+            Some(transport) => transport,
+            None => return codec.encode(message, dst),
+        };
+
+        // Serialize the length-prefixed message, then encrypt it in chunks.
+        let mut plaintext = BytesMut::new();
+        codec.encode(message, &mut plaintext)?;
+
+        for chunk in plaintext.chunks(NOISE_MAX_MESSAGE_LEN - NOISE_TAG_LEN) {
+            let length = transport.write_message(chunk, buffer)?;
+            dst.extend_from_slice(&(length as u16).to_le_bytes());
+            dst.extend_from_slice(&buffer[..length]);
+        }
+
+        Ok(())
+    }
+
+    /// Decodes the next message, decrypting it if the connection is encrypted.
+    fn decode_message(&mut self, source: &mut BytesMut) -> Result<Option<Message<N, E>>, std::io::Error> {
+        let Self {
+            codec,
+            transport,
+            buffer,
+            plaintext,
+            ..
+        } = self;
+
+        let transport = match transport {
+            Some(transport) => transport,
+            None => return codec.decode(source),
+        };
+
+        // Decrypt every chunk that has fully arrived.
+        while source.len() >= 2 {
+            let length = u16::from_le_bytes([source[0], source[1]]) as usize;
+            if source.len() < 2 + length {
+                source.reserve(2 + length - source.len());
+                break;
+            }
+
+            let plaintext_length = transport
+                .read_message(&source[2..][..length], buffer)
+                .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+            plaintext.extend_from_slice(&buffer[..plaintext_length]);
+
+            source.advance(2 + length);
+        }
+
+        // The plaintext codec enforces the maximum message size.
+        codec.decode(plaintext)
+    }
+}
+
+impl<N: Network, E: Environment> Encoder<Message<N, E>> for PeerCodec<N, E> {
+    type Error = anyhow::Error;
+
+    fn encode(&mut self, message: Message<N, E>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let initial_length = dst.len();
+        self.encode_message(message, dst)?;
+        self.bytes_sent += (dst.len() - initial_length) as u64;
+        Ok(())
+    }
+}
+
+impl<N: Network, E: Environment> Decoder for PeerCodec<N, E> {
+    type Error = std::io::Error;
+    type Item = Message<N, E>;
+
+    fn decode(&mut self, source: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let initial_length = source.len();
+        let message = self.decode_message(source);
+        self.bytes_received += (initial_length - source.len()) as u64;
+        message
+    }
+}
+
+///
+/// Performs the Noise XX handshake with the peer, after which all messages on the socket are encrypted.
+/// The node with the lower nonce initiates the handshake, and both serialized challenge requests are
+/// bound into it as the prologue, so a challenge request that was tampered with fails the handshake.
+///
+/// Returns the identity of the peer upon success.
+///
+pub(crate) async fn noise_handshake<N: Network, E: Environment>(
+    socket: &mut Framed<TcpStream, PeerCodec<N, E>>,
+    identity: &NodeIdentity,
+    (local_nonce, local_challenge_request): (u64, &[u8]),
+    (peer_nonce, peer_challenge_request): (u64, &[u8]),
+) -> Result<PeerIdentity> {
+    let peer_ip = socket.get_ref().peer_addr()?;
+
+    // The challenge request of the initiator precedes the one of the responder.
+    let (first_challenge_request, second_challenge_request) = match local_nonce < peer_nonce {
+        true => (local_challenge_request, peer_challenge_request),
+        false => (peer_challenge_request, local_challenge_request),
+    };
+    let mut prologue = Vec::with_capacity(first_challenge_request.len() + second_challenge_request.len());
+    prologue.extend_from_slice(first_challenge_request);
+    prologue.extend_from_slice(second_challenge_request);
+
+    let builder = snow::Builder::new(NOISE_PARAMS.parse()?)
+        .local_private_key(&identity.private_key)
+        .prologue(&prologue);
+    let mut state = match local_nonce < peer_nonce {
+        true => builder.build_initiator()?,
+        false => builder.build_responder()?,
+    };
+
+    let mut buffer = vec![0u8; NOISE_MAX_MESSAGE_LEN];
+    while !state.is_handshake_finished() {
+        if state.is_my_turn() {
+            let length = state.write_message(&[], &mut buffer)?;
+            let message = Message::NoiseHandshake(Bytes::copy_from_slice(&buffer[..length]));
+            trace!("Sending '{}' to {}", message.name(), peer_ip);
+            socket.send(message).await?;
+        } else {
+            match socket.next().await {
+                Some(Ok(Message::NoiseHandshake(handshake_message))) => {
+                    trace!("Received 'NoiseHandshake' from {}", peer_ip);
+                    state.read_message(&handshake_message, &mut buffer)?;
+                }
+                Some(Ok(Message::Disconnect(reason))) => {
+                    bail!("Peer {} disconnected for the following reason: {:?}", peer_ip, reason);
+                }
+                Some(Ok(message)) => bail!("Expected noise handshake, received '{}' from {}", message.name(), peer_ip),
+                Some(Err(error)) => bail!("Failed to get noise handshake from {}: {:?}", peer_ip, error),
+                None => bail!("Dropped prior to noise handshake of {}", peer_ip),
+            }
+        }
+    }
+
+    let mut peer_identity = [0u8; 32];
+    match state.get_remote_static() {
+        Some(remote_static) if remote_static.len() == 32 => peer_identity.copy_from_slice(remote_static),
+        _ => return Err(anyhow!("Noise handshake with {} did not yield a static key", peer_ip)),
+    }
+
+    socket.codec_mut().set_transport(state.into_transport_mode()?);
+
+    Ok(PeerIdentity(peer_identity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_environment::{Client, CurrentNetwork};
+
+    type Codec = PeerCodec<CurrentNetwork, Client<CurrentNetwork>>;
+
+    /// Returns a pair of codecs that share the transport keys of a completed handshake.
+    fn encrypted_codecs() -> (Codec, Codec) {
+        let (initiator_identity, responder_identity) = (NodeIdentity::new().unwrap(), NodeIdentity::new().unwrap());
+        let params: snow::params::NoiseParams = NOISE_PARAMS.parse().unwrap();
+
+        let mut initiator = snow::Builder::new(params.clone())
+            .local_private_key(&initiator_identity.private_key)
+            .build_initiator()
+            .unwrap();
+        let mut responder = snow::Builder::new(params)
+            .local_private_key(&responder_identity.private_key)
+            .build_responder()
+            .unwrap();
+
+        let (mut message, mut payload) = (vec![0u8; NOISE_MAX_MESSAGE_LEN], vec![0u8; NOISE_MAX_MESSAGE_LEN]);
+        while !initiator.is_handshake_finished() || !responder.is_handshake_finished() {
+            let (sender, receiver) = match initiator.is_my_turn() {
+                true => (&mut initiator, &mut responder),
+                false => (&mut responder, &mut initiator),
+            };
+            let length = sender.write_message(&[], &mut message).unwrap();
+            receiver.read_message(&message[..length], &mut payload).unwrap();
+        }
+        assert_eq!(initiator.get_remote_static().unwrap(), &responder_identity.public_key);
+        assert_eq!(responder.get_remote_static().unwrap(), &initiator_identity.public_key);
+
+        let (mut encoder, mut decoder) = (Codec::new(), Codec::new());
+        encoder.set_transport(initiator.into_transport_mode().unwrap());
+        decoder.set_transport(responder.into_transport_mode().unwrap());
+        (encoder, decoder)
+    }
+
+    #[test]
+    fn test_encrypted_codec() {
+        let (mut encoder, mut decoder) = encrypted_codecs();
+
+        // A message spanning several Noise messages.
+        let peer_ips = (0..20_000u32).map(|i| format!("127.0.0.1:{}", i % 65535).parse().unwrap()).collect::<Vec<_>>();
+
+        let mut bytes = BytesMut::new();
+        encoder.encode(Message::PeerResponse(peer_ips.clone()), &mut bytes).unwrap();
+        encoder.encode(Message::BlockRequest(1, 10), &mut bytes).unwrap();
+
+        // The plaintext is not visible on the wire.
+        let mut plaintext = BytesMut::new();
+        Codec::new().encode(Message::PeerResponse(peer_ips.clone()), &mut plaintext).unwrap();
+        assert!(bytes.windows(64).all(|window| window != &plaintext[8..72]));
+
+        // Feed the bytes to the decoder in small pieces.
+        let mut source = BytesMut::new();
+        let mut messages = vec![];
+        for chunk in bytes.chunks(1000) {
+            source.extend_from_slice(chunk);
+            while let Some(message) = decoder.decode(&mut source).unwrap() {
+                messages.push(message);
+            }
+        }
+
+        assert_eq!(messages.len(), 2);
+        match &messages[0] {
+            Message::PeerResponse(decoded) => assert_eq!(decoded, &peer_ips),
+            message => panic!("Unexpected message {}", message.name()),
+        }
+        assert!(matches!(messages[1], Message::BlockRequest(1, 10)));
+    }
+
+    #[test]
+    fn test_tampered_message() {
+        let (mut encoder, mut decoder) = encrypted_codecs();
+
+        let mut bytes = BytesMut::new();
+        encoder.encode(Message::BlockRequest(1, 10), &mut bytes).unwrap();
+
+        // Flip a bit of the ciphertext.
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+
+        assert!(decoder.decode(&mut bytes).is_err());
+    }
+
+    #[test]
+    fn test_node_identity() {
+        let path = std::env::temp_dir().join(format!("snarkos-test-identity-{}", rand::random::<u64>()));
+
+        let identity = NodeIdentity::open(&path).unwrap();
+        let reopened = NodeIdentity::open(&path).unwrap();
+        assert_eq!(identity.public_key(), reopened.public_key());
+        assert_eq!(identity.private_key, reopened.private_key);
+        assert_eq!(identity.public_key().to_string().len(), 64);
+
+        fs::remove_file(path).unwrap();
+    }
+}
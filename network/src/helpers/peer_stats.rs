@@ -0,0 +1,144 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_environment::helpers::{NodeType, State};
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use time::OffsetDateTime;
+
+///
+/// The direction of a connection with a peer.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionDirection {
+    /// The peer connected to this node.
+    Inbound,
+    /// This node connected to the peer.
+    Outbound,
+}
+
+///
+/// The traffic of one message type with a peer.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct MessageTraffic {
+    /// The number of messages.
+    pub messages: u64,
+    /// The number of bytes, as they were on the wire.
+    pub bytes: u64,
+}
+
+///
+/// The statistics of a connected peer, which are tracked by its handler and reported to the peers.
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct PeerStats {
+    /// The node type of the peer.
+    pub node_type: NodeType,
+    /// The state of the peer.
+    pub state: State,
+    /// The message version of the peer.
+    pub version: u32,
+    /// The direction of the connection with the peer.
+    pub direction: ConnectionDirection,
+    /// The UNIX timestamp of the connection with the peer.
+    pub connected_since: i64,
+    /// The UNIX timestamp of the last message received from the peer.
+    pub last_seen: i64,
+    /// The round-trip time of the last `Ping` to the peer, in milliseconds.
+    pub latency_in_millis: Option<u64>,
+    /// The latest block height of the peer, as known to the ledger.
+    pub block_height: Option<u32>,
+    /// The number of bytes sent to the peer.
+    pub bytes_sent: u64,
+    /// The number of bytes received from the peer.
+    pub bytes_received: u64,
+    /// The traffic sent to the peer, by message type.
+    pub messages_sent: BTreeMap<String, MessageTraffic>,
+    /// The traffic received from the peer, by message type.
+    pub messages_received: BTreeMap<String, MessageTraffic>,
+}
+
+impl PeerStats {
+    ///
+    /// Initializes the statistics of a peer that has just connected.
+    ///
+    pub fn new(node_type: NodeType, state: State, direction: ConnectionDirection) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        Self {
+            node_type,
+            state,
+            version: 0,
+            direction,
+            connected_since: now,
+            last_seen: now,
+            latency_in_millis: None,
+            block_height: None,
+            bytes_sent: 0,
+            bytes_received: 0,
+            messages_sent: Default::default(),
+            messages_received: Default::default(),
+        }
+    }
+
+    ///
+    /// Records a message of the given name and size in bytes that was sent to the peer.
+    ///
+    pub fn record_sent(&mut self, message_name: &str, message_size: u64) {
+        self.bytes_sent += message_size;
+        Self::record(&mut self.messages_sent, message_name, message_size);
+    }
+
+    ///
+    /// Records a message of the given name and size in bytes that was received from the peer.
+    ///
+    pub fn record_received(&mut self, message_name: &str, message_size: u64) {
+        self.bytes_received += message_size;
+        self.last_seen = OffsetDateTime::now_utc().unix_timestamp();
+        Self::record(&mut self.messages_received, message_name, message_size);
+    }
+
+    /// Adds a message of the given name and size to the given traffic.
+    fn record(traffic: &mut BTreeMap<String, MessageTraffic>, message_name: &str, message_size: u64) {
+        let traffic = match traffic.get_mut(message_name) {
+            Some(traffic) => traffic,
+            None => traffic.entry(message_name.to_string()).or_default(),
+        };
+        traffic.messages += 1;
+        traffic.bytes += message_size;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_stats() {
+        let mut stats = PeerStats::new(NodeType::Client, State::Ready, ConnectionDirection::Outbound);
+        stats.record_sent("Ping", 100);
+        stats.record_sent("Ping", 100);
+        stats.record_received("Pong", 300);
+
+        assert_eq!(200, stats.bytes_sent);
+        assert_eq!(300, stats.bytes_received);
+        assert_eq!(MessageTraffic { messages: 2, bytes: 200 }, stats.messages_sent["Ping"]);
+        assert_eq!(MessageTraffic { messages: 1, bytes: 300 }, stats.messages_received["Pong"]);
+        assert!(!stats.messages_received.contains_key("Ping"));
+    }
+}
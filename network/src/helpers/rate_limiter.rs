@@ -0,0 +1,182 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_environment::Environment;
+
+use anyhow::{bail, Result};
+use std::{collections::HashMap, time::Instant};
+
+///
+/// A token bucket, which refills at a constant rate up to its capacity,
+/// and from which each unit of usage consumes one token.
+///
+#[derive(Clone, Debug)]
+pub struct TokenBucket {
+    /// The maximum number of tokens, which bounds the size of a burst.
+    capacity: f64,
+    /// The number of tokens that are currently available.
+    tokens: f64,
+    /// The number of tokens that are added per second.
+    refill_per_sec: f64,
+    /// The timestamp of the last refill.
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    ///
+    /// Initializes a new instance of a full token bucket.
+    ///
+    pub fn new(refill_per_sec: u64, capacity: u64) -> Self {
+        Self {
+            capacity: capacity as f64,
+            tokens: capacity as f64,
+            refill_per_sec: refill_per_sec as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    ///
+    /// Attempts to consume the given number of tokens at the given time, returning `true` on success.
+    /// If there are not enough tokens, none are consumed.
+    ///
+    pub fn try_consume(&mut self, amount: u64, now: Instant) -> bool {
+        // Refill the tokens that were added since the last refill.
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+
+        match self.tokens >= amount as f64 {
+            true => {
+                self.tokens -= amount as f64;
+                true
+            }
+            false => false,
+        }
+    }
+}
+
+///
+/// The limits on the rate of messages and bytes that are received from a peer.
+///
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    /// The token bucket of each message type with a dedicated rate limit.
+    messages: HashMap<&'static str, TokenBucket>,
+    /// The token bucket shared by the message types without a dedicated rate limit.
+    other_messages: TokenBucket,
+    /// The token bucket of the bytes received.
+    bytes: TokenBucket,
+}
+
+impl RateLimiter {
+    ///
+    /// Initializes a new instance of a rate limiter, with the limits of the given environment.
+    ///
+    pub fn new<E: Environment>() -> Self {
+        // The overrides of the node type replace the shared rate limits of the same messages.
+        let messages = E::MESSAGE_RATE_LIMITS
+            .iter()
+            .chain(E::MESSAGE_RATE_LIMIT_OVERRIDES)
+            .map(|(name, messages_per_sec, burst)| (*name, TokenBucket::new(*messages_per_sec as u64, *burst as u64)))
+            .collect();
+        let (messages_per_sec, burst) = E::DEFAULT_MESSAGE_RATE_LIMIT;
+
+        Self {
+            messages,
+            other_messages: TokenBucket::new(messages_per_sec as u64, burst as u64),
+            // A burst must allow for a message of the maximum size.
+            bytes: TokenBucket::new(E::MAXIMUM_BYTES_PER_SEC_PER_PEER, E::MAXIMUM_MESSAGE_SIZE as u64),
+        }
+    }
+
+    ///
+    /// Records a received message of the given name and size in bytes,
+    /// returning an error if the peer has exceeded its rate limits.
+    ///
+    pub fn check(&mut self, message_name: &str, message_size: u64) -> Result<()> {
+        self.check_at(message_name, message_size, Instant::now())
+    }
+
+    /// Records a received message at the given time, returning an error if the peer has exceeded its rate limits.
+    fn check_at(&mut self, message_name: &str, message_size: u64, now: Instant) -> Result<()> {
+        let bucket = match self.messages.get_mut(message_name) {
+            Some(bucket) => bucket,
+            None => &mut self.other_messages,
+        };
+        if !bucket.try_consume(1, now) {
+            bail!("Exceeded the message rate limit for '{}'", message_name);
+        }
+        if !self.bytes.try_consume(message_size, now) {
+            bail!("Exceeded the bandwidth limit with '{}' of {} bytes", message_name, message_size);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_environment::{Client, CurrentNetwork, Operator, SyncNode};
+
+    use std::time::Duration;
+
+    #[test]
+    fn test_token_bucket() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2, 4);
+
+        // The bucket starts out full, and allows for a burst of its capacity.
+        assert!(bucket.try_consume(4, start));
+        assert!(!bucket.try_consume(1, start));
+
+        // The bucket refills at a constant rate, up to its capacity.
+        assert!(bucket.try_consume(1, start + Duration::from_millis(500)));
+        assert!(!bucket.try_consume(1, start + Duration::from_millis(500)));
+        assert!(bucket.try_consume(4, start + Duration::from_secs(60)));
+        assert!(!bucket.try_consume(1, start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let now = Instant::now();
+        let mut rate_limiter = RateLimiter::new::<Client<CurrentNetwork>>();
+
+        // A flood of `PeerRequest` messages is limited, without limiting the other message types.
+        let (_, burst) = Client::<CurrentNetwork>::message_rate_limit("PeerRequest").unwrap();
+        for _ in 0..burst {
+            assert!(rate_limiter.check_at("PeerRequest", 1, now).is_ok());
+        }
+        assert!(rate_limiter.check_at("PeerRequest", 1, now).is_err());
+        assert!(rate_limiter.check_at("Ping", 1, now).is_ok());
+
+        // A message larger than the remaining bandwidth is limited.
+        let message_size = Client::<CurrentNetwork>::MAXIMUM_MESSAGE_SIZE as u64;
+        assert!(rate_limiter.check_at("BlockResponse", message_size, now).is_err());
+    }
+
+    #[test]
+    fn test_rate_limits_per_node_type() {
+        let client = Client::<CurrentNetwork>::message_rate_limit;
+        let operator = Operator::<CurrentNetwork>::message_rate_limit;
+        let sync_node = SyncNode::<CurrentNetwork>::message_rate_limit;
+
+        // A sync node serves more block requests than a client.
+        assert!(sync_node("BlockRequest") > client("BlockRequest"));
+        // An operator accepts more pool responses than a client, and shares the other rate limits.
+        assert!(operator("PoolResponse") > client("PoolResponse"));
+        assert_eq!(operator("Ping"), client("Ping"));
+    }
+}
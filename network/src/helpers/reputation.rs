@@ -0,0 +1,160 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+///
+/// A typed misbehavior of a peer, along with a description of the offense.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Misbehavior {
+    /// The peer sent a block that failed validation.
+    InvalidBlock(String),
+    /// The peer requested more data than is permitted.
+    OversizeRequest(String),
+    /// The peer sent a share that failed verification.
+    InvalidShare(String),
+    /// The peer sent a message that could not be deserialized.
+    BadDeserialization(String),
+    /// The peer sent a message that is invalid in the current state of the protocol.
+    ProtocolViolation(String),
+    /// The peer exceeded its rate limits for messages or bandwidth.
+    RateLimitExceeded(String),
+}
+
+impl Misbehavior {
+    ///
+    /// Returns the weight of the misbehavior, which is added to the score of the offending peer.
+    ///
+    pub fn weight(&self) -> f64 {
+        match self {
+            Self::InvalidBlock(..) => 50.0,
+            Self::OversizeRequest(..) => 20.0,
+            Self::InvalidShare(..) => 10.0,
+            Self::BadDeserialization(..) => 20.0,
+            Self::ProtocolViolation(..) => 2.0,
+            Self::RateLimitExceeded(..) => 5.0,
+        }
+    }
+
+    ///
+    /// Returns the name of the misbehavior.
+    ///
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::InvalidBlock(..) => "InvalidBlock",
+            Self::OversizeRequest(..) => "OversizeRequest",
+            Self::InvalidShare(..) => "InvalidShare",
+            Self::BadDeserialization(..) => "BadDeserialization",
+            Self::ProtocolViolation(..) => "ProtocolViolation",
+            Self::RateLimitExceeded(..) => "RateLimitExceeded",
+        }
+    }
+}
+
+impl fmt::Display for Misbehavior {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidBlock(description)
+            | Self::OversizeRequest(description)
+            | Self::InvalidShare(description)
+            | Self::BadDeserialization(description)
+            | Self::ProtocolViolation(description)
+            | Self::RateLimitExceeded(description) => write!(f, "{} ({})", description, self.name()),
+        }
+    }
+}
+
+///
+/// The reputation of a peer, as the set of its recent misbehaviors := (misbehavior, timestamp).
+///
+#[derive(Clone, Debug, Default)]
+pub struct Reputation {
+    misbehaviors: Vec<(Misbehavior, i64)>,
+}
+
+impl Reputation {
+    ///
+    /// Records the given misbehavior at the given timestamp.
+    ///
+    pub fn add(&mut self, misbehavior: Misbehavior, timestamp: i64) {
+        self.misbehaviors.push((misbehavior, timestamp));
+    }
+
+    ///
+    /// Returns the misbehavior score at the given timestamp, where the weight
+    /// of each misbehavior halves every `half_life_in_secs`.
+    ///
+    pub fn score(&self, now: i64, half_life_in_secs: u64) -> f64 {
+        self.misbehaviors
+            .iter()
+            .map(|(misbehavior, timestamp)| {
+                let age = now.saturating_sub(*timestamp).max(0) as f64;
+                misbehavior.weight() * 0.5f64.powf(age / half_life_in_secs.max(1) as f64)
+            })
+            .sum()
+    }
+
+    ///
+    /// Removes the misbehaviors that are older than `expiry_in_secs`.
+    ///
+    pub fn remove_expired(&mut self, now: i64, expiry_in_secs: u64) {
+        self.misbehaviors.retain(|(_, timestamp)| now.saturating_sub(*timestamp) < expiry_in_secs as i64);
+    }
+
+    ///
+    /// Returns the recorded misbehaviors of the peer.
+    ///
+    pub fn misbehaviors(&self) -> &[(Misbehavior, i64)] {
+        &self.misbehaviors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reputation_decay() {
+        let mut reputation = Reputation::default();
+        assert_eq!(reputation.score(0, 100), 0.0);
+
+        reputation.add(Misbehavior::InvalidBlock("Invalid block".to_string()), 0);
+        reputation.add(Misbehavior::ProtocolViolation("Unexpected message".to_string()), 0);
+        assert_eq!(reputation.score(0, 100), 52.0);
+
+        // Check that the score halves after each half life.
+        assert_eq!(reputation.score(100, 100), 26.0);
+        assert_eq!(reputation.score(200, 100), 13.0);
+    }
+
+    #[test]
+    fn test_reputation_expiry() {
+        let mut reputation = Reputation::default();
+        reputation.add(Misbehavior::InvalidShare("Invalid share".to_string()), 0);
+        reputation.add(Misbehavior::OversizeRequest("Oversize request".to_string()), 50);
+
+        reputation.remove_expired(100, 100);
+        assert_eq!(reputation.misbehaviors().len(), 1);
+        assert_eq!(reputation.misbehaviors()[0].0.name(), "OversizeRequest");
+    }
+
+    #[test]
+    fn test_misbehavior_display() {
+        let misbehavior = Misbehavior::BadDeserialization("Malformed message".to_string());
+        assert_eq!(misbehavior.to_string(), "Malformed message (BadDeserialization)");
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::{anyhow, bail, Error, Result};
+use futures::future::{BoxFuture, FutureExt};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fmt,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+    sync::{PoisonError, RwLock},
+};
+
+/// Resolves hostnames to IP addresses.
+pub trait Resolver: Send + Sync {
+    /// Returns the IP addresses of the A and AAAA records of the given hostname.
+    fn lookup<'a>(&'a self, host: &'a str) -> BoxFuture<'a, Result<Vec<IpAddr>>>;
+}
+
+/// The resolver of the operating system.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn lookup<'a>(&'a self, host: &'a str) -> BoxFuture<'a, Result<Vec<IpAddr>>> {
+        async move {
+            // The lookup requires a port, which does not affect the resolved IP addresses.
+            let addrs = tokio::net::lookup_host((host, 0)).await?;
+            Ok(addrs.map(|addr| addr.ip()).collect())
+        }
+        .boxed()
+    }
+}
+
+/// A resolver with a fixed set of records, which may be updated, e.g. to stub out DNS in tests.
+#[derive(Debug, Default)]
+pub struct StaticResolver {
+    records: RwLock<HashMap<String, Vec<IpAddr>>>,
+}
+
+impl StaticResolver {
+    /// Sets the IP addresses of the given hostname.
+    pub fn insert(&self, host: &str, ips: Vec<IpAddr>) {
+        self.records.write().unwrap_or_else(PoisonError::into_inner).insert(host.to_string(), ips);
+    }
+}
+
+impl Resolver for StaticResolver {
+    fn lookup<'a>(&'a self, host: &'a str) -> BoxFuture<'a, Result<Vec<IpAddr>>> {
+        let ips = self.records.read().unwrap_or_else(PoisonError::into_inner).get(host).cloned();
+        async move { ips.ok_or_else(|| anyhow!("Failed to resolve '{}'", host)) }.boxed()
+    }
+}
+
+///
+/// The address of a peer, given either as an IP address or as a hostname, along with its port.
+/// A hostname is resolved each time the address is used, so that it follows changes to its records.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub enum PeerAddr {
+    /// An IP address := (ip)
+    Ip(SocketAddr),
+    /// A hostname := (host, port)
+    Host(String, u16),
+}
+
+impl PeerAddr {
+    /// Parses the given address, using the given port if the address is a hostname without one.
+    pub fn parse_with_default_port(addr: &str, default_port: u16) -> Result<Self> {
+        match addr.parse::<SocketAddr>().is_err() && !addr.contains(':') {
+            true => Self::host(addr, default_port),
+            false => addr.parse(),
+        }
+    }
+
+    /// Returns the IP addresses of the peer, resolving its hostname with the given resolver.
+    pub async fn resolve(&self, resolver: &dyn Resolver) -> Result<Vec<SocketAddr>> {
+        match self {
+            Self::Ip(ip) => Ok(vec![*ip]),
+            Self::Host(host, port) => {
+                let ips = resolver.lookup(host).await?;
+                match ips.is_empty() {
+                    true => Err(anyhow!("'{}' has no IP addresses", host)),
+                    false => Ok(ips.into_iter().map(|ip| SocketAddr::new(ip, *port)).collect()),
+                }
+            }
+        }
+    }
+
+    fn host(host: &str, port: u16) -> Result<Self> {
+        // IPv6 addresses must be given with their port, as in `[::1]:4132`.
+        if host.is_empty() || host.contains(':') || host.contains('[') {
+            bail!("Invalid hostname '{}'", host)
+        }
+        Ok(Self::Host(host.to_string(), port))
+    }
+}
+
+impl FromStr for PeerAddr {
+    type Err = Error;
+
+    fn from_str(addr: &str) -> Result<Self> {
+        if let Ok(ip) = addr.parse() {
+            return Ok(Self::Ip(ip));
+        }
+        let (host, port) = addr.rsplit_once(':').ok_or_else(|| anyhow!("Missing the port of '{}'", addr))?;
+        let port = port.parse().map_err(|_| anyhow!("Invalid port in '{}'", addr))?;
+        Self::host(host, port)
+    }
+}
+
+impl TryFrom<String> for PeerAddr {
+    type Error = Error;
+
+    fn try_from(addr: String) -> Result<Self> {
+        addr.parse()
+    }
+}
+
+impl fmt::Display for PeerAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ip(ip) => write!(f, "{}", ip),
+            Self::Host(host, port) => write!(f, "{}:{}", host, port),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_peer_addr() {
+        assert_eq!(PeerAddr::Ip("127.0.0.1:4132".parse().unwrap()), "127.0.0.1:4132".parse().unwrap());
+        assert_eq!(PeerAddr::Ip("[::1]:4132".parse().unwrap()), "[::1]:4132".parse().unwrap());
+        assert_eq!(PeerAddr::Host("pool.example.com".to_string(), 4132), "pool.example.com:4132".parse().unwrap());
+        assert_eq!("pool.example.com:4132", PeerAddr::Host("pool.example.com".to_string(), 4132).to_string());
+
+        assert!("pool.example.com".parse::<PeerAddr>().is_err());
+        assert!("pool.example.com:port".parse::<PeerAddr>().is_err());
+        assert!("::1:4132".parse::<PeerAddr>().is_err());
+        assert!(":4132".parse::<PeerAddr>().is_err());
+
+        let seed = PeerAddr::parse_with_default_port("seed.example.com", 4132).unwrap();
+        assert_eq!(PeerAddr::Host("seed.example.com".to_string(), 4132), seed);
+        let seed = PeerAddr::parse_with_default_port("seed.example.com:4133", 4132).unwrap();
+        assert_eq!(PeerAddr::Host("seed.example.com".to_string(), 4133), seed);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_peer_addr() {
+        let resolver = StaticResolver::default();
+        let peer_addr: PeerAddr = "pool.example.com:4132".parse().unwrap();
+        assert!(peer_addr.resolve(&resolver).await.is_err());
+
+        // The hostname is resolved each time, following changes to its records.
+        resolver.insert("pool.example.com", vec!["10.0.0.1".parse().unwrap(), "::2".parse().unwrap()]);
+        let expected: Vec<SocketAddr> = vec!["10.0.0.1:4132".parse().unwrap(), "[::2]:4132".parse().unwrap()];
+        assert_eq!(expected, peer_addr.resolve(&resolver).await.unwrap());
+
+        resolver.insert("pool.example.com", vec!["10.0.0.3".parse().unwrap()]);
+        assert_eq!(vec!["10.0.0.3:4132".parse::<SocketAddr>().unwrap()], peer_addr.resolve(&resolver).await.unwrap());
+
+        // An IP address is not resolved.
+        let peer_addr: PeerAddr = "127.0.0.1:4132".parse().unwrap();
+        assert_eq!(vec!["127.0.0.1:4132".parse::<SocketAddr>().unwrap()], peer_addr.resolve(&resolver).await.unwrap());
+    }
+}
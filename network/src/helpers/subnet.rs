@@ -0,0 +1,252 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_environment::Environment;
+
+use std::{
+    collections::HashMap,
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+///
+/// A subnet of IP addresses, by which the connections of a node are grouped,
+/// so that a single network operator cannot take all of its connections.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Subnet {
+    /// A /24 IPv4 subnet.
+    Ipv4Slash24([u8; 3]),
+    /// A /16 IPv4 subnet.
+    Ipv4Slash16([u8; 2]),
+    /// A /48 IPv6 subnet.
+    Ipv6Slash48([u16; 3]),
+}
+
+impl Subnet {
+    ///
+    /// Returns the subnets containing the given IP, from the narrowest to the widest.
+    /// Local IPs, such as loopback and private IPs, are not grouped into subnets.
+    ///
+    pub fn of(ip: IpAddr) -> Vec<Self> {
+        match canonical(ip) {
+            IpAddr::V4(ip) if !is_local_ipv4(&ip) => {
+                let [a, b, c, _] = ip.octets();
+                vec![Self::Ipv4Slash24([a, b, c]), Self::Ipv4Slash16([a, b])]
+            }
+            IpAddr::V6(ip) if !is_local_ipv6(&ip) => {
+                let [a, b, c, ..] = ip.segments();
+                vec![Self::Ipv6Slash48([a, b, c])]
+            }
+            _ => vec![],
+        }
+    }
+
+    ///
+    /// Returns the widest subnet containing the given IP, across which outbound connections are diversified.
+    ///
+    pub fn group(ip: IpAddr) -> Option<Self> {
+        Self::of(ip).pop()
+    }
+
+    /// Returns `true` if the given IP is in this subnet.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        Self::of(ip).contains(self)
+    }
+
+    /// Returns the maximum number of inbound connections permitted from this subnet.
+    pub fn maximum_inbound_peers<E: Environment>(&self) -> usize {
+        match self {
+            Self::Ipv4Slash24(..) | Self::Ipv6Slash48(..) => E::MAXIMUM_INBOUND_PEERS_PER_SUBNET,
+            Self::Ipv4Slash16(..) => E::MAXIMUM_INBOUND_PEERS_PER_IPV4_SLASH_16,
+        }
+    }
+}
+
+impl fmt::Display for Subnet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Ipv4Slash24([a, b, c]) => write!(f, "{}/24", Ipv4Addr::new(*a, *b, *c, 0)),
+            Self::Ipv4Slash16([a, b]) => write!(f, "{}/16", Ipv4Addr::new(*a, *b, 0, 0)),
+            Self::Ipv6Slash48([a, b, c]) => write!(f, "{}/48", Ipv6Addr::new(*a, *b, *c, 0, 0, 0, 0, 0)),
+        }
+    }
+}
+
+///
+/// Returns the reason an inbound connection from the given peer IP is refused,
+/// if it would surpass the limits of inbound connections per IP or per subnet,
+/// given the IPs of the inbound connections of the node.
+///
+pub fn exceeded_inbound_limit<E: Environment>(peer_ip: IpAddr, inbound_ips: &[IpAddr]) -> Option<String> {
+    let peer_ip = canonical(peer_ip);
+
+    // Connections on this machine are not limited.
+    if peer_ip.is_loopback() {
+        return None;
+    }
+
+    // Count the inbound connections of each IP.
+    let mut num_connections = HashMap::<IpAddr, usize>::new();
+    for ip in inbound_ips.iter().map(|ip| canonical(*ip)).filter(|ip| !ip.is_loopback()) {
+        *num_connections.entry(ip).or_default() += 1;
+    }
+
+    // A connection beyond the limit per IP takes from the prover allowance, which is shared by all IPs.
+    if num_connections.get(&peer_ip).copied().unwrap_or(0) >= E::MAXIMUM_INBOUND_PEERS_PER_IP {
+        let num_provers: usize = num_connections
+            .values()
+            .map(|num| num.saturating_sub(E::MAXIMUM_INBOUND_PEERS_PER_IP))
+            .sum();
+        return match num_provers >= E::MAXIMUM_INBOUND_PROVERS_PER_IP {
+            true => Some(format!("maximum peers from {} reached", peer_ip)),
+            false => None,
+        };
+    }
+
+    // Ensure the peer does not surpass the limit of inbound connections per subnet,
+    // towards which each IP counts up to the limit per IP.
+    Subnet::of(peer_ip).into_iter().find_map(|subnet| {
+        let num_peers: usize = num_connections
+            .iter()
+            .filter(|(ip, _)| subnet.contains(**ip))
+            .map(|(_, num)| (*num).min(E::MAXIMUM_INBOUND_PEERS_PER_IP))
+            .sum();
+        match num_peers >= subnet.maximum_inbound_peers::<E>() {
+            true => Some(format!("maximum peers from {} reached", subnet)),
+            false => None,
+        }
+    })
+}
+
+/// Returns the given IP, with IPv4-mapped IPv6 addresses converted to IPv4.
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(ipv6) => match ipv6.segments() {
+            [0, 0, 0, 0, 0, 0xffff, ..] => ipv6.to_ipv4().map(IpAddr::V4).unwrap_or(ip),
+            _ => ip,
+        },
+        IpAddr::V4(_) => ip,
+    }
+}
+
+/// Returns `true` if the given IPv4 is not routable on the internet.
+fn is_local_ipv4(ip: &Ipv4Addr) -> bool {
+    ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified()
+}
+
+/// Returns `true` if the given IPv6 is not routable on the internet.
+fn is_local_ipv6(ip: &Ipv6Addr) -> bool {
+    // Unique local addresses are in fc00::/7, and link-local unicast addresses are in fe80::/10.
+    ip.is_loopback() || ip.is_unspecified() || (ip.segments()[0] & 0xfe00) == 0xfc00 || (ip.segments()[0] & 0xffc0) == 0xfe80
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_environment::{Client, CurrentNetwork, Operator};
+
+    type E = Client<CurrentNetwork>;
+
+    #[test]
+    fn test_subnets() {
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        assert_eq!(
+            Subnet::of(ip),
+            vec![Subnet::Ipv4Slash24([203, 0, 113]), Subnet::Ipv4Slash16([203, 0])]
+        );
+        assert_eq!(Subnet::group(ip), Some(Subnet::Ipv4Slash16([203, 0])));
+        assert_eq!(
+            Subnet::group("::ffff:203.0.113.7".parse().unwrap()),
+            Some(Subnet::Ipv4Slash16([203, 0]))
+        );
+        assert_eq!(Subnet::Ipv4Slash24([203, 0, 113]).to_string(), "203.0.113.0/24");
+
+        let ip: IpAddr = "2001:db8:1:2::1".parse().unwrap();
+        assert_eq!(Subnet::of(ip), vec![Subnet::Ipv6Slash48([0x2001, 0xdb8, 1])]);
+        assert_eq!(Subnet::Ipv6Slash48([0x2001, 0xdb8, 1]).to_string(), "2001:db8:1::/48");
+
+        // Local IPs are not grouped into subnets.
+        for ip in ["127.0.0.1", "10.0.0.1", "192.168.1.1", "::1", "fd00::1"] {
+            assert_eq!(Subnet::group(ip.parse().unwrap()), None);
+        }
+    }
+
+    #[test]
+    fn test_exceeded_inbound_limit() {
+        let ip = |ip: &str| ip.parse::<IpAddr>().unwrap();
+
+        // The limit per IP.
+        let inbound_ips = vec![ip("203.0.113.7"); E::MAXIMUM_INBOUND_PEERS_PER_IP];
+        assert!(exceeded_inbound_limit::<E>(ip("203.0.113.7"), &inbound_ips).is_some());
+        assert!(exceeded_inbound_limit::<E>(ip("203.0.113.8"), &inbound_ips).is_none());
+
+        // The limit per /24 subnet.
+        let inbound_ips = (0..E::MAXIMUM_INBOUND_PEERS_PER_SUBNET)
+            .map(|i| ip(&format!("203.0.113.{}", i)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            exceeded_inbound_limit::<E>(ip("203.0.113.200"), &inbound_ips).unwrap(),
+            "maximum peers from 203.0.113.0/24 reached"
+        );
+        assert!(exceeded_inbound_limit::<E>(ip("203.0.114.1"), &inbound_ips).is_none());
+
+        // The limit per /16 subnet.
+        let inbound_ips = (0..E::MAXIMUM_INBOUND_PEERS_PER_IPV4_SLASH_16)
+            .map(|i| ip(&format!("203.0.{}.1", i)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            exceeded_inbound_limit::<E>(ip("203.0.200.1"), &inbound_ips).unwrap(),
+            "maximum peers from 203.0.0.0/16 reached"
+        );
+        assert!(exceeded_inbound_limit::<E>(ip("203.1.0.1"), &inbound_ips).is_none());
+
+        // The limit per /48 subnet.
+        let inbound_ips = (0..E::MAXIMUM_INBOUND_PEERS_PER_SUBNET)
+            .map(|i| ip(&format!("2001:db8:1:{}::1", i)))
+            .collect::<Vec<_>>();
+        assert!(exceeded_inbound_limit::<E>(ip("2001:db8:1:ffff::1"), &inbound_ips).is_some());
+        assert!(exceeded_inbound_limit::<E>(ip("2001:db8:2::1"), &inbound_ips).is_none());
+
+        // Connections on this machine are not limited.
+        let inbound_ips = vec![ip("127.0.0.1"); E::MAXIMUM_INBOUND_PEERS_PER_IP];
+        assert!(exceeded_inbound_limit::<E>(ip("127.0.0.1"), &inbound_ips).is_none());
+
+        // An operator permits the provers of a pool to share an IP, up to its allowance.
+        type O = Operator<CurrentNetwork>;
+        let inbound_ips = vec![ip("203.0.113.7"); E::MAXIMUM_INBOUND_PEERS_PER_IP];
+        assert!(exceeded_inbound_limit::<O>(ip("203.0.113.7"), &inbound_ips).is_none());
+        let inbound_ips = vec![ip("203.0.113.7"); O::MAXIMUM_INBOUND_PEERS_PER_IP + O::MAXIMUM_INBOUND_PROVERS_PER_IP];
+        assert!(exceeded_inbound_limit::<O>(ip("203.0.113.7"), &inbound_ips).is_some());
+
+        // The allowance is shared by all IPs, rather than granted to each.
+        let num_provers = O::MAXIMUM_INBOUND_PROVERS_PER_IP / 2;
+        let mut inbound_ips = vec![ip("203.0.113.7"); O::MAXIMUM_INBOUND_PEERS_PER_IP + num_provers];
+        inbound_ips.extend(vec![ip("198.51.100.7"); O::MAXIMUM_INBOUND_PEERS_PER_IP + num_provers]);
+        assert!(exceeded_inbound_limit::<O>(ip("203.0.113.7"), &inbound_ips).is_some());
+        assert!(exceeded_inbound_limit::<O>(ip("192.0.2.7"), &inbound_ips).is_none());
+
+        // The provers of a pool do not count towards the limits of its subnets, which are not raised by the allowance.
+        let mut inbound_ips = vec![ip("203.0.113.7"); O::MAXIMUM_INBOUND_PEERS_PER_IP + num_provers];
+        inbound_ips.push(ip("203.0.113.8"));
+        assert!(exceeded_inbound_limit::<O>(ip("203.0.113.9"), &inbound_ips).is_none());
+        inbound_ips.push(ip("203.0.113.9"));
+        assert_eq!(
+            exceeded_inbound_limit::<O>(ip("203.0.113.10"), &inbound_ips).unwrap(),
+            "maximum peers from 203.0.113.0/24 reached"
+        );
+    }
+}
@@ -15,7 +15,7 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    helpers::{block_requests::*, BlockRequest, CircularMap},
+    helpers::{block_requests::*, BlockRequest, CircularMap, Misbehavior, Reputation},
     Data,
     DisconnectReason,
     Message,
@@ -28,7 +28,14 @@ use snarkos_environment::{
     helpers::{NodeType, State},
     Environment,
 };
-use snarkos_storage::{storage::Storage, BlockLocators, LedgerState, MAXIMUM_LINEAR_BLOCK_LOCATORS};
+use snarkos_storage::{
+    storage::{BackupInfo, Storage},
+    BlockLocators,
+    IndexerKey,
+    LedgerCacheConfig,
+    LedgerState,
+    MAXIMUM_LINEAR_BLOCK_LOCATORS,
+};
 use snarkvm::dpc::prelude::*;
 
 #[cfg(any(feature = "test", feature = "prometheus"))]
@@ -64,21 +71,23 @@ type LedgerHandler<N> = mpsc::Receiver<LedgerRequest<N>>;
 ///
 #[derive(Debug)]
 pub enum LedgerRequest<N: Network> {
+    /// BlockRequest := (peer_ip, block_height)
+    BlockRequest(SocketAddr, u32),
     /// BlockResponse := (peer_ip, block, prover_router)
     BlockResponse(SocketAddr, Block<N>, ProverRouter<N>),
     /// Disconnect := (peer_ip, reason)
     Disconnect(SocketAddr, DisconnectReason),
-    /// Failure := (peer_ip, failure)
-    Failure(SocketAddr, String),
+    /// Failure := (peer_ip, misbehavior)
+    Failure(SocketAddr, Misbehavior),
     /// Heartbeat := (prover_router)
     Heartbeat(ProverRouter<N>),
-    /// Pong := (peer_ip, node_type, status, is_fork, block_locators)
-    Pong(SocketAddr, NodeType, State, Option<bool>, BlockLocators<N>),
+    /// Pong := (peer_ip, node_type, status, is_fork, block_locators, pruned_block_height)
+    Pong(SocketAddr, NodeType, State, Option<bool>, BlockLocators<N>, Option<u32>),
     /// UnconfirmedBlock := (peer_ip, block, prover_router)
     UnconfirmedBlock(SocketAddr, Block<N>, ProverRouter<N>),
 }
 
-pub type PeersState<N> = HashMap<SocketAddr, Option<(NodeType, State, Option<bool>, u32, BlockLocators<N>)>>;
+pub type PeersState<N> = HashMap<SocketAddr, Option<(NodeType, State, Option<bool>, u32, BlockLocators<N>, Option<u32>)>>;
 
 ///
 /// A ledger for a specific network on the node server.
@@ -90,14 +99,14 @@ pub struct Ledger<N: Network, E: Environment> {
     ledger_router: LedgerRouter<N>,
     /// The canonical chain of blocks.
     canon: Arc<LedgerState<N>>,
-    /// The canonical chain of blocks in read-only mode.
+    /// The canonical chain of blocks in read-only mode, or the canonical chain itself if the reader is shared.
     canon_reader: Arc<LedgerState<N>>,
     /// A lock to ensure methods that need to be mutually-exclusive are enforced.
     /// In this context, `add_block`, and `revert_to_block_height` must be mutually-exclusive.
     canon_lock: Arc<Mutex<()>>,
     /// A map of previous block hashes to unconfirmed blocks.
     unconfirmed_blocks: RwLock<CircularMap<N::BlockHash, Block<N>, { MAXIMUM_UNCONFIRMED_BLOCKS }>>,
-    /// The map of each peer to their ledger state := (node_type, status, is_fork, latest_block_height, block_locators).
+    /// The map of each peer to their ledger state := (node_type, status, is_fork, latest_block_height, block_locators, pruned_block_height).
     peers_state: RwLock<PeersState<N>>,
     /// The map of each peer to their block requests := HashMap<(block_height, block_hash), timestamp>
     block_requests: RwLock<HashMap<SocketAddr, HashMap<BlockRequest<N>, i64>>>,
@@ -106,22 +115,42 @@ pub struct Ledger<N: Network, E: Environment> {
     block_requests_lock: Arc<Mutex<()>>,
     /// The timestamp of the last successful block update.
     last_block_update_timestamp: RwLock<Instant>,
-    /// The map of each peer to their failure messages := (failure_message, timestamp).
-    failures: RwLock<HashMap<SocketAddr, Vec<(String, i64)>>>,
+    /// The map of each peer to their reputation, as their recent misbehaviors.
+    reputations: RwLock<HashMap<SocketAddr, Reputation>>,
     /// The peers router of the node.
     peers_router: PeersRouter<N, E>,
 }
 
 impl<N: Network, E: Environment> Ledger<N, E> {
-    /// Initializes a new instance of the ledger.
-    pub async fn open<S: Storage, P: AsRef<Path> + Copy>(path: P, peers_router: PeersRouter<N, E>) -> Result<Arc<Self>> {
+    /// Initializes a new instance of the ledger, optionally pruning transactions older than the given depth,
+    /// and indexing the records owned by the given indexer keys.
+    ///
+    /// If `shared_reader` is `true`, the ledger reader is a handle to the canonical chain itself,
+    /// rather than a secondary instance that polls storage to catch up with it.
+    /// The caches of recent blocks of both are sized according to the given `cache_config`.
+    pub async fn open<S: Storage, P: AsRef<Path> + Copy>(
+        path: P,
+        prune_depth: Option<u32>,
+        indexer_keys: Vec<IndexerKey<N>>,
+        shared_reader: bool,
+        cache_config: LedgerCacheConfig,
+        peers_router: PeersRouter<N, E>,
+    ) -> Result<Arc<Self>> {
         // Initialize an mpsc channel for sending requests to the `Ledger` struct.
         let (ledger_router, mut ledger_handler) = mpsc::channel(1024);
 
-        let canon = Arc::new(LedgerState::open_writer::<S, P>(path)?);
-        let (canon_reader, reader_resource) = LedgerState::open_reader::<S, P>(path)?;
-        // Register the thread; no need to provide an id, as it will run indefinitely.
-        E::resources().register(reader_resource, None);
+        let canon = Arc::new(LedgerState::open_writer_with_options::<S, P>(path, prune_depth, indexer_keys)?);
+        canon.set_cache_config(cache_config);
+        let canon_reader = match shared_reader {
+            true => canon.clone(),
+            false => {
+                let (canon_reader, reader_resource) = LedgerState::open_reader::<S, P>(path)?;
+                canon_reader.set_cache_config(cache_config);
+                // Register the thread; no need to provide an id, as it will run indefinitely.
+                E::resources().register(reader_resource, None);
+                canon_reader
+            }
+        };
 
         // Initialize the ledger.
         let ledger = Arc::new(Self {
@@ -134,7 +163,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
             block_requests: Default::default(),
             block_requests_lock: Arc::new(Mutex::new(())),
             last_block_update_timestamp: RwLock::new(Instant::now()),
-            failures: Default::default(),
+            reputations: Default::default(),
             peers_router,
         });
 
@@ -174,6 +203,11 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         self.ledger_router.clone()
     }
 
+    /// Creates an incremental backup of the canonical chain in the given directory, keeping the given number of recent backups.
+    pub fn backup<P: AsRef<Path>>(&self, path: P, num_backups_to_keep: Option<usize>) -> Result<BackupInfo> {
+        self.canon.backup(path, num_backups_to_keep)
+    }
+
     pub async fn shut_down(&self) {
         debug!("Ledger is shutting down...");
 
@@ -199,6 +233,10 @@ impl<N: Network, E: Environment> Ledger<N, E> {
     ///
     pub(super) async fn update(&self, request: LedgerRequest<N>) {
         match request {
+            LedgerRequest::BlockRequest(peer_ip, block_height) => {
+                // Request the block from the peer in full.
+                self.request_block(peer_ip, block_height).await;
+            }
             LedgerRequest::BlockResponse(peer_ip, block, prover_router) => {
                 // Remove the block request from the ledger.
                 if self.remove_block_request(peer_ip, block.height()).await {
@@ -221,8 +259,8 @@ impl<N: Network, E: Environment> Ledger<N, E> {
             LedgerRequest::Disconnect(peer_ip, reason) => {
                 self.disconnect(peer_ip, reason).await;
             }
-            LedgerRequest::Failure(peer_ip, failure) => {
-                self.add_failure(peer_ip, failure).await;
+            LedgerRequest::Failure(peer_ip, misbehavior) => {
+                self.add_failure(peer_ip, misbehavior).await;
             }
             LedgerRequest::Heartbeat(prover_router) => {
                 // Update for sync nodes.
@@ -235,8 +273,6 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                 self.remove_expired_block_requests().await;
                 // Remove expired failures.
                 self.remove_expired_failures().await;
-                // Disconnect from peers with frequent failures.
-                self.disconnect_from_failing_peers().await;
                 // Update the block requests.
                 if E::NODE_TYPE != NodeType::Prover {
                     self.update_block_requests().await;
@@ -255,11 +291,12 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                     connected_peers,
                 );
             }
-            LedgerRequest::Pong(peer_ip, node_type, status, is_fork, block_locators) => {
+            LedgerRequest::Pong(peer_ip, node_type, status, is_fork, block_locators, pruned_block_height) => {
                 // Ensure the peer has been initialized in the ledger.
                 self.initialize_peer(peer_ip).await;
                 // Process the pong.
-                self.update_peer(peer_ip, node_type, status, is_fork, block_locators).await;
+                self.update_peer(peer_ip, node_type, status, is_fork, block_locators, pruned_block_height)
+                    .await;
             }
             LedgerRequest::UnconfirmedBlock(peer_ip, block, prover_router) => {
                 // Ensure the node is not peering.
@@ -323,6 +360,29 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         }
     }
 
+    ///
+    /// Disconnects and bans the given peer from the ledger.
+    ///
+    async fn disconnect_and_ban(&self, peer_ip: SocketAddr, reason: DisconnectReason) {
+        info!("Disconnecting and banning {} ({:?})", peer_ip, reason);
+        // Remove all entries of the peer from the ledger.
+        self.remove_peer(&peer_ip).await;
+        // Update the status of the ledger.
+        self.update_status().await;
+        // Send a `Disconnect` message to the peer.
+        if let Err(error) = self
+            .peers_router
+            .send(PeersRequest::MessageSend(peer_ip, Message::Disconnect(reason)))
+            .await
+        {
+            warn!("[Disconnect] {}", error);
+        }
+        // Route a `PeerBanned` to the peers.
+        if let Err(error) = self.peers_router.send(PeersRequest::PeerBanned(peer_ip)).await {
+            warn!("[PeerBanned] {}", error);
+        }
+    }
+
     ///
     /// Performs a heartbeat update for the sync nodes.
     ///
@@ -339,7 +399,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
 
             // Check if any of the peers are ahead and have a larger block height.
             for (peer_ip, peer_state) in peers_state.iter() {
-                if let Some((node_type, status, Some(_), block_height, block_locators)) = peer_state {
+                if let Some((node_type, status, Some(_), block_height, block_locators, _)) = peer_state {
                     // Retrieve the cumulative weight, defaulting to the block height if it does not exist.
                     let cumulative_weight = match block_locators.get_cumulative_weight(*block_height) {
                         Some(cumulative_weight) => cumulative_weight,
@@ -439,7 +499,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                 let latest_cumulative_weight = self.canon.latest_cumulative_weight();
                 // Iterate through the connected peers, to determine if the ledger state is out of date.
                 for (_, peer_state) in self.peers_state.read().await.iter() {
-                    if let Some((_, _, Some(_), block_height, block_locators)) = peer_state {
+                    if let Some((_, _, Some(_), block_height, block_locators, _)) = peer_state {
                         // Retrieve the cumulative weight, defaulting to the block height if it does not exist.
                         let cumulative_weight = match block_locators.get_cumulative_weight(*block_height) {
                             Some(cumulative_weight) => cumulative_weight,
@@ -467,6 +527,15 @@ impl<N: Network, E: Environment> Ledger<N, E> {
 
         // Update the ledger to the determined status.
         E::status().update(status);
+
+        // Report the hit rates of the caches of the ledger reader, as it serves the peers and the RPC server.
+        #[cfg(any(feature = "test", feature = "prometheus"))]
+        {
+            let cache_stats = self.canon_reader.cache_stats();
+            metrics::gauge!(metrics::ledger::BLOCK_CACHE_HIT_RATE, cache_stats.blocks.hit_rate());
+            metrics::gauge!(metrics::ledger::HEADER_CACHE_HIT_RATE, cache_stats.block_headers.hit_rate());
+            metrics::gauge!(metrics::ledger::BLOCK_BYTES_CACHE_HIT_RATE, cache_stats.serialized_blocks.hit_rate());
+        }
     }
 
     ///
@@ -626,7 +695,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         if !peer_state_exists {
             self.peers_state.write().await.entry(peer_ip).or_insert(None);
             self.block_requests.write().await.entry(peer_ip).or_insert_with(Default::default);
-            self.failures.write().await.entry(peer_ip).or_insert_with(Default::default);
+            self.reputations.write().await.entry(peer_ip).or_insert_with(Default::default);
         }
     }
 
@@ -636,7 +705,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
     async fn remove_peer(&self, peer_ip: &SocketAddr) {
         self.peers_state.write().await.remove(peer_ip);
         self.block_requests.write().await.remove(peer_ip);
-        self.failures.write().await.remove(peer_ip);
+        self.reputations.write().await.remove(peer_ip);
     }
 
     ///
@@ -649,18 +718,19 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         status: State,
         is_fork: Option<bool>,
         block_locators: BlockLocators<N>,
+        pruned_block_height: Option<u32>,
     ) {
         // Ensure the list of block locators is not empty.
         if block_locators.is_empty() {
-            self.add_failure(peer_ip, "Received a sync response with no block locators".to_string())
-                .await;
+            let misbehavior = Misbehavior::ProtocolViolation("Received a sync response with no block locators".to_string());
+            self.add_failure(peer_ip, misbehavior).await;
         } else {
             // Ensure the peer provided well-formed block locators.
             match self.canon.check_block_locators(&block_locators) {
                 Ok(is_valid) => {
                     if !is_valid {
                         warn!("Invalid block locators from {}", peer_ip);
-                        self.add_failure(peer_ip, "Invalid block locators".to_string()).await;
+                        self.add_failure(peer_ip, Misbehavior::ProtocolViolation("Invalid block locators".to_string())).await;
                         return;
                     }
                 }
@@ -680,7 +750,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                         continue;
 //                        let error = format!("Invalid block height {} for block hash {}", block_height, block_hash);
 //                        trace!("{}", error);
-//                        self.add_failure(peer_ip, error).await;
+//                        self.add_failure(peer_ip, Misbehavior::ProtocolViolation(error)).await;
 //                        return;
                     } else {
                         // Update the common ancestor, as this block hash exists in this ledger.
@@ -720,10 +790,34 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                 peer_ip, latest_block_height_of_peer, node_type, status, fork_status, cumulative_weight, common_ancestor,
             );
 
-            match self.peers_state.write().await.get_mut(&peer_ip) {
-                Some(peer_state) => *peer_state = Some((node_type, status, is_fork, latest_block_height_of_peer, block_locators)),
-                None => self.add_failure(peer_ip, format!("Missing ledger state for {}", peer_ip)).await,
+            let is_updated = match self.peers_state.write().await.get_mut(&peer_ip) {
+                Some(peer_state) => {
+                    *peer_state = Some((
+                        node_type,
+                        status,
+                        is_fork,
+                        latest_block_height_of_peer,
+                        block_locators,
+                        pruned_block_height,
+                    ));
+                    true
+                }
+                None => false,
             };
+
+            match is_updated {
+                // Route a `PeerBlockHeight` to the peers.
+                true => {
+                    let request = PeersRequest::PeerBlockHeight(peer_ip, latest_block_height_of_peer);
+                    if let Err(error) = self.peers_router.send(request).await {
+                        warn!("[PeerBlockHeight] {}", error);
+                    }
+                }
+                false => {
+                    let misbehavior = Misbehavior::ProtocolViolation(format!("Missing ledger state for {}", peer_ip));
+                    self.add_failure(peer_ip, misbehavior).await
+                }
+            }
         }
     }
 
@@ -748,6 +842,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         // Check if any of the peers are ahead and have a larger block height.
         if let Some((peer_ip, maximal_peer_is_on_fork, maximum_block_locators)) = find_maximal_peer::<N, E>(
             &*self.peers_state.read().await,
+            latest_block_height,
             &mut maximum_block_height,
             &mut maximum_cumulative_weight,
         ) {
@@ -767,7 +862,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                 Ok(ret) => ret,
                 Err(error) => {
                     trace!("{}", error);
-                    self.add_failure(peer_ip, error).await;
+                    self.add_failure(peer_ip, Misbehavior::ProtocolViolation(error)).await;
                     return;
                 }
             };
@@ -815,38 +910,41 @@ impl<N: Network, E: Environment> Ledger<N, E> {
             }
 
             // Filter out any pre-existing block requests for the peer.
-            let mut missing_block_requests = false;
-            let mut new_block_heights = Vec::new();
-            if let Some(block_requests) = self.block_requests.read().await.get(&peer_ip) {
-                for block_height in start_block_height..=end_block_height {
-                    if !block_requests.contains_key(&block_height.into()) {
-                        new_block_heights.push(block_height);
-                    }
+            // Note: the lock on the block requests is released before a failure is added, as it may ban the peer.
+            let new_block_heights = self.block_requests.read().await.get(&peer_ip).map(|block_requests| {
+                (start_block_height..=end_block_height)
+                    .filter(|block_height| !block_requests.contains_key(&(*block_height).into()))
+                    .collect::<Vec<_>>()
+            });
+            let new_block_heights = match new_block_heights {
+                Some(new_block_heights) => new_block_heights,
+                None => {
+                    let misbehavior = Misbehavior::ProtocolViolation(format!("Missing block requests for {}", peer_ip));
+                    self.add_failure(peer_ip, misbehavior).await;
+                    return;
                 }
-            } else {
-                self.add_failure(peer_ip, format!("Missing block requests for {}", peer_ip)).await;
-                missing_block_requests = true;
-            }
+            };
 
-            if !missing_block_requests && !new_block_heights.is_empty() {
+            if !new_block_heights.is_empty() {
                 // Log each block request to ensure the peer responds with all requested blocks.
+                let mut number_of_duplicate_requests = 0;
                 if let Some(locked_block_requests) = self.block_requests.write().await.get_mut(&peer_ip) {
                     for block_height in new_block_heights {
                         // If the ledger is on a fork and was reverted, include the expected new block hash for the fork.
-                        match ledger_is_on_fork {
-                            true => {
-                                self.add_block_request(
-                                    peer_ip,
-                                    block_height,
-                                    maximum_block_locators.get_block_hash(block_height),
-                                    locked_block_requests,
-                                )
-                                .await
-                            }
-                            false => self.add_block_request(peer_ip, block_height, None, locked_block_requests).await,
+                        let block_hash = match ledger_is_on_fork {
+                            true => maximum_block_locators.get_block_hash(block_height),
+                            false => None,
                         };
+                        if !self.add_block_request(peer_ip, block_height, block_hash, locked_block_requests) {
+                            number_of_duplicate_requests += 1;
+                        }
                     }
                 }
+
+                for _ in 0..number_of_duplicate_requests {
+                    let misbehavior = Misbehavior::ProtocolViolation(format!("Duplicate block request for {}", peer_ip));
+                    self.add_failure(peer_ip, misbehavior).await;
+                }
             }
         }
     }
@@ -860,17 +958,44 @@ impl<N: Network, E: Environment> Ledger<N, E> {
 
     ///
     /// Adds a block request for the given block height to the specified peer.
+    /// Returns `false` if the block request already existed.
     ///
-    async fn add_block_request(
+    fn add_block_request(
         &self,
         peer_ip: SocketAddr,
         block_height: u32,
         block_hash: Option<N::BlockHash>,
         locked_block_requests: &mut HashMap<BlockRequest<N>, i64>,
-    ) {
+    ) -> bool {
         match locked_block_requests.insert((block_height, block_hash).into(), OffsetDateTime::now_utc().unix_timestamp()) {
-            None => debug!("Requesting block {} from {}", block_height, peer_ip),
-            Some(_old_request) => self.add_failure(peer_ip, format!("Duplicate block request for {}", peer_ip)).await,
+            None => {
+                debug!("Requesting block {} from {}", block_height, peer_ip);
+                true
+            }
+            Some(_old_request) => false,
+        }
+    }
+
+    ///
+    /// Requests the block at the given height from the specified peer, as a fallback
+    /// for an unconfirmed block that could not be reconstructed from its compact form.
+    ///
+    async fn request_block(&self, peer_ip: SocketAddr, block_height: u32) {
+        // Ensure the block is not in the ledger already.
+        if block_height <= self.canon.latest_block_height() {
+            return;
+        }
+
+        // Log the block request, so that the block response is accepted.
+        let is_new_request = match self.block_requests.write().await.get_mut(&peer_ip) {
+            Some(block_requests) => self.add_block_request(peer_ip, block_height, None, block_requests),
+            None => false,
+        };
+        if is_new_request {
+            let request = PeersRequest::MessageSend(peer_ip, Message::BlockRequest(block_height, block_height));
+            if let Err(error) = self.peers_router.send(request).await {
+                warn!("[BlockRequest] {}", error);
+            }
         }
     }
 
@@ -891,20 +1016,26 @@ impl<N: Network, E: Environment> Ledger<N, E> {
     async fn remove_block_request(&self, peer_ip: SocketAddr, block_height: u32) -> bool {
         // Ensure the block height corresponds to a requested block.
         if !self.contains_block_request(peer_ip, block_height).await {
-            self.add_failure(peer_ip, "Received an invalid block response".to_string()).await;
+            let misbehavior = Misbehavior::ProtocolViolation("Received an invalid block response".to_string());
+            self.add_failure(peer_ip, misbehavior).await;
             false
         } else {
-            if let Some(requests) = self.block_requests.write().await.get_mut(&peer_ip) {
-                let is_success = requests.remove(&block_height.into()).is_some();
-                match is_success {
-                    true => return true,
-                    false => {
-                        self.add_failure(peer_ip, format!("Non-existent block request from {}", peer_ip))
-                            .await
-                    }
+            // Note: the lock on the block requests is released before a failure is added, as it may ban the peer.
+            let is_success = self
+                .block_requests
+                .write()
+                .await
+                .get_mut(&peer_ip)
+                .map(|requests| requests.remove(&block_height.into()).is_some());
+            match is_success {
+                Some(true) => true,
+                Some(false) => {
+                    let misbehavior = Misbehavior::ProtocolViolation(format!("Non-existent block request from {}", peer_ip));
+                    self.add_failure(peer_ip, misbehavior).await;
+                    false
                 }
+                None => false,
             }
-            false
         }
     }
 
@@ -920,14 +1051,31 @@ impl<N: Network, E: Environment> Ledger<N, E> {
     }
 
     ///
-    /// Adds the given failure message to the specified peer IP.
+    /// Adds the given misbehavior to the specified peer IP, and disconnects and bans
+    /// the peer if its misbehavior score reaches `E::MAXIMUM_MISBEHAVIOR_SCORE`.
     ///
-    async fn add_failure(&self, peer_ip: SocketAddr, failure: String) {
-        trace!("Adding failure for {}: {}", peer_ip, failure);
-        match self.failures.write().await.get_mut(&peer_ip) {
-            Some(failures) => failures.push((failure, OffsetDateTime::now_utc().unix_timestamp())),
-            None => error!("Missing failure entry for {}", peer_ip),
+    async fn add_failure(&self, peer_ip: SocketAddr, misbehavior: Misbehavior) {
+        trace!("Adding failure for {}: {}", peer_ip, misbehavior);
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        // Compute the score of the peer, and release the lock on the reputations before banning.
+        let score = {
+            let mut reputations = self.reputations.write().await;
+            match reputations.get_mut(&peer_ip) {
+                Some(reputation) => {
+                    reputation.add(misbehavior, now);
+                    reputation.score(now, E::MISBEHAVIOR_HALF_LIFE_IN_SECS)
+                }
+                None => {
+                    error!("Missing reputation entry for {}", peer_ip);
+                    return;
+                }
+            }
         };
+
+        if score >= E::MAXIMUM_MISBEHAVIOR_SCORE as f64 {
+            warn!("Misbehavior score of {} reached {:.2}", peer_ip, score);
+            self.disconnect_and_ban(peer_ip, DisconnectReason::TooManyFailures).await;
+        }
     }
 
     ///
@@ -936,26 +1084,10 @@ impl<N: Network, E: Environment> Ledger<N, E> {
     async fn remove_expired_failures(&self) {
         // Clear all failures that have lived longer than `E::FAILURE_EXPIRY_TIME_IN_SECS`.
         let now = OffsetDateTime::now_utc().unix_timestamp();
-        self.failures.write().await.iter_mut().for_each(|(_, failures)| {
-            failures.retain(|(_, time_of_fail)| now.saturating_sub(*time_of_fail) < E::FAILURE_EXPIRY_TIME_IN_SECS as i64)
-        });
-    }
-
-    ///
-    /// Disconnects from connected peers who exhibit frequent failures.
-    ///
-    async fn disconnect_from_failing_peers(&self) {
-        let peers_to_disconnect = self
-            .failures
-            .read()
+        self.reputations
+            .write()
             .await
-            .iter()
-            .filter(|(_, failures)| failures.len() > E::MAXIMUM_NUMBER_OF_FAILURES)
-            .map(|(peer_ip, _)| *peer_ip)
-            .collect::<Vec<_>>();
-
-        for peer_ip in peers_to_disconnect {
-            self.disconnect(peer_ip, DisconnectReason::TooManyFailures).await;
-        }
+            .iter_mut()
+            .for_each(|(_, reputation)| reputation.remove_expired(now, E::FAILURE_EXPIRY_TIME_IN_SECS));
     }
 }
@@ -22,6 +22,9 @@
 #[macro_use]
 extern crate tracing;
 
+pub mod backups;
+pub use backups::*;
+
 pub mod helpers;
 
 pub mod ledger;
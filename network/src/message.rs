@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+use crate::helpers::CompactBlock;
 use snarkos_environment::{
     helpers::{NodeType, State},
     Environment,
@@ -109,6 +110,8 @@ pub enum DisconnectReason {
     YouNeedToSyncFirst,
     /// The peer's listening port is closed.
     YourPortIsClosed(u16),
+    /// The requested blocks have been pruned (only sent to peers on the latest message version).
+    BlocksPruned,
 }
 
 #[derive(Clone, Debug)]
@@ -117,8 +120,8 @@ pub enum Message<N: Network, E: Environment> {
     BlockRequest(u32, u32),
     /// BlockResponse := (block)
     BlockResponse(Data<Block<N>>),
-    /// ChallengeRequest := (version, fork_depth, node_type, status, listener_port, nonce, cumulative_weight)
-    ChallengeRequest(u32, u32, NodeType, State, u16, u64, u128),
+    /// ChallengeRequest := (version, fork_depth, node_type, status, listener_port, nonce, cumulative_weight, pruned_block_height)
+    ChallengeRequest(u32, u32, NodeType, State, u16, u64, u128, Option<u32>),
     /// ChallengeResponse := (block_header)
     ChallengeResponse(Data<BlockHeader<N>>),
     /// Disconnect := ()
@@ -141,8 +144,16 @@ pub enum Message<N: Network, E: Environment> {
     PoolRequest(u64, Data<BlockTemplate<N>>),
     /// PoolResponse := (address, nonce, proof)
     PoolResponse(Address<N>, N::PoSWNonce, Data<PoSWProof<N>>),
+    /// NoiseHandshake := (handshake_message)
+    NoiseHandshake(Bytes),
     NewBlockTemplate(Data<BlockTemplate<N>>),
     PoolBlock(N::PoSWNonce, Data<PoSWProof<N>>),
+    /// CompactBlock := (block_height, block_hash, compact_block)
+    CompactBlock(u32, N::BlockHash, Data<CompactBlock<N>>),
+    /// GetBlockTransactions := (block_hash, \[transaction_index\])
+    GetBlockTransactions(N::BlockHash, Vec<u32>),
+    /// BlockTransactions := (block_hash, \[transaction\])
+    BlockTransactions(N::BlockHash, Data<Vec<Transaction<N>>>),
     /// Unused
     #[allow(unused)]
     Unused(PhantomData<E>),
@@ -167,8 +178,12 @@ impl<N: Network, E: Environment> Message<N, E> {
             Self::PoolRegister(..) => "PoolRegister",
             Self::PoolRequest(..) => "PoolRequest",
             Self::PoolResponse(..) => "PoolResponse",
+            Self::NoiseHandshake(..) => "NoiseHandshake",
             Self::NewBlockTemplate(..) => "NewBlockTemplate",
             Self::PoolBlock(..) => "PoolBlock",
+            Self::CompactBlock(..) => "CompactBlock",
+            Self::GetBlockTransactions(..) => "GetBlockTransactions",
+            Self::BlockTransactions(..) => "BlockTransactions",
             Self::Unused(..) => "Unused",
         }
     }
@@ -191,8 +206,12 @@ impl<N: Network, E: Environment> Message<N, E> {
             Self::PoolRegister(..) => 11,
             Self::PoolRequest(..) => 12,
             Self::PoolResponse(..) => 13,
+            Self::NoiseHandshake(..) => 15,
             Self::NewBlockTemplate(..) => 100,
             Self::PoolBlock(..) => 101,
+            Self::CompactBlock(..) => 16,
+            Self::GetBlockTransactions(..) => 17,
+            Self::BlockTransactions(..) => 18,
             Self::Unused(..) => 14,
         }
     }
@@ -206,12 +225,19 @@ impl<N: Network, E: Environment> Message<N, E> {
                 Ok(writer.write_all(&bytes)?)
             }
             Self::BlockResponse(block) => block.serialize_blocking_into(writer),
-            Self::ChallengeRequest(version, fork_depth, node_type, status, listener_port, nonce, cumulative_weight) => {
-                Ok(bincode::serialize_into(
-                    writer,
-                    &(version, fork_depth, node_type, status, listener_port, nonce, cumulative_weight),
-                )?)
-            }
+            Self::ChallengeRequest(
+                version,
+                fork_depth,
+                node_type,
+                status,
+                listener_port,
+                nonce,
+                cumulative_weight,
+                pruned_block_height,
+            ) => Ok(bincode::serialize_into(
+                writer,
+                &(version, fork_depth, node_type, status, listener_port, nonce, cumulative_weight, pruned_block_height),
+            )?),
             Self::ChallengeResponse(block_header) => Ok(block_header.serialize_blocking_into(writer)?),
             Self::Disconnect(reason) => Ok(bincode::serialize_into(writer, reason)?),
             Self::PeerRequest => Ok(()),
@@ -248,11 +274,24 @@ impl<N: Network, E: Environment> Message<N, E> {
                 bincode::serialize_into(&mut *writer, nonce)?;
                 proof.serialize_blocking_into(writer)
             }
+            Self::NoiseHandshake(handshake_message) => Ok(writer.write_all(handshake_message)?),
             Self::NewBlockTemplate(block_template) => block_template.serialize_blocking_into(writer),
             Self::PoolBlock(nonce, proof) => {
                 bincode::serialize_into(&mut *writer, nonce)?;
                 proof.serialize_blocking_into(writer)
             }
+            Self::CompactBlock(block_height, block_hash, compact_block) => {
+                writer.write_all(&block_height.to_le_bytes())?;
+                writer.write_all(&block_hash.to_bytes_le()?)?;
+                compact_block.serialize_blocking_into(writer)
+            }
+            Self::GetBlockTransactions(block_hash, transaction_indices) => {
+                Ok(bincode::serialize_into(writer, &(block_hash, transaction_indices))?)
+            }
+            Self::BlockTransactions(block_hash, transactions) => {
+                writer.write_all(&block_hash.to_bytes_le()?)?;
+                transactions.serialize_blocking_into(writer)
+            }
             Self::Unused(_) => Ok(()),
         }
     }
@@ -287,7 +326,23 @@ impl<N: Network, E: Environment> Message<N, E> {
                 let (version, fork_depth, node_type, status, listener_port, nonce, cumulative_weight) =
                     bincode::deserialize_from(&mut *reader)?;
 
-                Self::ChallengeRequest(version, fork_depth, node_type, status, listener_port, nonce, cumulative_weight)
+                // The pruned block height is optional, as peers on older versions omit it.
+                let data = read_to_end(&mut *reader)?;
+                let pruned_block_height = match data.is_empty() {
+                    true => None,
+                    false => bincode::deserialize(&data)?,
+                };
+
+                Self::ChallengeRequest(
+                    version,
+                    fork_depth,
+                    node_type,
+                    status,
+                    listener_port,
+                    nonce,
+                    cumulative_weight,
+                    pruned_block_height,
+                )
             }
             3 => Self::ChallengeResponse(Data::Buffer(read_to_end(&mut *reader)?)),
             4 => {
@@ -342,6 +397,17 @@ impl<N: Network, E: Environment> Message<N, E> {
                 bincode::deserialize_from(&mut *reader)?,
                 Data::Buffer(read_to_end(&mut *reader)?),
             ),
+            15 => Self::NoiseHandshake(read_to_end(&mut *reader)?),
+            16 => Self::CompactBlock(
+                bincode::deserialize_from(&mut *reader)?,
+                bincode::deserialize_from(&mut *reader)?,
+                Data::Buffer(read_to_end(&mut *reader)?),
+            ),
+            17 => {
+                let (block_hash, transaction_indices) = bincode::deserialize_from(&mut *reader)?;
+                Self::GetBlockTransactions(block_hash, transaction_indices)
+            }
+            18 => Self::BlockTransactions(bincode::deserialize_from(&mut *reader)?, Data::Buffer(read_to_end(&mut *reader)?)),
             100 => Self::NewBlockTemplate(Data::Buffer(read_to_end(&mut *reader)?)),
             101 => Self::PoolBlock(bincode::deserialize_from(&mut *reader)?, Data::Buffer(read_to_end(&mut *reader)?)),
             _ => return Err(anyhow!("Invalid message ID {}", id)),
@@ -14,9 +14,22 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{Data, LedgerReader, LedgerRequest, LedgerRouter, Message, PeersRequest, PeersRouter, ProverRouter};
+use crate::{
+    helpers::{MemoryPool, Misbehavior},
+    Data,
+    LedgerReader,
+    LedgerRequest,
+    LedgerRouter,
+    Message,
+    PeersRequest,
+    PeersRouter,
+    ProverRouter,
+};
 use snarkos_environment::{helpers::NodeType, Environment};
-use snarkos_storage::{storage::Storage, OperatorState};
+use snarkos_storage::{
+    storage::{BackupInfo, Storage},
+    OperatorState,
+};
 use snarkvm::dpc::{prelude::*, PoSWProof};
 
 use anyhow::Result;
@@ -150,6 +163,8 @@ impl<N: Network, E: Environment> Operator<N, E> {
                     task::spawn(async move {
                         // Notify the outer function that the task is ready.
                         let _ = router.send(());
+                        // Subscribe to the latest block, to replace the block template as soon as it is stale.
+                        let mut latest_block = operator.ledger_reader.subscribe();
                         // TODO (julesdesmit): Add logic to the loop to retarget share difficulty.
                         loop {
                             // Determine if the current block template is stale.
@@ -161,7 +176,12 @@ impl<N: Network, E: Environment> Operator<N, E> {
                             // Update the block template if it is stale.
                             if is_block_template_stale {
                                 // Construct a new block template.
-                                let transactions = operator.memory_pool.read().await.transactions();
+                                // Select the unconfirmed transactions with the highest fees that fit in a block.
+                                let transactions = operator
+                                    .memory_pool
+                                    .read()
+                                    .await
+                                    .select_transactions(E::MAXIMUM_BLOCK_TRANSACTIONS_SIZE);
                                 let ledger_reader = operator.ledger_reader.clone();
                                 let result = task::spawn_blocking(move || {
                                     E::thread_pool().install(move || {
@@ -196,8 +216,8 @@ impl<N: Network, E: Environment> Operator<N, E> {
                                 };
                             }
 
-                            // Proceed to sleep for a preset amount of time.
-                            tokio::time::sleep(HEARTBEAT_IN_SECONDS).await;
+                            // Proceed to wait for a new latest block, for up to a preset amount of time.
+                            let _ = tokio::time::timeout(HEARTBEAT_IN_SECONDS, latest_block.changed()).await;
                         }
                     }),
                 );
@@ -217,6 +237,11 @@ impl<N: Network, E: Environment> Operator<N, E> {
         self.operator_router.clone()
     }
 
+    /// Creates an incremental backup of the operator storage in the given directory, keeping the given number of recent backups.
+    pub fn backup<P: AsRef<Path>>(&self, path: P, num_backups_to_keep: Option<usize>) -> Result<BackupInfo> {
+        self.state.backup(path, num_backups_to_keep)
+    }
+
     /// Returns all the shares in storage.
     pub fn to_shares(&self) -> Vec<((u32, Record<N>), HashMap<Address<N>, u64>)> {
         self.state.to_shares()
@@ -270,7 +295,7 @@ impl<N: Network, E: Environment> Operator<N, E> {
                     // Ensure the given nonce from the prover is new.
                     if self.known_nonces.read().await.contains(&nonce) {
                         warn!("[PoolResponse] Peer {} sent a duplicate share", peer_ip);
-                        // TODO (julesdesmit): punish?
+                        self.add_failure(peer_ip, Misbehavior::InvalidShare("Duplicate share".to_string())).await;
                         return;
                     }
 
@@ -298,6 +323,7 @@ impl<N: Network, E: Environment> Operator<N, E> {
                         &proof,
                     ) {
                         warn!("[PoolResponse] PoSW proof verification failed");
+                        self.add_failure(peer_ip, Misbehavior::InvalidShare("Invalid PoSW proof".to_string())).await;
                         return;
                     }
 
@@ -332,7 +358,6 @@ impl<N: Network, E: Environment> Operator<N, E> {
                         if let Ok(block) = Block::from(previous_block_hash, block_header, transactions) {
                             info!("Operator has found unconfirmed block {} ({})", block.height(), block.hash());
                             let request = LedgerRequest::UnconfirmedBlock(self.local_ip, block, self.prover_router.clone());
-                            self.ledger_reader.invalidate_coinbase_cache();
                             if let Err(error) = self.ledger_router.send(request).await {
                                 warn!("Failed to broadcast mined block - {}", error);
                             }
@@ -356,7 +381,6 @@ impl<N: Network, E: Environment> Operator<N, E> {
                         if let Ok(block) = Block::from(previous_block_hash, block_header, transactions) {
                             info!("Operator has found unconfirmed block {} ({})", block.height(), block.hash());
                             let request = LedgerRequest::UnconfirmedBlock(self.local_ip, block, self.prover_router.clone());
-                            self.ledger_reader.invalidate_coinbase_cache();
                             if let Err(error) = self.ledger_router.send(request).await {
                                 warn!("Failed to broadcast mined block - {}", error);
                             }
@@ -368,4 +392,13 @@ impl<N: Network, E: Environment> Operator<N, E> {
             }
         }
     }
+
+    ///
+    /// Routes a `Failure` for the given misbehavior of the specified peer IP to the ledger.
+    ///
+    async fn add_failure(&self, peer_ip: SocketAddr, misbehavior: Misbehavior) {
+        if let Err(error) = self.ledger_router.send(LedgerRequest::Failure(peer_ip, misbehavior)).await {
+            warn!("[Failure] {}", error);
+        }
+    }
 }
@@ -15,6 +15,7 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
+    helpers::{noise_handshake, ConnectionDirection, Misbehavior, NodeIdentity, PeerCodec, PeerIdentity, PeerStats, RateLimiter},
     ConnectionResult,
     Data,
     DisconnectReason,
@@ -35,14 +36,22 @@ use snarkos_environment::{
 };
 use snarkvm::dpc::prelude::*;
 
-use anyhow::{anyhow, bail, Result};
+#[cfg(any(feature = "test", feature = "prometheus"))]
+use snarkos_metrics as metrics;
+
+use anyhow::{anyhow, bail, ensure, Result};
 use futures::SinkExt;
 use std::{
     collections::HashMap,
     net::SocketAddr,
     time::{Duration, Instant, SystemTime},
 };
-use tokio::{net::TcpStream, sync::mpsc, task, time::timeout};
+use tokio::{
+    net::TcpStream,
+    sync::{mpsc, oneshot},
+    task,
+    time::{interval, timeout},
+};
 use tokio_stream::StreamExt;
 use tokio_util::codec::Framed;
 
@@ -65,15 +74,29 @@ pub(crate) struct Peer<N: Network, E: Environment> {
     status: Status,
     /// The block header of the peer.
     block_header: BlockHeader<N>,
+    /// The highest block height the peer has pruned the transactions of, if any.
+    pruned_block_height: Option<u32>,
     /// The timestamp of the last message received from this peer.
     last_seen: Instant,
+    /// The timestamp of the last `Ping` sent to this peer, if it has not been answered yet.
+    last_ping_sent: Option<Instant>,
+    /// The limits on the rate of messages and bytes received from this peer.
+    rate_limiter: RateLimiter,
+    /// The number of bytes received from this peer, up to the last message.
+    bytes_received: u64,
+    /// The traffic statistics of this peer.
+    stats: PeerStats,
+    /// A flag indicating whether the statistics changed since they were last reported to the peers.
+    is_stats_changed: bool,
     /// The TCP socket that handles sending and receiving data with this peer.
-    outbound_socket: Framed<TcpStream, Message<N, E>>,
+    outbound_socket: Framed<TcpStream, PeerCodec<N, E>>,
     /// The `outbound_handler` half of the MPSC message channel, used to receive messages from peers.
     /// When a message is received on this `OutboundHandler`, it will be written to the socket.
     outbound_handler: OutboundHandler<N, E>,
     /// The map of block hashes to their last seen timestamp.
     seen_inbound_blocks: HashMap<N::BlockHash, SystemTime>,
+    /// The map of compact block hashes to their last seen timestamp.
+    seen_inbound_compact_blocks: HashMap<N::BlockHash, SystemTime>,
     /// The map of transaction IDs to their last seen timestamp.
     seen_inbound_transactions: HashMap<N::TransactionID, SystemTime>,
     /// The map of peers to a map of block hashes to their last seen timestamp.
@@ -91,23 +114,48 @@ impl<N: Network, E: Environment> Peer<N, E> {
         peers_router: &PeersRouter<N, E>,
         ledger_reader: &LedgerReader<N>,
         connected_nonces: &[u64],
+        identity: Option<&NodeIdentity>,
+        direction: ConnectionDirection,
     ) -> Result<Self> {
         // Construct the socket.
-        let mut outbound_socket = Framed::new(stream, Message::<N, E>::PeerRequest);
+        let mut outbound_socket = Framed::new(stream, PeerCodec::new());
 
-        // Perform the handshake before proceeding.
-        let (peer_ip, peer_nonce, node_type, status) = Peer::handshake(
+        // Perform the handshake before proceeding, which the peer must complete in time.
+        let handshake = Peer::handshake(
             &mut outbound_socket,
             local_ip,
             local_nonce,
             ledger_reader.latest_cumulative_weight(),
+            ledger_reader.get_pruned_block_height()?,
             connected_nonces,
-        )
-        .await?;
+            identity,
+        );
+        let (peer_ip, peer_nonce, node_type, status, pruned_block_height, peer_identity) =
+            match timeout(Duration::from_secs(E::HANDSHAKE_TIMEOUT_IN_SECS), handshake).await {
+                Ok(result) => result?,
+                Err(_) => bail!("The handshake timed out after {} seconds", E::HANDSHAKE_TIMEOUT_IN_SECS),
+            };
+
+        match peer_identity {
+            Some(peer_identity) => debug!("Established an encrypted connection with {} (identity {})", peer_ip, peer_identity),
+            None => trace!("Established an unencrypted connection with {}", peer_ip),
+        }
+
+        // Ensure the identity of the peer matches the one on record, if the peer must keep its identity.
+        let (router, handler) = oneshot::channel();
+        peers_router
+            .send(PeersRequest::PeerIdentified(peer_ip, peer_identity, router))
+            .await?;
+        if !handler.await? {
+            match peer_identity {
+                Some(peer_identity) => bail!("Dropping {} for a changed identity {}", peer_ip, peer_identity),
+                None => bail!("Dropping {} for an unencrypted connection despite its pinned identity", peer_ip),
+            }
+        }
 
         // Send the first `Ping` message to the peer.
         let message = Message::Ping(
-            E::MESSAGE_VERSION,
+            E::COMPACT_BLOCK_MESSAGE_VERSION,
             N::ALEO_MAXIMUM_FORK_DEPTH,
             E::NODE_TYPE,
             E::status().get(),
@@ -122,19 +170,36 @@ impl<N: Network, E: Environment> Peer<N, E> {
 
         // Add an entry for this `Peer` in the connected peers.
         peers_router
-            .send(PeersRequest::PeerConnected(peer_ip, peer_nonce, outbound_router))
+            .send(PeersRequest::PeerConnected(
+                peer_ip,
+                peer_nonce,
+                node_type,
+                direction,
+                outbound_router,
+            ))
             .await?;
 
+        // The bytes of the handshake are not subject to the rate limits, nor part of the statistics.
+        let bytes_received = outbound_socket.codec().bytes_received();
+        let stats = PeerStats::new(node_type, status.get(), direction);
+
         Ok(Peer {
             listener_ip: peer_ip,
             version: 0,
             node_type,
             status,
             block_header: N::genesis_block().header().clone(),
+            pruned_block_height,
             last_seen: Instant::now(),
+            last_ping_sent: Some(Instant::now()),
+            rate_limiter: RateLimiter::new::<E>(),
+            bytes_received,
+            stats,
+            is_stats_changed: true,
             outbound_socket,
             outbound_handler,
             seen_inbound_blocks: Default::default(),
+            seen_inbound_compact_blocks: Default::default(),
             seen_inbound_transactions: Default::default(),
             seen_outbound_blocks: Default::default(),
             seen_outbound_transactions: Default::default(),
@@ -149,42 +214,223 @@ impl<N: Network, E: Environment> Peer<N, E> {
     /// Sends the given message to this peer.
     async fn send(&mut self, message: Message<N, E>) -> Result<()> {
         trace!("Sending '{}' to {}", message.name(), self.peer_ip());
+        let message_name = message.name().to_string();
+        let bytes_sent = self.outbound_socket.codec().bytes_sent();
         self.outbound_socket.send(message).await?;
+
+        // Record the sent message in the statistics.
+        let message_size = self.outbound_socket.codec().bytes_sent().saturating_sub(bytes_sent);
+        self.stats.record_sent(&message_name, message_size);
+        self.is_stats_changed = true;
         Ok(())
     }
 
+    /// Returns the statistics of this peer, with its latest node type, state, and version.
+    fn stats(&self) -> PeerStats {
+        PeerStats {
+            node_type: self.node_type,
+            state: self.status.get(),
+            version: self.version,
+            ..self.stats.clone()
+        }
+    }
+
+    ///
+    /// Returns the transactions of the block with the given hash, at the given indices, or an error if this node
+    /// has not recently sent the block to this peer in compact form, or the block is not among the latest blocks.
+    ///
+    fn get_block_transactions(
+        &self,
+        ledger_reader: &LedgerReader<N>,
+        block_hash: &N::BlockHash,
+        indices: &[u32],
+    ) -> Result<Vec<Transaction<N>>> {
+        // Ensure the block was relayed to this peer in compact form, as the transactions are only served to complete it.
+        let is_sent = match self.seen_outbound_blocks.get(block_hash) {
+            Some(last_sent) => last_sent.elapsed().unwrap_or(Duration::MAX).as_secs() <= E::RADIO_SILENCE_IN_SECS,
+            None => false,
+        };
+        ensure!(
+            is_sent && self.version >= E::COMPACT_BLOCK_MESSAGE_VERSION,
+            "Requested the transactions of block {}, which was not sent in compact form",
+            block_hash
+        );
+
+        // Ensure the block is among the latest blocks.
+        let block_height = ledger_reader.get_block_height(block_hash)?;
+        let latest_block_height = ledger_reader.latest_block_height();
+        ensure!(
+            block_height.saturating_add(E::MAXIMUM_COMPACT_BLOCK_DEPTH) >= latest_block_height,
+            "Requested the transactions of block {}, which is {} blocks deep",
+            block_height,
+            latest_block_height - block_height
+        );
+
+        let transactions = ledger_reader.get_block_transactions(block_height)?;
+        ensure!(
+            indices.len() <= transactions.len(),
+            "Requested {} of {} transactions",
+            indices.len(),
+            transactions.len()
+        );
+        indices
+            .iter()
+            .map(|index| {
+                transactions
+                    .get(*index as usize)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Invalid transaction index {}", index))
+            })
+            .collect()
+    }
+
+    /// Returns `true` if the block with the given hash was not sent to this peer recently, and records it as sent.
+    fn is_ready_to_send_block(&mut self, block_hash: N::BlockHash) -> bool {
+        // Retrieve the last seen timestamp of this block for this peer.
+        let last_seen = self.seen_outbound_blocks.entry(block_hash).or_insert(SystemTime::UNIX_EPOCH);
+        let is_ready_to_send = last_seen.elapsed().unwrap().as_secs() > E::RADIO_SILENCE_IN_SECS;
+
+        // Update the timestamp for the peer and sent block.
+        self.seen_outbound_blocks.insert(block_hash, SystemTime::now());
+        is_ready_to_send
+    }
+
+    ///
+    /// Returns `true` if the unconfirmed block with the given height and hash from this peer is to be processed,
+    /// or an error if the peer is spamming unconfirmed blocks. The block is recorded as seen.
+    ///
+    fn is_unconfirmed_block_ready(&mut self, block_height: u32, block_hash: N::BlockHash, ledger_reader: &LedgerReader<N>) -> Result<bool> {
+        let is_ready = self.is_block_ready(block_height, block_hash, ledger_reader)?;
+
+        // Update the timestamp for the received block.
+        self.seen_inbound_blocks.insert(block_hash, SystemTime::now());
+        Ok(is_ready)
+    }
+
+    ///
+    /// Returns `true` if the compact block with the given height and hash from this peer is to be processed,
+    /// or an error if the peer is spamming compact blocks. The compact block is recorded as seen apart from
+    /// the blocks, so that the block may still be received in full, if it cannot be reconstructed.
+    ///
+    fn is_compact_block_ready(&mut self, block_height: u32, block_hash: N::BlockHash, ledger_reader: &LedgerReader<N>) -> Result<bool> {
+        // Drop the peer, if they have sent more than 10 compact blocks in the last 5 seconds.
+        let frequency = self
+            .seen_inbound_compact_blocks
+            .values()
+            .filter(|t| t.elapsed().unwrap().as_secs() <= 5)
+            .count();
+        if frequency >= 10 {
+            bail!(
+                "Dropping {} for spamming compact blocks (frequency = {})",
+                self.peer_ip(),
+                frequency
+            );
+        }
+
+        // Skip a compact block that was already received, before it is reconstructed again.
+        let is_router_ready = match self.seen_inbound_compact_blocks.get(&block_hash) {
+            Some(last_seen) => last_seen.elapsed().unwrap().as_secs() > E::RADIO_SILENCE_IN_SECS,
+            None => true,
+        };
+        let is_ready = is_router_ready && self.is_block_ready(block_height, block_hash, ledger_reader)?;
+
+        // Update the timestamp for the received compact block.
+        self.seen_inbound_compact_blocks.insert(block_hash, SystemTime::now());
+        Ok(is_ready)
+    }
+
+    ///
+    /// Returns `true` if the block with the given height and hash from this peer is to be processed,
+    /// or an error if the peer is spamming unconfirmed blocks.
+    ///
+    fn is_block_ready(&self, block_height: u32, block_hash: N::BlockHash, ledger_reader: &LedgerReader<N>) -> Result<bool> {
+        // Drop the peer, if they have sent more than 5 unconfirmed blocks in the last 5 seconds.
+        let frequency = self
+            .seen_inbound_blocks
+            .values()
+            .filter(|t| t.elapsed().unwrap().as_secs() <= 5)
+            .count();
+        if frequency >= 10 {
+            bail!(
+                "Dropping {} for spamming unconfirmed blocks (frequency = {})",
+                self.peer_ip(),
+                frequency
+            );
+        }
+
+        // Retrieve the last seen timestamp of the received block.
+        let is_router_ready = match self.seen_inbound_blocks.get(&block_hash) {
+            Some(last_seen) => last_seen.elapsed().unwrap().as_secs() > E::RADIO_SILENCE_IN_SECS,
+            None => true,
+        };
+
+        // Ensure the unconfirmed block is at least within 2 blocks of the latest block height,
+        // and no more that 2 blocks ahead of the latest block height.
+        // If it is stale, skip the routing of this unconfirmed block to the ledger.
+        let latest_block_height = ledger_reader.latest_block_height();
+        let lower_bound = latest_block_height.saturating_sub(2);
+        let upper_bound = latest_block_height.saturating_add(20);
+        let is_within_range = block_height >= lower_bound && block_height <= upper_bound;
+
+        // Ensure the node is not peering.
+        let is_node_ready = !E::status().is_peering();
+
+        // If this node is a beacon or sync node, skip this message.
+        let is_node_type_ready = E::NODE_TYPE != NodeType::Beacon && E::NODE_TYPE != NodeType::Sync;
+
+        Ok(is_node_type_ready && is_router_ready && is_within_range && is_node_ready)
+    }
+
     /// Performs the handshake protocol, returning the listener IP and nonce of the peer upon success.
+    ///
+    /// If both nodes advertise the Noise message version, the connection is encrypted
+    /// after the challenge requests, and the identity of the peer is returned as well.
+    #[allow(clippy::too_many_arguments)]
     async fn handshake(
-        outbound_socket: &mut Framed<TcpStream, Message<N, E>>,
+        outbound_socket: &mut Framed<TcpStream, PeerCodec<N, E>>,
         local_ip: SocketAddr,
         local_nonce: u64,
         local_cumulative_weight: u128,
+        local_pruned_block_height: Option<u32>,
         connected_nonces: &[u64],
-    ) -> Result<(SocketAddr, u64, NodeType, Status)> {
+        identity: Option<&NodeIdentity>,
+    ) -> Result<(SocketAddr, u64, NodeType, Status, Option<u32>, Option<PeerIdentity>)> {
         // Get the IP address of the peer.
         let mut peer_ip = outbound_socket.get_ref().peer_addr()?;
 
         // Retrieve the genesis block header.
         let genesis_header = N::genesis_block().header();
 
+        // Advertise support for the encrypted transport only if this node has an identity.
+        let local_version = match identity {
+            Some(_) => E::NOISE_MESSAGE_VERSION,
+            None => E::MESSAGE_VERSION,
+        };
+
         // Send a challenge request to the peer.
         let message = Message::<N, E>::ChallengeRequest(
-            E::MESSAGE_VERSION,
+            local_version,
             N::ALEO_MAXIMUM_FORK_DEPTH,
             E::NODE_TYPE,
             E::status().get(),
             local_ip.port(),
             local_nonce,
             local_cumulative_weight,
+            local_pruned_block_height,
         );
+        // Keep the serialized challenge request, to bind it into the encrypted connection.
+        let mut local_challenge_request = Vec::new();
+        message.serialize_into(&mut local_challenge_request)?;
         trace!("Sending '{}-A' to {}", message.name(), peer_ip);
         outbound_socket.send(message).await?;
 
         // Wait for the counterparty challenge request to come in.
-        let (peer_nonce, node_type, status) = match outbound_socket.next().await {
+        let (peer_nonce, node_type, status, pruned_block_height, peer_identity) = match outbound_socket.next().await {
             Some(Ok(message)) => {
                 // Process the message.
                 trace!("Received '{}-B' from {}", message.name(), peer_ip);
+                let mut peer_challenge_request = Vec::new();
+                message.serialize_into(&mut peer_challenge_request)?;
                 match message {
                     Message::ChallengeRequest(
                         version,
@@ -194,6 +440,7 @@ impl<N: Network, E: Environment> Peer<N, E> {
                         listener_port,
                         peer_nonce,
                         peer_cumulative_weight,
+                        peer_pruned_block_height,
                     ) => {
                         // Ensure the message protocol version is not outdated.
                         if version < E::MESSAGE_VERSION {
@@ -261,6 +508,16 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                 bail!("Unable to reach '{}': '{:?}'", peer_ip, error);
                             }
                         }
+                        // Encrypt the connection if both nodes support it, before the challenge response.
+                        let peer_identity = match identity {
+                            Some(identity) if version >= E::NOISE_MESSAGE_VERSION => {
+                                let local_challenge_request = (local_nonce, &local_challenge_request[..]);
+                                let peer_challenge_request = (peer_nonce, &peer_challenge_request[..]);
+                                Some(noise_handshake(outbound_socket, identity, local_challenge_request, peer_challenge_request).await?)
+                            }
+                            _ => None,
+                        };
+
                         // Send the challenge response.
                         let message = Message::ChallengeResponse(Data::Object(genesis_header.clone()));
                         trace!("Sending '{}-B' to {}", message.name(), peer_ip);
@@ -270,7 +527,7 @@ impl<N: Network, E: Environment> Peer<N, E> {
                         let status = Status::new();
                         status.update(peer_status);
 
-                        (peer_nonce, node_type, status)
+                        (peer_nonce, node_type, status, peer_pruned_block_height, peer_identity)
                     }
                     Message::Disconnect(reason) => {
                         bail!("Peer {} disconnected for the following reason: {:?}", peer_ip, reason);
@@ -296,7 +553,7 @@ impl<N: Network, E: Environment> Peer<N, E> {
                         // Perform the deferred non-blocking deserialization of the block header.
                         let block_header = block_header.deserialize().await?;
                         match &block_header == genesis_header {
-                            true => Ok((peer_ip, peer_nonce, node_type, status)),
+                            true => Ok((peer_ip, peer_nonce, node_type, status, pruned_block_height, peer_identity)),
                             false => Err(anyhow!("Challenge response from {} failed, received '{}'", peer_ip, block_header)),
                         }
                     }
@@ -321,6 +578,7 @@ impl<N: Network, E: Environment> Peer<N, E> {
     #[allow(clippy::too_many_arguments)]
     pub(super) async fn handler(
         stream: TcpStream,
+        connection_ip: SocketAddr,
         local_ip: SocketAddr,
         local_nonce: u64,
         peers_router: &PeersRouter<N, E>,
@@ -330,14 +588,39 @@ impl<N: Network, E: Environment> Peer<N, E> {
         operator_router: OperatorRouter<N>,
         connected_nonces: Vec<u64>,
         connection_result: Option<ConnectionResult>,
+        identity: Option<NodeIdentity>,
     ) {
         let peers_router = peers_router.clone();
+        // A connection is outbound if this node initiated it, which is when a connection result is requested.
+        let direction = match connection_result.is_some() {
+            true => ConnectionDirection::Outbound,
+            false => ConnectionDirection::Inbound,
+        };
 
         // Procure a resource id to register the task with, as it might be terminated at any point in time.
         let peer_resource_id = E::resources().procure_id();
         E::resources().register_task(Some(peer_resource_id), task::spawn(async move {
             // Register our peer with state which internally sets up some channels.
-            let mut peer = match Peer::new(stream, local_ip, local_nonce, &peers_router, &ledger_reader, &connected_nonces).await {
+            let peer = Peer::new(
+                stream,
+                local_ip,
+                local_nonce,
+                &peers_router,
+                &ledger_reader,
+                &connected_nonces,
+                identity.as_ref(),
+                direction,
+            )
+            .await;
+
+            // An inbound connection no longer counts as connecting, once its handshake has ended in any way.
+            if direction == ConnectionDirection::Inbound {
+                if let Err(error) = peers_router.send(PeersRequest::PeerHandshakeEnded(connection_ip)).await {
+                    warn!("[PeerHandshakeEnded] {}", error);
+                }
+            }
+
+            let mut peer = match peer {
                 Ok(peer) => {
                     // If the optional connection result router is given, report a successful connection result.
                     if let Some(router) = connection_result {
@@ -365,9 +648,20 @@ impl<N: Network, E: Environment> Peer<N, E> {
             let peer_ip = peer.peer_ip();
             info!("Connected to {}", peer_ip);
 
+            // Report the statistics of the peer to the peers once per heartbeat, if they changed.
+            let mut stats_interval = interval(Duration::from_secs(E::HEARTBEAT_IN_SECS));
+
             // Process incoming messages until this stream is disconnected.
             loop {
                 tokio::select! {
+                    _ = stats_interval.tick() => {
+                        if peer.is_stats_changed {
+                            peer.is_stats_changed = false;
+                            if let Err(error) = peers_router.send(PeersRequest::PeerStats(peer_ip, peer.stats())).await {
+                                warn!("[PeerStats] {}", error);
+                            }
+                        }
+                    }
                     // Message channel is routing a message outbound to the peer.
                     Some(mut message) = peer.outbound_handler.recv() => {
                         // Disconnect if the peer has not communicated back within the predefined time.
@@ -381,16 +675,13 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                     // Perform non-blocking serialisation of the block header.
                                     let serialized_header = Data::serialize(data.clone()).await.expect("Block header serialization is bugged");
                                     let _ = std::mem::replace(data, Data::Buffer(serialized_header));
+                                    // Measure the round trip until the corresponding `Pong`.
+                                    peer.last_ping_sent = Some(Instant::now());
 
                                     true
                                 }
                                 Message::UnconfirmedBlock(block_height, block_hash, ref mut data) => {
-                                    // Retrieve the last seen timestamp of this block for this peer.
-                                    let last_seen = peer.seen_outbound_blocks.entry(block_hash).or_insert(SystemTime::UNIX_EPOCH);
-                                    let is_ready_to_send = last_seen.elapsed().unwrap().as_secs() > E::RADIO_SILENCE_IN_SECS;
-
-                                    // Update the timestamp for the peer and sent block.
-                                    peer.seen_outbound_blocks.insert(block_hash, SystemTime::now());
+                                    let is_ready_to_send = peer.is_ready_to_send_block(block_hash);
                                     // Report the unconfirmed block height.
                                     if is_ready_to_send {
                                         trace!("Preparing to send 'UnconfirmedBlock {}' to {}", block_height, peer_ip);
@@ -402,6 +693,19 @@ impl<N: Network, E: Environment> Peer<N, E> {
 
                                     is_ready_to_send
                                 }
+                                Message::CompactBlock(block_height, block_hash, ref mut data) => {
+                                    let is_ready_to_send = peer.is_ready_to_send_block(block_hash);
+                                    // Report the unconfirmed block height.
+                                    if is_ready_to_send {
+                                        trace!("Preparing to send 'CompactBlock {}' to {}", block_height, peer_ip);
+                                    }
+
+                                    // Perform non-blocking serialization of the compact block (if it hasn't been serialized yet).
+                                    let serialized_block = Data::serialize(data.clone()).await.expect("Compact block serialization is bugged");
+                                    let _ = std::mem::replace(data, Data::Buffer(serialized_block));
+
+                                    is_ready_to_send
+                                }
                                 Message::UnconfirmedTransaction(ref mut data) => {
                                     let transaction = if let Data::Object(transaction) = data {
                                         transaction
@@ -459,6 +763,24 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                     peer.last_seen = Instant::now();
                                 }
                             }
+                            // Ensure the peer is within its rate limits, dropping the message otherwise.
+                            let bytes_received = peer.outbound_socket.codec().bytes_received();
+                            let message_size = bytes_received.saturating_sub(peer.bytes_received);
+                            peer.bytes_received = bytes_received;
+                            peer.stats.record_received(message.name(), message_size);
+                            peer.is_stats_changed = true;
+                            if let Err(error) = peer.rate_limiter.check(message.name(), message_size) {
+                                debug!("Dropping '{}' from {}: {}", message.name(), peer_ip, error);
+                                #[cfg(any(feature = "test", feature = "prometheus"))]
+                                metrics::increment_counter!(metrics::peers::RATE_LIMITED);
+
+                                // Route a `Failure` to the ledger.
+                                let failure = Misbehavior::RateLimitExceeded(format!("{}", error));
+                                if let Err(error) = ledger_router.send(LedgerRequest::Failure(peer_ip, failure)).await {
+                                    warn!("[Failure] {}", error);
+                                }
+                                continue;
+                            }
                             // Process the message.
                             trace!("Received '{}' from {}", message.name(), peer_ip);
                             match message {
@@ -467,27 +789,50 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                     let number_of_blocks = end_block_height.saturating_sub(start_block_height);
                                     if number_of_blocks > E::MAXIMUM_BLOCK_REQUEST {
                                         // Route a `Failure` to the ledger.
-                                        let failure = format!("Attempted to request {} blocks", number_of_blocks);
+                                        let failure = Misbehavior::OversizeRequest(format!("Attempted to request {} blocks", number_of_blocks));
                                         if let Err(error) = ledger_router.send(LedgerRequest::Failure(peer_ip, failure)).await {
                                             warn!("[Failure] {}", error);
                                         }
                                         continue;
                                     }
-                                    // Retrieve the requested blocks.
-                                    let blocks = match ledger_reader.get_blocks(start_block_height, end_block_height) {
+                                    // Skip the requested blocks that have been pruned, as the pruned block height may have
+                                    // advanced since it was disclosed in the handshake, and serve the remaining blocks.
+                                    let start_block_height = match ledger_reader.get_pruned_block_height() {
+                                        Ok(Some(pruned_block_height)) if start_block_height != 0 && start_block_height <= pruned_block_height => {
+                                            // If every requested block has been pruned, disconnect from the peer, so that it learns
+                                            // of the latest pruned block height from the next handshake and requests its blocks elsewhere.
+                                            if end_block_height <= pruned_block_height {
+                                                debug!("Unable to serve blocks {} to {} to {}, as they were pruned", start_block_height, end_block_height, peer_ip);
+                                                // Peers that have not advertised the latest message version may not know the `BlocksPruned` reason.
+                                                let reason = match peer.version >= E::COMPACT_BLOCK_MESSAGE_VERSION {
+                                                    true => DisconnectReason::BlocksPruned,
+                                                    false => DisconnectReason::NoReasonGiven,
+                                                };
+                                                if let Err(error) = peer.send(Message::Disconnect(reason)).await {
+                                                    warn!("[Disconnect] {}", error);
+                                                }
+                                                break;
+                                            }
+                                            pruned_block_height + 1
+                                        }
+                                        _ => start_block_height,
+                                    };
+                                    // Retrieve the requested blocks, which are cached in their serialized form.
+                                    let blocks = match ledger_reader.get_serialized_blocks(start_block_height, end_block_height) {
                                         Ok(blocks) => blocks,
                                         Err(error) => {
                                             // Route a `Failure` to the ledger.
-                                            if let Err(error) = ledger_router.send(LedgerRequest::Failure(peer_ip, format!("{}", error))).await {
+                                            let failure = Misbehavior::ProtocolViolation(format!("{}", error));
+                                            if let Err(error) = ledger_router.send(LedgerRequest::Failure(peer_ip, failure)).await {
                                                 warn!("[Failure] {}", error);
                                             }
                                             continue;
                                         }
                                     };
                                     // Send a `BlockResponse` message for each block to the peer.
-                                    for block in blocks {
-                                        debug!("Sending 'BlockResponse {}' to {}", block.height(), peer_ip);
-                                        if let Err(error) = peer.outbound_socket.send(Message::BlockResponse(Data::Object(block))).await {
+                                    for (block_height, block) in (start_block_height..).zip(blocks) {
+                                        debug!("Sending 'BlockResponse {}' to {}", block_height, peer_ip);
+                                        if let Err(error) = peer.send(Message::BlockResponse(Data::Buffer(block))).await {
                                             warn!("[BlockResponse] {}", error);
                                             break;
                                         }
@@ -513,12 +858,18 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                             }
                                         },
                                         // Route the `Failure` to the ledger.
-                                        Err(error) => if let Err(error) = ledger_router.send(LedgerRequest::Failure(peer_ip, format!("{}", error))).await {
-                                            warn!("[Failure] {}", error);
+                                        Err(error) => {
+                                            let failure = Misbehavior::BadDeserialization(format!("{}", error));
+                                            if let Err(error) = ledger_router.send(LedgerRequest::Failure(peer_ip, failure)).await {
+                                                warn!("[Failure] {}", error);
+                                            }
                                         }
                                     }
                                 }
-                                Message::ChallengeRequest(..) | Message::ChallengeResponse(..) | Message::NewBlockTemplate(..) => {
+                                Message::ChallengeRequest(..)
+                                | Message::ChallengeResponse(..)
+                                | Message::NoiseHandshake(..)
+                                | Message::NewBlockTemplate(..) => {
                                     // Peer is not following the protocol.
                                     warn!("Peer {} is not following the protocol", peer_ip);
                                     break;
@@ -614,9 +965,9 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                     // Perform the deferred non-blocking deserialization of block locators.
                                     let request = match block_locators.deserialize().await {
                                         // Route the `Pong` to the ledger.
-                                        Ok(block_locators) => LedgerRequest::Pong(peer_ip, peer.node_type, peer.status.get(), is_fork, block_locators),
+                                        Ok(block_locators) => LedgerRequest::Pong(peer_ip, peer.node_type, peer.status.get(), is_fork, block_locators, peer.pruned_block_height),
                                         // Route the `Failure` to the ledger.
-                                        Err(error) => LedgerRequest::Failure(peer_ip, format!("{}", error)),
+                                        Err(error) => LedgerRequest::Failure(peer_ip, Misbehavior::BadDeserialization(format!("{}", error))),
                                     };
 
                                     // Route the request to the ledger.
@@ -624,6 +975,15 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                         warn!("[Pong] {}", error);
                                     }
 
+                                    // Report the round-trip time of the `Ping` to the peers.
+                                    if let Some(last_ping_sent) = peer.last_ping_sent.take() {
+                                        let round_trip_time = last_ping_sent.elapsed();
+                                        peer.stats.latency_in_millis = Some(round_trip_time.as_millis() as u64);
+                                        if let Err(error) = peers_router.send(PeersRequest::PeerLatency(peer_ip, round_trip_time)).await {
+                                            warn!("[PeerLatency] {}", error);
+                                        }
+                                    }
+
                                     // Spawn an asynchronous task for the `Ping` request.
                                     let peers_router = peers_router.clone();
                                     let ledger_reader = ledger_reader.clone();
@@ -638,7 +998,7 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                         let latest_block_header = ledger_reader.latest_block_header();
 
                                         // Send a `Ping` request to the peer.
-                                        let message = Message::Ping(E::MESSAGE_VERSION, N::ALEO_MAXIMUM_FORK_DEPTH, E::NODE_TYPE, E::status().get(), latest_block_hash, Data::Object(latest_block_header));
+                                        let message = Message::Ping(E::COMPACT_BLOCK_MESSAGE_VERSION, N::ALEO_MAXIMUM_FORK_DEPTH, E::NODE_TYPE, E::status().get(), latest_block_hash, Data::Object(latest_block_header));
                                         if let Err(error) = peers_router.send(PeersRequest::MessageSend(peer_ip, message)).await {
                                             warn!("[Ping] {}", error);
                                         }
@@ -647,55 +1007,105 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                     }));
                                 }
                                 Message::UnconfirmedBlock(block_height, block_hash, block) => {
-                                    // Drop the peer, if they have sent more than 5 unconfirmed blocks in the last 5 seconds.
-                                    let frequency = peer.seen_inbound_blocks.values().filter(|t| t.elapsed().unwrap().as_secs() <= 5).count();
-                                    if frequency >= 10 {
-                                        warn!("Dropping {} for spamming unconfirmed blocks (frequency = {})", peer_ip, frequency);
-                                        // Send a `PeerRestricted` message.
-                                        if let Err(error) = peers_router.send(PeersRequest::PeerRestricted(peer_ip)).await {
-                                            warn!("[PeerRestricted] {}", error);
+                                    match peer.is_unconfirmed_block_ready(block_height, block_hash, &ledger_reader) {
+                                        Err(error) => {
+                                            warn!("{}", error);
+                                            // Send a `PeerRestricted` message.
+                                            if let Err(error) = peers_router.send(PeersRequest::PeerRestricted(peer_ip)).await {
+                                                warn!("[PeerRestricted] {}", error);
+                                            }
+                                            break;
                                         }
-                                        break;
-                                    }
-
-                                    // Retrieve the last seen timestamp of the received block.
-                                    let last_seen = peer.seen_inbound_blocks.entry(block_hash).or_insert(SystemTime::UNIX_EPOCH);
-                                    let is_router_ready = last_seen.elapsed().unwrap().as_secs() > E::RADIO_SILENCE_IN_SECS;
-
-                                    // Update the timestamp for the received block.
-                                    peer.seen_inbound_blocks.insert(block_hash, SystemTime::now());
-
-                                    // Ensure the unconfirmed block is at least within 2 blocks of the latest block height,
-                                    // and no more that 2 blocks ahead of the latest block height.
-                                    // If it is stale, skip the routing of this unconfirmed block to the ledger.
-                                    let latest_block_height = ledger_reader.latest_block_height();
-                                    let lower_bound = latest_block_height.saturating_sub(2);
-                                    let upper_bound = latest_block_height.saturating_add(20);
-                                    let is_within_range = block_height >= lower_bound && block_height <= upper_bound;
-
-                                    // Ensure the node is not peering.
-                                    let is_node_ready = !E::status().is_peering();
+                                        Ok(false) => trace!("Skipping 'UnconfirmedBlock {}' from {}", block_height, peer_ip),
+                                        Ok(true) => {
+                                            // Perform the deferred non-blocking deserialization of the block.
+                                            let request = match block.deserialize().await {
+                                                // Ensure the claimed block height and block hash matches in the deserialized block.
+                                                Ok(block) => match block_height == block.height() && block_hash == block.hash() {
+                                                    // Route the `UnconfirmedBlock` to the ledger.
+                                                    true => LedgerRequest::UnconfirmedBlock(peer_ip, block, prover_router.clone()),
+                                                    // Route the `Failure` to the ledger.
+                                                    false => LedgerRequest::Failure(peer_ip, Misbehavior::InvalidBlock("Malformed UnconfirmedBlock message".to_string()))
+                                                },
+                                                // Route the `Failure` to the ledger.
+                                                Err(error) => LedgerRequest::Failure(peer_ip, Misbehavior::BadDeserialization(format!("{}", error))),
+                                            };
 
-                                    // If this node is a beacon or sync node, skip this message, after updating the timestamp.
-                                    if E::NODE_TYPE == NodeType::Beacon || E::NODE_TYPE == NodeType::Sync || !is_router_ready || !is_within_range || !is_node_ready {
-                                        trace!("Skipping 'UnconfirmedBlock {}' from {}", block_height, peer_ip)
-                                    } else {
-                                        // Perform the deferred non-blocking deserialization of the block.
-                                        let request = match block.deserialize().await {
-                                            // Ensure the claimed block height and block hash matches in the deserialized block.
-                                            Ok(block) => match block_height == block.height() && block_hash == block.hash() {
-                                                // Route the `UnconfirmedBlock` to the ledger.
-                                                true => LedgerRequest::UnconfirmedBlock(peer_ip, block, prover_router.clone()),
+                                            // Route the request to the ledger.
+                                            if let Err(error) = ledger_router.send(request).await {
+                                                warn!("[UnconfirmedBlock] {}", error);
+                                            }
+                                        }
+                                    }
+                                }
+                                Message::CompactBlock(block_height, block_hash, compact_block) => {
+                                    match peer.is_compact_block_ready(block_height, block_hash, &ledger_reader) {
+                                        Err(error) => {
+                                            warn!("{}", error);
+                                            // Send a `PeerRestricted` message.
+                                            if let Err(error) = peers_router.send(PeersRequest::PeerRestricted(peer_ip)).await {
+                                                warn!("[PeerRestricted] {}", error);
+                                            }
+                                            break;
+                                        }
+                                        Ok(false) => trace!("Skipping 'CompactBlock {}' from {}", block_height, peer_ip),
+                                        Ok(true) => {
+                                            // Perform the deferred non-blocking deserialization of the compact block.
+                                            let request = match compact_block.deserialize().await {
+                                                // Ensure the claimed block height and block hash matches in the deserialized compact block.
+                                                Ok(compact_block) => match block_height == compact_block.header.height() && block_hash == compact_block.block_hash {
+                                                    true => Ok(ProverRequest::CompactBlock(peer_ip, compact_block)),
+                                                    false => Err(Misbehavior::InvalidBlock("Malformed CompactBlock message".to_string())),
+                                                },
+                                                Err(error) => Err(Misbehavior::BadDeserialization(format!("{}", error))),
+                                            };
+
+                                            match request {
+                                                // Route the `CompactBlock` to the prover, which reconstructs the block from its memory pool.
+                                                Ok(request) => if let Err(error) = prover_router.send(request).await {
+                                                    warn!("[CompactBlock] {}", error);
+                                                },
                                                 // Route the `Failure` to the ledger.
-                                                false => LedgerRequest::Failure(peer_ip, "Malformed UnconfirmedBlock message".to_string())
-                                            },
-                                            // Route the `Failure` to the ledger.
-                                            Err(error) => LedgerRequest::Failure(peer_ip, format!("{}", error)),
-                                        };
-
-                                        // Route the request to the ledger.
-                                        if let Err(error) = ledger_router.send(request).await {
-                                            warn!("[UnconfirmedBlock] {}", error);
+                                                Err(failure) => if let Err(error) = ledger_router.send(LedgerRequest::Failure(peer_ip, failure)).await {
+                                                    warn!("[Failure] {}", error);
+                                                },
+                                            }
+                                        }
+                                    }
+                                }
+                                Message::GetBlockTransactions(block_hash, transaction_indices) => {
+                                    // Retrieve the requested transactions of a block this node has relayed in compact form.
+                                    match peer.get_block_transactions(&ledger_reader, &block_hash, &transaction_indices) {
+                                        // Send a `BlockTransactions` message to the peer.
+                                        Ok(transactions) => {
+                                            if let Err(error) = peer.send(Message::BlockTransactions(block_hash, Data::Object(transactions))).await {
+                                                warn!("[BlockTransactions] {}", error);
+                                            }
+                                        }
+                                        // Route the `Failure` to the ledger.
+                                        Err(error) => {
+                                            let failure = Misbehavior::ProtocolViolation(format!("{}", error));
+                                            if let Err(error) = ledger_router.send(LedgerRequest::Failure(peer_ip, failure)).await {
+                                                warn!("[Failure] {}", error);
+                                            }
+                                        }
+                                    }
+                                }
+                                Message::BlockTransactions(block_hash, transactions) => {
+                                    // Perform the deferred non-blocking deserialization of the transactions.
+                                    match transactions.deserialize().await {
+                                        // Route the `BlockTransactions` to the prover, which completes the compact block.
+                                        Ok(transactions) => {
+                                            if let Err(error) = prover_router.send(ProverRequest::BlockTransactions(peer_ip, block_hash, transactions)).await {
+                                                warn!("[BlockTransactions] {}", error);
+                                            }
+                                        }
+                                        // Route the `Failure` to the ledger.
+                                        Err(error) => {
+                                            let failure = Misbehavior::BadDeserialization(format!("{}", error));
+                                            if let Err(error) = ledger_router.send(LedgerRequest::Failure(peer_ip, failure)).await {
+                                                warn!("[Failure] {}", error);
+                                            }
                                         }
                                     }
                                 }
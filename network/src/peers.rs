@@ -14,18 +14,42 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{Data, DisconnectReason, LedgerReader, LedgerRouter, Message, OperatorRouter, OutboundRouter, Peer, ProverRouter};
+use crate::{
+    helpers::{
+        exceeded_inbound_limit,
+        CompactBlock,
+        ConnectionDirection,
+        NodeIdentity,
+        PeerAddr,
+        PeerIdentity,
+        PeerStats,
+        Resolver,
+        Subnet,
+        SystemResolver,
+    },
+    Data,
+    DisconnectReason,
+    LedgerReader,
+    LedgerRouter,
+    Message,
+    OperatorRouter,
+    OutboundRouter,
+    Peer,
+    ProverRouter,
+};
 use snarkos_environment::Environment;
+use snarkos_storage::{storage::Storage, BanSource, PeerBook};
 use snarkvm::dpc::prelude::*;
 
 #[cfg(any(feature = "test", feature = "prometheus"))]
 use snarkos_metrics as metrics;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use rand::{prelude::IteratorRandom, rngs::OsRng, thread_rng, Rng};
 use std::{
     collections::{HashMap, HashSet},
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
+    path::Path,
     sync::Arc,
     time::{Duration, Instant, SystemTime},
 };
@@ -77,14 +101,34 @@ pub enum PeersRequest<N: Network, E: Environment> {
         OperatorRouter<N>,
         ProverRouter<N>,
     ),
-    /// PeerConnected := (peer_ip, peer_nonce, outbound_router)
-    PeerConnected(SocketAddr, u64, OutboundRouter<N, E>),
+    /// PeerIdentified := (peer_ip, peer_identity, identity_result), without an identity if the connection is unencrypted
+    PeerIdentified(SocketAddr, Option<PeerIdentity>, oneshot::Sender<bool>),
+    /// PeerHandshakeEnded := (peer_ip)
+    PeerHandshakeEnded(SocketAddr),
+    /// PeerConnected := (peer_ip, peer_nonce, node_type, direction, outbound_router)
+    PeerConnected(SocketAddr, u64, NodeType, ConnectionDirection, OutboundRouter<N, E>),
     PeerIsProver(SocketAddr),
     PeerIsPoolServer(SocketAddr),
     /// PeerDisconnected := (peer_ip)
     PeerDisconnected(SocketAddr),
     /// PeerRestricted := (peer_ip)
     PeerRestricted(SocketAddr),
+    /// PeerStats := (peer_ip, peer_stats)
+    PeerStats(SocketAddr, PeerStats),
+    /// PeerBlockHeight := (peer_ip, block_height)
+    PeerBlockHeight(SocketAddr, u32),
+    /// PeerBanned := (peer_ip)
+    PeerBanned(SocketAddr),
+    /// PeerLatency := (peer_ip, round_trip_time)
+    PeerLatency(SocketAddr, Duration),
+    /// BanPeer := (peer_ip, duration)
+    BanPeer(SocketAddr, Duration),
+    /// UnbanPeer := (peer_ip)
+    UnbanPeer(SocketAddr),
+    /// AddNode := (peer_ip)
+    AddNode(SocketAddr),
+    /// RemoveNode := (peer_ip)
+    RemoveNode(SocketAddr),
     /// SendPeerResponse := (peer_ip)
     SendPeerResponse(SocketAddr),
     /// ReceivePeerResponse := (\[peer_ip\])
@@ -103,23 +147,52 @@ pub struct Peers<N: Network, E: Environment> {
     local_nonce: u64,
     /// The map connected peer IPs to their nonce and outbound message router.
     connected_peers: RwLock<HashMap<SocketAddr, (u64, OutboundRouter<N, E>)>>,
+    /// The set of connected peer IPs that initiated their connection with this node.
+    inbound_peers: RwLock<HashSet<SocketAddr>>,
+    /// The set of peer IPs of the inbound connections that are performing the handshake.
+    connecting_peers: RwLock<HashSet<SocketAddr>>,
     /// The set of candidate peer IPs.
     candidate_peers: RwLock<HashSet<SocketAddr>>,
     /// The set of restricted peer IPs.
     restricted_peers: RwLock<HashMap<SocketAddr, Instant>>,
     prover_peers: RwLock<HashSet<SocketAddr>>,
     poolserver_peers: RwLock<HashSet<SocketAddr>>,
+    /// The map of connected peer IPs to their traffic statistics.
+    peer_stats: RwLock<HashMap<SocketAddr, PeerStats>>,
     /// The map of peers to their first-seen port number, number of attempts, and timestamp of the last inbound connection request.
     seen_inbound_connections: RwLock<HashMap<SocketAddr, ((u16, u32), SystemTime)>>,
     /// The map of peers to the timestamp of their last outbound connection request.
     seen_outbound_connections: RwLock<HashMap<SocketAddr, SystemTime>>,
+    /// The identity key of this node, if its connections are encrypted.
+    identity: Option<NodeIdentity>,
+    /// The persistent history of the peers, which ranks the candidate peers and records their bans.
+    peer_book: PeerBook<N>,
+    /// The set of trusted nodes added at runtime, as recorded in the peer book.
+    trusted_peers: RwLock<HashSet<SocketAddr>>,
+    /// The resolver of the hostnames of peers.
+    resolver: Arc<dyn Resolver>,
+    /// The map of the hostnames of trusted nodes to their most recently resolved IPs.
+    trusted_hosts: RwLock<HashMap<PeerAddr, Vec<SocketAddr>>>,
+    /// The DNS seeds, whose records are peers to bootstrap the node server with.
+    dns_seeds: RwLock<Vec<PeerAddr>>,
 }
 
 impl<N: Network, E: Environment> Peers<N, E> {
     ///
-    /// Initializes a new instance of `Peers`.
+    /// Opens a new instance of `Peers`, with its peer book at the given storage path.
+    /// Hostnames are resolved with the given resolver, or else the resolver of the operating system.
     ///
-    pub async fn new(local_ip: SocketAddr, local_nonce: Option<u64>) -> Arc<Self> {
+    pub async fn open<S: Storage, P: AsRef<Path>>(
+        path: P,
+        local_ip: SocketAddr,
+        local_nonce: Option<u64>,
+        identity: Option<NodeIdentity>,
+        resolver: Option<Arc<dyn Resolver>>,
+    ) -> Result<Arc<Self>> {
+        // Open the peer book.
+        let peer_book = PeerBook::open::<S, _>(path)?;
+        let trusted_peers = peer_book.trusted_peers().into_iter().collect();
+
         // Initialize an mpsc channel for sending requests to the `Peers` struct.
         let (peers_router, mut peers_handler) = mpsc::channel(1024);
 
@@ -135,14 +208,27 @@ impl<N: Network, E: Environment> Peers<N, E> {
             local_ip,
             local_nonce,
             connected_peers: Default::default(),
+            inbound_peers: Default::default(),
+            connecting_peers: Default::default(),
             candidate_peers: Default::default(),
             restricted_peers: Default::default(),
             prover_peers: Default::default(),
             poolserver_peers: Default::default(),
+            peer_stats: Default::default(),
             seen_inbound_connections: Default::default(),
             seen_outbound_connections: Default::default(),
+            identity,
+            peer_book,
+            trusted_peers: RwLock::new(trusted_peers),
+            resolver: resolver.unwrap_or_else(|| Arc::new(SystemResolver)),
+            trusted_hosts: Default::default(),
+            dns_seeds: Default::default(),
         });
 
+        // Resume from the highest-scoring peers of the previous sessions.
+        let best_peers = peers.peer_book.best_peers(E::MAXIMUM_CANDIDATE_PEERS);
+        peers.add_candidate_peers(best_peers.iter()).await;
+
         // Initialize the peers router process.
         {
             let peers = peers.clone();
@@ -175,7 +261,7 @@ impl<N: Network, E: Environment> Peers<N, E> {
             let _ = handler.await;
         }
 
-        peers
+        Ok(peers)
     }
 
     /// Returns an instance of the peers router.
@@ -194,10 +280,61 @@ impl<N: Network, E: Environment> Peers<N, E> {
     /// Returns `true` if the given IP is restricted.
     ///
     pub async fn is_restricted(&self, ip: SocketAddr) -> bool {
-        match self.restricted_peers.read().await.get(&ip) {
+        let is_restricted = match self.restricted_peers.read().await.get(&ip) {
             Some(timestamp) => timestamp.elapsed().as_secs() < E::RADIO_SILENCE_IN_SECS,
             None => false,
+        };
+        // A ban in the peer book applies to the IP, as the peer may connect from another port, and persists across restarts.
+        is_restricted || self.peer_book.is_banned(ip.ip())
+    }
+
+    ///
+    /// Returns the trusted nodes of the node, including those added at runtime and those given by hostname.
+    ///
+    pub async fn trusted_nodes(&self) -> HashSet<SocketAddr> {
+        let mut trusted_nodes = (*E::trusted_nodes()).clone();
+        trusted_nodes.extend(self.trusted_peers.read().await.iter());
+        trusted_nodes.extend(self.trusted_hosts.read().await.values().flatten());
+        trusted_nodes
+    }
+
+    ///
+    /// Returns the IPs of the given peer address, resolving its hostname.
+    ///
+    pub async fn resolve(&self, peer_addr: &PeerAddr) -> Result<Vec<SocketAddr>> {
+        peer_addr.resolve(&*self.resolver).await
+    }
+
+    ///
+    /// Replaces the trusted nodes given by hostname, which are resolved again whenever they are not connected.
+    ///
+    pub async fn set_trusted_hosts(&self, hosts: HashSet<PeerAddr>) {
+        let mut trusted_hosts = HashMap::with_capacity(hosts.len());
+        for host in hosts {
+            let peer_ips = match self.resolve(&host).await {
+                Ok(peer_ips) => peer_ips,
+                Err(error) => {
+                    warn!("Failed to resolve the trusted node {}: {}", host, error);
+                    vec![]
+                }
+            };
+            trusted_hosts.insert(host, peer_ips);
         }
+        *self.trusted_hosts.write().await = trusted_hosts;
+    }
+
+    ///
+    /// Replaces the DNS seeds, which are queried for peers whenever the node has no candidate peers.
+    ///
+    pub async fn set_dns_seeds(&self, dns_seeds: Vec<PeerAddr>) {
+        *self.dns_seeds.write().await = dns_seeds;
+    }
+
+    ///
+    /// Returns the peer book of the node.
+    ///
+    pub fn peer_book(&self) -> &PeerBook<N> {
+        &self.peer_book
     }
 
     ///
@@ -241,6 +378,27 @@ impl<N: Network, E: Environment> Peers<N, E> {
             .count()
     }
 
+    ///
+    /// Returns the traffic statistics of each connected peer.
+    ///
+    pub async fn peer_stats(&self) -> HashMap<SocketAddr, PeerStats> {
+        self.peer_stats.read().await.clone()
+    }
+
+    ///
+    /// Returns `true` if the given peer is a prover.
+    ///
+    pub async fn is_prover(&self, peer_ip: &SocketAddr) -> bool {
+        self.prover_peers.read().await.contains(peer_ip)
+    }
+
+    ///
+    /// Returns `true` if the given peer is a pool server.
+    ///
+    pub async fn is_pool_server(&self, peer_ip: &SocketAddr) -> bool {
+        self.poolserver_peers.read().await.contains(peer_ip)
+    }
+
     ///
     /// Returns the number of connected peers.
     ///
@@ -286,6 +444,7 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     || (peer_ip.ip().is_unspecified() || peer_ip.ip().is_loopback()) && peer_ip.port() == self.local_ip.port()
                 {
                     debug!("Skipping connection request to {} (attempted to self-connect)", peer_ip);
+                    let _ = connection_result.send(Err(anyhow!("Attempted to self-connect to {}", peer_ip)));
                 }
                 // Ensure the node does not surpass the maximum number of peer connections.
                 else if self
@@ -295,14 +454,17 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     >= E::MAXIMUM_NUMBER_OF_PEERS
                 {
                     debug!("Skipping connection request to {} (maximum peers reached)", peer_ip);
+                    let _ = connection_result.send(Err(anyhow!("Maximum number of peers reached")));
                 }
                 // Ensure the peer is a new connection.
                 else if self.is_connected_to(peer_ip).await {
                     debug!("Skipping connection request to {} (already connected)", peer_ip);
+                    let _ = connection_result.send(Err(anyhow!("Already connected to {}", peer_ip)));
                 }
                 // Ensure the peer is not restricted.
                 else if self.is_restricted(peer_ip).await {
                     debug!("Skipping connection request to {} (restricted)", peer_ip);
+                    let _ = connection_result.send(Err(anyhow!("{} is restricted", peer_ip)));
                 }
                 // Attempt to open a TCP stream.
                 else {
@@ -314,6 +476,7 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     let elapsed = last_seen.elapsed().unwrap_or(Duration::MAX).as_secs();
                     if elapsed < E::RADIO_SILENCE_IN_SECS {
                         trace!("Skipping connection request to {} (tried {} secs ago)", peer_ip, elapsed);
+                        let _ = connection_result.send(Err(anyhow!("Attempted to connect to {} {} secs ago", peer_ip, elapsed)));
                     } else {
                         debug!("Connecting to {}...", peer_ip);
                         // Update the last seen timestamp for this peer.
@@ -328,6 +491,7 @@ impl<N: Network, E: Environment> Peers<N, E> {
                                 Ok(stream) => {
                                     Peer::handler(
                                         stream,
+                                        peer_ip,
                                         self.local_ip,
                                         self.local_nonce,
                                         &self.peers_router,
@@ -337,17 +501,22 @@ impl<N: Network, E: Environment> Peers<N, E> {
                                         operator_router,
                                         self.connected_nonces().await,
                                         Some(connection_result),
+                                        self.identity.clone(),
                                     )
                                     .await
                                 }
                                 Err(error) => {
                                     trace!("Failed to connect to '{}': '{:?}'", peer_ip, error);
                                     self.candidate_peers.write().await.remove(&peer_ip);
+                                    self.update_peer_book(|peer_book| peer_book.record_failure(peer_ip));
+                                    let _ = connection_result.send(Err(anyhow!("Failed to connect to {}: {}", peer_ip, error)));
                                 }
                             },
                             Err(error) => {
                                 error!("Unable to reach '{}': '{:?}'", peer_ip, error);
                                 self.candidate_peers.write().await.remove(&peer_ip);
+                                self.update_peer_book(|peer_book| peer_book.record_failure(peer_ip));
+                                let _ = connection_result.send(Err(anyhow!("Unable to reach {}: {}", peer_ip, error)));
                             }
                         };
                     }
@@ -364,22 +533,19 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     let num_excess_peers = number_of_connected_peers
                         .saturating_sub(self.poolserver_peers.read().await.len())
                         .saturating_sub(E::MAXIMUM_NUMBER_OF_PEERS);
+                    // Sync nodes, beacon nodes, trusted nodes and pool servers are exempt from disconnection.
+                    let (sync_nodes, beacon_nodes, trusted_nodes) = (E::sync_nodes(), E::beacon_nodes(), self.trusted_nodes().await);
+                    let poolserver_peers = self.poolserver_peers.read().await.clone();
                     let peer_ips_to_disconnect = self
                         .connected_peers
                         .read()
                         .await
                         .iter()
                         .filter(|(peer_ip, _)| {
-                            return if !E::sync_nodes().contains(peer_ip)
-                                && !E::beacon_nodes().contains(peer_ip)
-                                && !E::trusted_nodes().contains(peer_ip) {
-                                false
-                            } else {
-                                match self.poolserver_peers.try_read() {
-                                    Ok(peers) => !peers.contains(peer_ip),
-                                    Err(_) => false,
-                                }
-                            };
+                            !sync_nodes.contains(peer_ip)
+                                && !beacon_nodes.contains(peer_ip)
+                                && !trusted_nodes.contains(peer_ip)
+                                && !poolserver_peers.contains(peer_ip)
                         })
                         .take(num_excess_peers)
                         .map(|(&peer_ip, _)| peer_ip)
@@ -416,9 +582,10 @@ impl<N: Network, E: Environment> Peers<N, E> {
                 }
 
                 // Ensure that the trusted nodes are connected.
-                if !E::trusted_nodes().is_empty() {
+                self.resolve_trusted_hosts().await;
+                let trusted_nodes = self.trusted_nodes().await;
+                if !trusted_nodes.is_empty() {
                     let connected_peers = self.connected_peers().await.into_iter().collect::<HashSet<_>>();
-                    let trusted_nodes = E::trusted_nodes();
                     let disconnected_trusted_nodes = trusted_nodes.difference(&connected_peers).copied();
                     for peer_ip in disconnected_trusted_nodes {
                         // Initialize the connection process.
@@ -469,15 +636,42 @@ impl<N: Network, E: Environment> Peers<N, E> {
                 // Add the beacon nodes to the list of candidate peers.
                 self.add_candidate_peers(E::beacon_nodes().iter()).await;
 
+                // Add the peers of the DNS seeds to the list of candidate peers, if there are no other candidates.
+                if self.number_of_candidate_peers().await == 0 {
+                    let seed_peers = self.resolve_dns_seeds().await;
+                    self.add_candidate_peers(seed_peers.iter()).await;
+                }
+
+                // Bound the size of the peer book, discarding the lowest-scoring peers.
+                self.update_peer_book(|peer_book| peer_book.prune(E::MAXIMUM_PEER_BOOK_SIZE).map(|_| ()));
+
                 // Attempt to connect to more peers if the number of connected peers is below the minimum threshold.
-                // Select the peers randomly from the list of candidate peers.
+                // Select the highest-scoring peers in the peer book from the list of candidate peers.
                 let midpoint_number_of_peers = E::MINIMUM_NUMBER_OF_PEERS.saturating_add(E::MAXIMUM_NUMBER_OF_PEERS) / 2;
+                let candidate_peers = self.candidate_peers().await;
+                // Diversify the outbound connections across subnets, so that a single network operator cannot eclipse this node.
+                let (beacon_nodes, sync_nodes) = (E::beacon_nodes(), E::sync_nodes());
+                let connected_peers = self.connected_peers().await;
+                let mut outbound_subnets = {
+                    let inbound_peers = self.inbound_peers.read().await;
+                    connected_peers
+                        .iter()
+                        .filter(|peer_ip| !inbound_peers.contains(peer_ip))
+                        .filter_map(|peer_ip| Subnet::group(peer_ip.ip()))
+                        .collect::<HashSet<_>>()
+                };
                 for peer_ip in self
-                    .candidate_peers()
-                    .await
-                    .iter()
-                    .copied()
-                    .choose_multiple(&mut OsRng::default(), midpoint_number_of_peers)
+                    .peer_book
+                    .best_peers(E::MAXIMUM_PEER_BOOK_SIZE)
+                    .into_iter()
+                    .filter(|peer_ip| candidate_peers.contains(peer_ip))
+                    // Skip the peers in the subnet of another outbound connection, unless they are beacon or sync nodes.
+                    .filter(|peer_ip| match Subnet::group(peer_ip.ip()) {
+                        Some(subnet) => beacon_nodes.contains(peer_ip) || sync_nodes.contains(peer_ip) || outbound_subnets.insert(subnet),
+                        None => true,
+                    })
+                    .take(midpoint_number_of_peers)
+                    .collect::<Vec<_>>()
                 {
                     // Ensure this node is not connected to more than the permitted number of sync nodes.
                     if E::sync_nodes().contains(&peer_ip) && number_of_connected_sync_nodes >= 1 {
@@ -537,6 +731,10 @@ impl<N: Network, E: Environment> Peers<N, E> {
                 else if E::NODE_TYPE != NodeType::Operator && self.number_of_connected_peers().await >= E::MAXIMUM_NUMBER_OF_PEERS {
                     debug!("Dropping connection request from {} (maximum peers reached)", peer_ip);
                 }
+                // Ensure the node keeps its outbound slots, and the peer does not surpass the inbound limits of its IP and subnets.
+                else if let Some(reason) = self.exceeded_inbound_limit(peer_ip).await {
+                    debug!("Dropping connection request from {} ({})", peer_ip, reason);
+                }
                 // Ensure the node is not already connected to this peer.
                 else if self.is_connected_to(peer_ip).await {
                     debug!("Dropping connection request from {} (already connected)", peer_ip);
@@ -587,9 +785,13 @@ impl<N: Network, E: Environment> Peers<N, E> {
                         // Release the lock over seen_inbound_connections.
                         drop(seen_inbound_connections);
 
+                        // Count the connection towards the inbound limits until its handshake has ended.
+                        self.connecting_peers.write().await.insert(peer_ip);
+
                         // Initialize the peer handler.
                         Peer::handler(
                             stream,
+                            peer_ip,
                             self.local_ip,
                             self.local_nonce,
                             &self.peers_router,
@@ -599,16 +801,66 @@ impl<N: Network, E: Environment> Peers<N, E> {
                             operator_router,
                             self.connected_nonces().await,
                             None,
+                            self.identity.clone(),
                         )
                         .await;
                     }
                 }
             }
-            PeersRequest::PeerConnected(peer_ip, peer_nonce, outbound) => {
+            PeersRequest::PeerIdentified(peer_ip, peer_identity, identity_result) => {
+                // Trusted, beacon, and sync nodes must keep the identity they were first seen with.
+                let is_pinned = E::beacon_nodes().contains(&peer_ip)
+                    || E::sync_nodes().contains(&peer_ip)
+                    || self.trusted_nodes().await.contains(&peer_ip);
+                let is_accepted = match peer_identity {
+                    Some(peer_identity) => {
+                        let is_accepted = match self.peer_book.pin_identity(peer_ip, peer_identity.0, !is_pinned) {
+                            Ok(is_unchanged) => is_unchanged || !is_pinned,
+                            Err(error) => {
+                                warn!("Failed to record the identity of {}: {}", peer_ip, error);
+                                !is_pinned
+                            }
+                        };
+                        if !is_accepted {
+                            warn!("Refusing {}, as its identity has changed to {}", peer_ip, peer_identity);
+                        }
+                        is_accepted
+                    }
+                    // A pinned peer with an identity on record must not fall back to an unencrypted connection.
+                    None if is_pinned => {
+                        let is_identity_pinned = match self.peer_book.get(&peer_ip) {
+                            Ok(entry) => entry.map_or(false, |entry| entry.identity.is_some()),
+                            Err(error) => {
+                                warn!("Failed to read the identity of {}: {}", peer_ip, error);
+                                true
+                            }
+                        };
+                        if is_identity_pinned {
+                            warn!(
+                                "Refusing {}, as it connected without encryption despite its pinned identity",
+                                peer_ip
+                            );
+                        }
+                        !is_identity_pinned
+                    }
+                    None => true,
+                };
+                let _ = identity_result.send(is_accepted);
+            }
+            PeersRequest::PeerHandshakeEnded(peer_ip) => {
+                // Remove an entry for this `Peer` in the connecting peers.
+                self.connecting_peers.write().await.remove(&peer_ip);
+            }
+            PeersRequest::PeerConnected(peer_ip, peer_nonce, node_type, direction, outbound) => {
                 // Add an entry for this `Peer` in the connected peers.
                 self.connected_peers.write().await.insert(peer_ip, (peer_nonce, outbound));
+                if direction == ConnectionDirection::Inbound {
+                    self.inbound_peers.write().await.insert(peer_ip);
+                }
                 // Remove an entry for this `Peer` in the candidate peers, if it exists.
                 self.candidate_peers.write().await.remove(&peer_ip);
+                // Record the successful connection in the peer book.
+                self.update_peer_book(|peer_book| peer_book.record_success(peer_ip, node_type));
 
                 #[cfg(any(feature = "test", feature = "prometheus"))]
                 {
@@ -629,8 +881,13 @@ impl<N: Network, E: Environment> Peers<N, E> {
             PeersRequest::PeerDisconnected(peer_ip) => {
                 // Remove an entry for this `Peer` in the connected peers, if it exists.
                 self.connected_peers.write().await.remove(&peer_ip);
+                self.inbound_peers.write().await.remove(&peer_ip);
+                // Remove the statistics of this `Peer`, if they exist.
+                self.peer_stats.write().await.remove(&peer_ip);
                 // Add an entry for this `Peer` in the candidate peers.
                 self.candidate_peers.write().await.insert(peer_ip);
+                // Record the last time the peer was seen in the peer book.
+                self.update_peer_book(|peer_book| peer_book.record_disconnect(peer_ip));
 
                 #[cfg(any(feature = "test", feature = "prometheus"))]
                 {
@@ -643,8 +900,15 @@ impl<N: Network, E: Environment> Peers<N, E> {
             PeersRequest::PeerRestricted(peer_ip) => {
                 // Remove an entry for this `Peer` in the connected peers, if it exists.
                 self.connected_peers.write().await.remove(&peer_ip);
+                self.inbound_peers.write().await.remove(&peer_ip);
+                // Remove the statistics of this `Peer`, if they exist.
+                self.peer_stats.write().await.remove(&peer_ip);
                 // Add an entry for this `Peer` in the restricted peers.
                 self.restricted_peers.write().await.insert(peer_ip, Instant::now());
+                // Ban the peer in the peer book, so that the restriction survives a restart.
+                self.update_peer_book(|peer_book| {
+                    peer_book.ban(peer_ip, Duration::from_secs(E::RADIO_SILENCE_IN_SECS), BanSource::Restriction)
+                });
 
                 #[cfg(any(feature = "test", feature = "prometheus"))]
                 {
@@ -654,6 +918,88 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     metrics::gauge!(metrics::peers::RESTRICTED, number_of_restricted_peers as f64);
                 }
             }
+            PeersRequest::PeerBanned(peer_ip) => {
+                // Remove an entry for this `Peer` in the connected peers, if it exists.
+                self.connected_peers.write().await.remove(&peer_ip);
+                self.inbound_peers.write().await.remove(&peer_ip);
+                // Remove the statistics of this `Peer`, if they exist.
+                self.peer_stats.write().await.remove(&peer_ip);
+                // Add an entry for this `Peer` in the restricted peers.
+                self.restricted_peers.write().await.insert(peer_ip, Instant::now());
+                // Ban the peer in the peer book, for longer with each repeat offense.
+                let base = Duration::from_secs(E::BAN_DURATION_IN_SECS);
+                let maximum = Duration::from_secs(E::MAXIMUM_BAN_DURATION_IN_SECS);
+                match self.peer_book.ban_repeat_offender(peer_ip, base, maximum) {
+                    Ok(duration) => info!("Banned {} for {} seconds", peer_ip, duration.as_secs()),
+                    Err(error) => warn!("Failed to update the peer book: {}", error),
+                }
+
+                #[cfg(any(feature = "test", feature = "prometheus"))]
+                {
+                    let number_of_connected_peers = self.number_of_connected_peers().await;
+                    let number_of_restricted_peers = self.number_of_restricted_peers().await;
+                    metrics::gauge!(metrics::peers::CONNECTED, number_of_connected_peers as f64);
+                    metrics::gauge!(metrics::peers::RESTRICTED, number_of_restricted_peers as f64);
+                }
+            }
+            PeersRequest::PeerStats(peer_ip, peer_stats) => {
+                // Ensure the peer is still connected, as its last report may arrive after it disconnected.
+                if self.is_connected_to(peer_ip).await {
+                    let mut stats = self.peer_stats.write().await;
+                    // The block height of the peer is reported by the ledger.
+                    let block_height = stats.get(&peer_ip).and_then(|stats| stats.block_height);
+                    stats.insert(peer_ip, PeerStats { block_height, ..peer_stats });
+                }
+            }
+            PeersRequest::PeerBlockHeight(peer_ip, block_height) => {
+                if let Some(stats) = self.peer_stats.write().await.get_mut(&peer_ip) {
+                    stats.block_height = Some(block_height);
+                }
+            }
+            PeersRequest::PeerLatency(peer_ip, round_trip_time) => {
+                self.update_peer_book(|peer_book| peer_book.record_latency(peer_ip, round_trip_time));
+            }
+            PeersRequest::BanPeer(peer_ip, duration) => {
+                // Remove an entry for this `Peer` in the candidate peers, if it exists.
+                self.candidate_peers.write().await.remove(&peer_ip);
+                // Add an entry for this `Peer` in the restricted peers.
+                self.restricted_peers.write().await.insert(peer_ip, Instant::now());
+                // Ban the peer in the peer book, so that the ban survives a restart.
+                self.update_peer_book(|peer_book| peer_book.ban(peer_ip, duration, BanSource::Manual));
+                info!("Banned {} for {} seconds", peer_ip, duration.as_secs());
+
+                #[cfg(any(feature = "test", feature = "prometheus"))]
+                {
+                    let number_of_candidate_peers = self.number_of_candidate_peers().await;
+                    let number_of_restricted_peers = self.number_of_restricted_peers().await;
+                    metrics::gauge!(metrics::peers::CANDIDATE, number_of_candidate_peers as f64);
+                    metrics::gauge!(metrics::peers::RESTRICTED, number_of_restricted_peers as f64);
+                }
+            }
+            PeersRequest::UnbanPeer(peer_ip) => {
+                // Remove the entries with the IP of this `Peer` in the restricted peers, if they exist.
+                self.restricted_peers.write().await.retain(|ip, _| ip.ip() != peer_ip.ip());
+                // Lift the bans of the IP of the peer in the peer book.
+                self.update_peer_book(|peer_book| peer_book.unban(peer_ip.ip()));
+                info!("Unbanned {}", peer_ip);
+
+                #[cfg(any(feature = "test", feature = "prometheus"))]
+                {
+                    let number_of_restricted_peers = self.number_of_restricted_peers().await;
+                    metrics::gauge!(metrics::peers::RESTRICTED, number_of_restricted_peers as f64);
+                }
+            }
+            PeersRequest::AddNode(peer_ip) => {
+                // The heartbeat maintains a connection with every trusted node.
+                self.trusted_peers.write().await.insert(peer_ip);
+                self.update_peer_book(|peer_book| peer_book.set_trusted(peer_ip, true));
+                info!("Added {} as a trusted node", peer_ip);
+            }
+            PeersRequest::RemoveNode(peer_ip) => {
+                self.trusted_peers.write().await.remove(&peer_ip);
+                self.update_peer_book(|peer_book| peer_book.set_trusted(peer_ip, false));
+                info!("Removed {} as a trusted node", peer_ip);
+            }
             PeersRequest::SendPeerResponse(recipient) => {
                 // Send a `PeerResponse` message.
                 let connected_peers = self.connected_peers().await;
@@ -688,10 +1034,89 @@ impl<N: Network, E: Environment> Peers<N, E> {
             if !is_self && !self.is_connected_to(*peer_ip).await {
                 // Proceed to insert each new candidate peer IP.
                 candidate_peers.insert(*peer_ip);
+                // Remember the candidate peer for subsequent sessions.
+                self.update_peer_book(|peer_book| peer_book.add_peer(*peer_ip));
             }
         }
     }
 
+    ///
+    /// Resolves again the hostnames of the trusted nodes that are not connected, as their records may have changed.
+    ///
+    async fn resolve_trusted_hosts(&self) {
+        let hosts = self.trusted_hosts.read().await.clone();
+        for (host, peer_ips) in hosts {
+            let mut is_connected = false;
+            for peer_ip in &peer_ips {
+                is_connected |= self.is_connected_to(*peer_ip).await;
+            }
+            if !is_connected {
+                match self.resolve(&host).await {
+                    Ok(peer_ips) => {
+                        // Ensure the hostname was not removed while it was resolved.
+                        if let Some(entry) = self.trusted_hosts.write().await.get_mut(&host) {
+                            *entry = peer_ips;
+                        }
+                    }
+                    Err(error) => warn!("Failed to resolve the trusted node {}: {}", host, error),
+                }
+            }
+        }
+    }
+
+    ///
+    /// Returns the peers of the DNS seeds.
+    ///
+    async fn resolve_dns_seeds(&self) -> Vec<SocketAddr> {
+        let mut seed_peers = Vec::new();
+        for dns_seed in self.dns_seeds.read().await.iter() {
+            match self.resolve(dns_seed).await {
+                Ok(peer_ips) => {
+                    debug!("Found {} peers from the DNS seed {}", peer_ips.len(), dns_seed);
+                    seed_peers.extend(peer_ips);
+                }
+                Err(error) => warn!("Failed to resolve the DNS seed {}: {}", dns_seed, error),
+            }
+        }
+        seed_peers
+    }
+
+    ///
+    /// Returns the reason an inbound connection from the given peer IP is refused, if it would surpass the inbound limits.
+    ///
+    async fn exceeded_inbound_limit(&self, peer_ip: SocketAddr) -> Option<String> {
+        // Trusted nodes are not subject to the inbound limits.
+        if self.is_trusted_ip(peer_ip.ip()).await {
+            return None;
+        }
+
+        // The inbound connections that are performing the handshake count towards the limits as well.
+        let mut inbound_ips = self.inbound_peers.read().await.iter().map(|ip| ip.ip()).collect::<Vec<_>>();
+        inbound_ips.extend(self.connecting_peers.read().await.iter().map(|ip| ip.ip()));
+
+        // Ensure the inbound connections do not take the connection slots reserved for outbound connections,
+        // unless this node is an operator, which accepts connections beyond the maximum number of peers.
+        let maximum_inbound_peers = E::MAXIMUM_NUMBER_OF_PEERS.saturating_sub(E::RESERVED_OUTBOUND_PEERS);
+        if E::NODE_TYPE != NodeType::Operator && inbound_ips.len() >= maximum_inbound_peers {
+            return Some("maximum inbound peers reached".to_string());
+        }
+        exceeded_inbound_limit::<E>(peer_ip.ip(), &inbound_ips)
+    }
+
+    /// Returns `true` if the given IP is the IP of a trusted node.
+    async fn is_trusted_ip(&self, ip: IpAddr) -> bool {
+        E::trusted_nodes().iter().any(|peer_ip| peer_ip.ip() == ip)
+            || self.trusted_peers.read().await.iter().any(|peer_ip| peer_ip.ip() == ip)
+            || self.trusted_hosts.read().await.values().flatten().any(|peer_ip| peer_ip.ip() == ip)
+    }
+
+    /// Applies the given update to the peer book, logging the error if it fails.
+    fn update_peer_book<F: FnOnce(&PeerBook<N>) -> Result<()>>(&self, update: F) {
+        if let Err(error) = update(&self.peer_book) {
+            warn!("Failed to update the peer book: {}", error);
+        }
+    }
+
     ///
     /// Sends the given message to specified peer.
     ///
@@ -702,6 +1127,7 @@ impl<N: Network, E: Environment> Peers<N, E> {
                 if let Err(error) = outbound.send(message).await {
                     trace!("Outbound channel failed: {}", error);
                     self.connected_peers.write().await.remove(&peer);
+                    self.inbound_peers.write().await.remove(&peer);
 
                     #[cfg(any(feature = "test", feature = "prometheus"))]
                     {
@@ -718,6 +1144,20 @@ impl<N: Network, E: Environment> Peers<N, E> {
     /// Sends the given message to every connected peer, excluding the sender.
     ///
     async fn propagate(&self, sender: SocketAddr, mut message: Message<N, E>) {
+        // Prepare the compact form of an unconfirmed block, for the peers that support it.
+        let compact_message = match &message {
+            Message::UnconfirmedBlock(block_height, block_hash, block) => {
+                match Self::to_compact_message(*block_height, *block_hash, block.clone()).await {
+                    Ok(compact_message) => Some(compact_message),
+                    Err(error) => {
+                        warn!("Failed to compact block {}: {}", block_height, error);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
         // Perform ahead-of-time, non-blocking serialization just once for applicable objects.
         if let Message::UnconfirmedBlock(_, _, ref mut data) = message {
             let serialized_block = Data::serialize(data.clone()).await.expect("Block serialization is bugged");
@@ -725,18 +1165,41 @@ impl<N: Network, E: Environment> Peers<N, E> {
         }
 
         // Iterate through all peers that are not the sender, sync node, or beacon node.
+        let (sync_nodes, beacon_nodes) = (E::sync_nodes(), E::beacon_nodes());
         for peer in self
             .connected_peers()
             .await
             .iter()
-            .filter(|peer_ip| *peer_ip != &sender && !E::sync_nodes().contains(peer_ip) && !E::beacon_nodes().contains(peer_ip))
+            .filter(|peer_ip| *peer_ip != &sender && !sync_nodes.contains(peer_ip) && !beacon_nodes.contains(peer_ip))
             .copied()
             .collect::<Vec<_>>()
         {
-            self.send(peer, message.clone()).await;
+            // Send the compact form of the message, if the peer advertised support for it.
+            let version = self
+                .peer_stats
+                .read()
+                .await
+                .get(&peer)
+                .map(|stats| stats.version)
+                .unwrap_or_default();
+            match &compact_message {
+                Some(compact_message) if version >= E::COMPACT_BLOCK_MESSAGE_VERSION => self.send(peer, compact_message.clone()).await,
+                _ => self.send(peer, message.clone()).await,
+            }
         }
     }
 
+    ///
+    /// Returns the compact form of the given unconfirmed block, serialized ahead of time.
+    ///
+    async fn to_compact_message(block_height: u32, block_hash: N::BlockHash, block: Data<Block<N>>) -> Result<Message<N, E>> {
+        let block = block.deserialize().await?;
+        // The salt makes the collisions of short transaction IDs differ with every relay.
+        let compact_block = CompactBlock::new(&block, thread_rng().gen())?;
+        let serialized_block = Data::Object(compact_block).serialize().await?;
+        Ok(Message::CompactBlock(block_height, block_hash, Data::Buffer(serialized_block)))
+    }
+
     async fn propagate_prover(&self, message: Message<N, E>) {
         // Iterate through all provers.
         for peer in self.connected_peers().await.iter() {
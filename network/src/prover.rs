@@ -14,7 +14,16 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{Data, LedgerReader, LedgerRequest, LedgerRouter, Message, PeersRequest, PeersRouter};
+use crate::{
+    helpers::{CompactBlock, MemoryPool, Misbehavior, PartialBlock},
+    Data,
+    LedgerReader,
+    LedgerRequest,
+    LedgerRouter,
+    Message,
+    PeersRequest,
+    PeersRouter,
+};
 use snarkos_environment::{
     helpers::{NodeType, State},
     Environment
@@ -26,6 +35,7 @@ use anyhow::{anyhow, Result};
 use rand::thread_rng;
 use rayon::{ThreadPool, ThreadPoolBuilder};
 use std::{
+    collections::HashMap,
     net::SocketAddr,
     path::Path,
     sync::{atomic::Ordering, Arc},
@@ -54,6 +64,14 @@ pub enum ProverRequest<N: Network> {
     /// UnconfirmedTransaction := (peer_ip, transaction)
     UnconfirmedTransaction(SocketAddr, Transaction<N>),
     OperatorConnected(SocketAddr),
+    /// PoolAddress := (pool_ip)
+    PoolAddress(SocketAddr),
+    /// CompactBlock := (peer_ip, compact_block)
+    CompactBlock(SocketAddr, CompactBlock<N>),
+    /// BlockTransactions := (peer_ip, block_hash, \[transaction\])
+    BlockTransactions(SocketAddr, N::BlockHash, Vec<Transaction<N>>),
+    /// Heartbeat := ()
+    Heartbeat,
 }
 
 ///
@@ -65,20 +83,22 @@ pub struct Prover<N: Network, E: Environment> {
     state: Arc<ProverState<N>>,
     /// The Aleo address of the prover.
     address: Option<Address<N>>,
-    /// The IP address of the connected pool.
-    pool: Option<SocketAddr>,
+    /// The IP address of the connected pool, which changes if the pool is resolved to a new IP.
+    pool: RwLock<Option<SocketAddr>>,
     /// The thread pool for the prover.
     thread_pool: Arc<ThreadPool>,
     /// The prover router of the node.
     prover_router: ProverRouter<N>,
     /// The pool of unconfirmed transactions.
     memory_pool: Arc<RwLock<MemoryPool<N>>>,
+    /// The map of compact blocks being reconstructed to the peers whose missing transactions were requested from.
+    partial_blocks: RwLock<HashMap<N::BlockHash, (SocketAddr, PartialBlock<N>)>>,
     /// The peers router of the node.
     peers_router: PeersRouter<N, E>,
     /// The ledger state of the node.
     ledger_reader: LedgerReader<N>,
     /// The ledger router of the node.
-    _ledger_router: LedgerRouter<N>,
+    ledger_router: LedgerRouter<N>,
     current_block: Arc<RwLock<u32>>,
 }
 
@@ -105,13 +125,18 @@ impl<N: Network, E: Environment> Prover<N, E> {
         let prover = Arc::new(Self {
             state: Arc::new(ProverState::open::<S, P>(path, false)?),
             address,
-            pool: pool_ip,
+            pool: RwLock::new(pool_ip),
             thread_pool: Arc::new(thread_pool),
             prover_router,
-            memory_pool: Arc::new(RwLock::new(MemoryPool::new())),
+            memory_pool: Arc::new(RwLock::new(MemoryPool::new(
+                E::MAXIMUM_MEMORY_POOL_SIZE,
+                E::MAXIMUM_MEMORY_POOL_TRANSACTIONS,
+                Duration::from_secs(E::MEMORY_POOL_EXPIRY_IN_SECS),
+            ))),
+            partial_blocks: Default::default(),
             peers_router,
             ledger_reader,
-            _ledger_router: ledger_router,
+            ledger_router,
             current_block: Arc::new(RwLock::new(0)),
         });
 
@@ -184,7 +209,7 @@ impl<N: Network, E: Environment> Prover<N, E> {
             }
             ProverRequest::MemoryPoolClear(block) => match block {
                 Some(block) => self.memory_pool.write().await.remove_transactions(block.transactions()),
-                None => *self.memory_pool.write().await = MemoryPool::new(),
+                None => self.memory_pool.write().await.clear(),
             },
             ProverRequest::UnconfirmedTransaction(peer_ip, transaction) => {
                 // Ensure the node is not peering.
@@ -194,22 +219,46 @@ impl<N: Network, E: Environment> Prover<N, E> {
                 }
             }
             ProverRequest::OperatorConnected(peer_ip) => {
-                if let Some(pool_ip) = self.pool {
+                if let Some(pool_ip) = self.pool_ip().await {
                     if pool_ip == peer_ip {
                         self.send_pool_register().await;
                     }
                 }
             }
+            ProverRequest::PoolAddress(pool_ip) => {
+                info!("The pool is now at {}", pool_ip);
+                *self.pool.write().await = Some(pool_ip);
+            }
+            ProverRequest::CompactBlock(peer_ip, compact_block) => {
+                // Ensure the node is not peering.
+                if !E::status().is_peering() {
+                    // Reconstruct the compact block from the memory pool.
+                    self.add_compact_block(peer_ip, compact_block).await
+                }
+            }
+            ProverRequest::BlockTransactions(peer_ip, block_hash, transactions) => {
+                // Complete the compact block with the missing transactions.
+                self.add_block_transactions(peer_ip, block_hash, transactions).await
+            }
+            ProverRequest::Heartbeat => {
+                // Request the blocks whose missing transactions were not received in time in full.
+                self.remove_expired_partial_blocks().await
+            }
         }
     }
 
+    /// Returns the IP address of the pool, if any.
+    async fn pool_ip(&self) -> Option<SocketAddr> {
+        *self.pool.read().await
+    }
+
     ///
     /// Sends a `PoolRegister` message to the pool IP address.
     ///
     async fn send_pool_register(&self) {
         if E::NODE_TYPE == NodeType::Prover {
             if let Some(recipient) = self.address {
-                if let Some(pool_ip) = self.pool {
+                if let Some(pool_ip) = self.pool_ip().await {
                     // Proceed to register the prover to receive a block template.
                     let request = PeersRequest::MessageSend(pool_ip, Message::PoolRegister(recipient));
                     if let Err(error) = self.peers_router.send(request).await {
@@ -230,7 +279,7 @@ impl<N: Network, E: Environment> Prover<N, E> {
     async fn process_pool_request(&self, operator_ip: SocketAddr, share_difficulty: u64, block_template: BlockTemplate<N>) {
         if E::NODE_TYPE == NodeType::Prover {
             if let Some(recipient) = self.address {
-                if let Some(pool_ip) = self.pool {
+                if let Some(pool_ip) = self.pool_ip().await {
                     // Refuse work from any pool other than the registered one.
                     if pool_ip == operator_ip {
                         let thread_pool = self.thread_pool.clone();
@@ -343,4 +392,172 @@ impl<N: Network, E: Environment> Prover<N, E> {
             }
         }
     }
+
+    ///
+    /// Reconstructs the given compact block from the memory pool, requesting its missing transactions from the peer.
+    ///
+    async fn add_compact_block(&self, peer_ip: SocketAddr, compact_block: CompactBlock<N>) {
+        let block_hash = compact_block.block_hash;
+        trace!("Received compact block {} from {}", block_hash, peer_ip);
+        // Ensure the block is new, and not already being reconstructed.
+        if self.ledger_reader.contains_block_hash(&block_hash).unwrap_or(false) {
+            return;
+        }
+        if self.partial_blocks.read().await.contains_key(&block_hash) {
+            return;
+        }
+
+        // Fill in the transactions of the block from the memory pool.
+        let memory_pool = self.memory_pool.read().await.transactions();
+        let mut partial_block = match PartialBlock::new(compact_block, &memory_pool) {
+            Ok(partial_block) => partial_block,
+            Err(error) => {
+                self.report_failure(peer_ip, Misbehavior::InvalidBlock(format!("{}", error))).await;
+                return;
+            }
+        };
+
+        if partial_block.missing_indices().is_empty() {
+            match partial_block.to_block() {
+                Ok(block) => {
+                    self.add_unconfirmed_block(peer_ip, block).await;
+                    return;
+                }
+                Err(error) => {
+                    debug!("Failed to reconstruct block {} from the memory pool: {}", block_hash, error);
+                    partial_block.discard_memory_pool();
+                }
+            }
+        }
+        self.request_block_transactions(peer_ip, partial_block).await;
+    }
+
+    ///
+    /// Completes the compact block with the given hash with its missing transactions, as they were received from the peer.
+    ///
+    async fn add_block_transactions(&self, peer_ip: SocketAddr, block_hash: N::BlockHash, transactions: Vec<Transaction<N>>) {
+        // Ensure the transactions were requested from the peer.
+        let partial_block = {
+            let mut partial_blocks = self.partial_blocks.write().await;
+            match partial_blocks.get(&block_hash) {
+                Some((requested_ip, _)) if *requested_ip == peer_ip => partial_blocks.remove(&block_hash),
+                _ => None,
+            }
+        };
+        let mut partial_block = match partial_block {
+            Some((_, partial_block)) => partial_block,
+            None => {
+                trace!("Skipping the transactions of block {} from {} (not requested)", block_hash, peer_ip);
+                return;
+            }
+        };
+
+        if let Err(error) = partial_block.insert_missing(transactions) {
+            let failure = Misbehavior::ProtocolViolation(format!("{}", error));
+            self.report_failure(peer_ip, failure).await;
+            self.request_block(peer_ip, partial_block.block_height()).await;
+            return;
+        }
+        match partial_block.to_block() {
+            Ok(block) => self.add_unconfirmed_block(peer_ip, block).await,
+            // Fall back to requesting all of the transactions, in case the memory pool yielded the wrong transactions.
+            Err(error) if partial_block.uses_memory_pool() => {
+                debug!("Failed to reconstruct block {} from the memory pool: {}", block_hash, error);
+                partial_block.discard_memory_pool();
+                self.request_block_transactions(peer_ip, partial_block).await;
+            }
+            // Fall back to requesting the block in full, as it could not be reconstructed.
+            Err(error) => {
+                self.report_failure(peer_ip, Misbehavior::InvalidBlock(format!("{}", error))).await;
+                self.request_block(peer_ip, partial_block.block_height()).await;
+            }
+        }
+    }
+
+    ///
+    /// Requests the missing transactions of the given partial block from the peer.
+    ///
+    async fn request_block_transactions(&self, peer_ip: SocketAddr, partial_block: PartialBlock<N>) {
+        let block_hash = partial_block.block_hash();
+        let missing_indices = partial_block.missing_indices();
+        {
+            let mut partial_blocks = self.partial_blocks.write().await;
+            // Bound the partial blocks of each peer, as they are keyed by the block hashes the peer claims,
+            // and fall back to requesting the block in full instead.
+            let num_partial_blocks = partial_blocks.values().filter(|(requested_ip, _)| *requested_ip == peer_ip).count();
+            if num_partial_blocks >= E::MAXIMUM_PARTIAL_BLOCKS_PER_PEER {
+                drop(partial_blocks);
+                debug!("Too many compact blocks from {} are awaiting their transactions", peer_ip);
+                self.request_block(peer_ip, partial_block.block_height()).await;
+                return;
+            }
+
+            debug!(
+                "Requesting {} transactions of block {} from {}",
+                missing_indices.len(),
+                block_hash,
+                peer_ip
+            );
+            partial_blocks.insert(block_hash, (peer_ip, partial_block));
+        }
+
+        let request = PeersRequest::MessageSend(peer_ip, Message::GetBlockTransactions(block_hash, missing_indices));
+        if let Err(error) = self.peers_router.send(request).await {
+            warn!("[GetBlockTransactions] {}", error);
+        }
+    }
+
+    ///
+    /// Discards the partial blocks whose missing transactions were not received in time, requesting their blocks in full instead.
+    ///
+    async fn remove_expired_partial_blocks(&self) {
+        let expired_blocks = {
+            let mut partial_blocks = self.partial_blocks.write().await;
+            let expired_hashes = partial_blocks
+                .iter()
+                .filter(|(_, (_, partial_block))| partial_block.is_expired(Duration::from_secs(E::PARTIAL_BLOCK_TIMEOUT_IN_SECS)))
+                .map(|(block_hash, _)| *block_hash)
+                .collect::<Vec<_>>();
+            expired_hashes
+                .into_iter()
+                .filter_map(|block_hash| partial_blocks.remove(&block_hash))
+                .collect::<Vec<_>>()
+        };
+
+        for (peer_ip, partial_block) in expired_blocks {
+            debug!(
+                "The transactions of block {} from {} were not received in time",
+                partial_block.block_hash(),
+                peer_ip
+            );
+            self.request_block(peer_ip, partial_block.block_height()).await;
+        }
+    }
+
+    ///
+    /// Requests the block at the given height from the peer in full, as it could not be reconstructed from its compact form.
+    ///
+    async fn request_block(&self, peer_ip: SocketAddr, block_height: u32) {
+        if let Err(error) = self.ledger_router.send(LedgerRequest::BlockRequest(peer_ip, block_height)).await {
+            warn!("[BlockRequest] {}", error);
+        }
+    }
+
+    ///
+    /// Routes the given reconstructed block to the ledger, as if it was received as an unconfirmed block.
+    ///
+    async fn add_unconfirmed_block(&self, peer_ip: SocketAddr, block: Block<N>) {
+        trace!("Reconstructed block {} from {}", block.height(), peer_ip);
+        let request = LedgerRequest::UnconfirmedBlock(peer_ip, block, self.prover_router.clone());
+        if let Err(error) = self.ledger_router.send(request).await {
+            warn!("[UnconfirmedBlock] {}", error);
+        }
+    }
+
+    /// Routes the given misbehavior of the peer to the ledger.
+    async fn report_failure(&self, peer_ip: SocketAddr, failure: Misbehavior) {
+        if let Err(error) = self.ledger_router.send(LedgerRequest::Failure(peer_ip, failure)).await {
+            warn!("[Failure] {}", error);
+        }
+    }
 }
@@ -17,8 +17,19 @@
 //! Logic for instantiating the RPC server.
 
 use snarkos_environment::Environment;
-use snarkos_network::{LedgerReader, Operator, Peers, ProverRouter, LedgerRouter, OperatorRouter};
-use snarkvm::dpc::{Address, MemoryPool, Network};
+use snarkos_network::{
+    helpers::MemoryPool,
+    Backups,
+    LedgerReader,
+    LedgerRequest,
+    LedgerRouter,
+    Operator,
+    OperatorRouter,
+    Peers,
+    PeersRequest,
+    ProverRouter,
+};
+use snarkvm::dpc::{Address, Network};
 
 use futures::TryFutureExt;
 use jsonrpsee::{
@@ -26,9 +37,10 @@ use jsonrpsee::{
     http_server::{AccessControlBuilder, HttpServerBuilder, RpcModule},
 };
 use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
 use std::{net::SocketAddr, ops::Deref, sync::Arc, time::Instant};
 use tokio::sync::{oneshot, RwLock};
-use crate::RpcFunctions;
+use crate::{RpcError, RpcFunctions};
 
 // The details on resource-limiting can be found at https://github.com/paritytech/jsonrpsee/blob/master/core/src/server/resource_limiting.rs
 // note: jsonrpsee expects string literals as resource names; we'll be distinguishing
@@ -49,8 +61,8 @@ pub struct RpcInner<N: Network, E: Environment> {
     pub(crate) operator_router: OperatorRouter<N>,
     pub(crate) prover_router: ProverRouter<N>,
     pub(crate) memory_pool: Arc<RwLock<MemoryPool<N>>>,
+    pub(crate) backups: Option<Arc<Backups<N, E>>>,
     /// RPC credentials for accessing guarded endpoints
-    #[allow(unused)]
     pub(crate) credentials: RpcCredentials,
     pub(crate) launched: Instant,
 }
@@ -81,6 +93,7 @@ impl<N: Network, E: Environment> RpcContext<N, E> {
         operator_router: OperatorRouter<N>,
         prover_router: ProverRouter<N>,
         memory_pool: Arc<RwLock<MemoryPool<N>>>,
+        backups: Option<Arc<Backups<N, E>>>,
     ) -> Self {
         Self(Arc::new(RpcInner {
             address,
@@ -91,10 +104,39 @@ impl<N: Network, E: Environment> RpcContext<N, E> {
             operator_router,
             prover_router,
             memory_pool,
+            backups,
             credentials: RpcCredentials { username, password },
             launched: Instant::now(),
         }))
     }
+
+    /// Ensures the given credentials match the credentials for accessing guarded endpoints.
+    pub(crate) fn authenticate(&self, username: &str, password: &str) -> Result<(), RpcError> {
+        // The credentials are compared in constant time, so their contents cannot be inferred from the response time.
+        let is_username_valid = self.credentials.username.as_bytes().ct_eq(username.as_bytes());
+        let is_password_valid = self.credentials.password.as_bytes().ct_eq(password.as_bytes());
+        match bool::from(is_username_valid & is_password_valid) {
+            true => Ok(()),
+            false => Err(RpcError::Message("Invalid RPC credentials".to_string())),
+        }
+    }
+
+    /// Routes the given request to the peers.
+    pub(crate) async fn route_to_peers(&self, request: PeersRequest<N, E>) -> Result<(), RpcError> {
+        self.peers
+            .router()
+            .send(request)
+            .await
+            .map_err(|error| RpcError::Message(format!("Failed to route the request to the peers: {}", error)))
+    }
+
+    /// Routes the given request to the ledger.
+    pub(crate) async fn route_to_ledger(&self, request: LedgerRequest<N>) -> Result<(), RpcError> {
+        self.ledger_router
+            .send(request)
+            .await
+            .map_err(|error| RpcError::Message(format!("Failed to route the request to the ledger: {}", error)))
+    }
 }
 
 /// Defines the authentication format for accessing private endpoints on the RPC server.
@@ -281,10 +323,91 @@ fn create_rpc_module<N: Network, E: Environment>(rpc_context: RpcContext<N, E>)
         rpc_context.get_connected_peers().map_err(JsonrpseeError::to_call_error).await
     })?;
 
+    module.register_async_method("getpeerinfo", |_rpc_params, rpc_context| async move {
+        rpc_context.get_peer_info().map_err(JsonrpseeError::to_call_error).await
+    })?;
+
+    module.register_async_method("getbannedpeers", |_rpc_params, rpc_context| async move {
+        rpc_context.get_banned_peers().map_err(JsonrpseeError::to_call_error).await
+    })?;
+
     module.register_async_method("getnodestate", |_rpc_params, rpc_context| async move {
         rpc_context.get_node_state().map_err(JsonrpseeError::to_call_error).await
     })?;
 
+    module.register_async_method("getstorageinfo", |_rpc_params, rpc_context| async move {
+        rpc_context.get_storage_info().map_err(JsonrpseeError::to_call_error).await
+    })?;
+
+    module.register_async_method("backup", |rpc_params, rpc_context| async move {
+        let [username, password]: [String; 2] = rpc_params.parse()?;
+        rpc_context.backup(username, password).map_err(JsonrpseeError::to_call_error).await
+    })?;
+
+    module.register_async_method("disconnectpeer", |rpc_params, rpc_context| async move {
+        let (username, password, peer_ip): (String, String, SocketAddr) = rpc_params.parse()?;
+        rpc_context
+            .disconnect_peer(username, password, peer_ip)
+            .map_err(JsonrpseeError::to_call_error)
+            .await
+    })?;
+
+    module.register_async_method("banpeer", |rpc_params, rpc_context| async move {
+        let (username, password, peer_ip, duration_in_secs): (String, String, SocketAddr, u64) = rpc_params.parse()?;
+        rpc_context
+            .ban_peer(username, password, peer_ip, duration_in_secs)
+            .map_err(JsonrpseeError::to_call_error)
+            .await
+    })?;
+
+    module.register_async_method("unban", |rpc_params, rpc_context| async move {
+        let (username, password, peer_ip): (String, String, SocketAddr) = rpc_params.parse()?;
+        rpc_context
+            .unban(username, password, peer_ip)
+            .map_err(JsonrpseeError::to_call_error)
+            .await
+    })?;
+
+    module.register_async_method("listbanned", |rpc_params, rpc_context| async move {
+        let [username, password]: [String; 2] = rpc_params.parse()?;
+        rpc_context
+            .list_banned(username, password)
+            .map_err(JsonrpseeError::to_call_error)
+            .await
+    })?;
+
+    module.register_async_method("addnode", |rpc_params, rpc_context| async move {
+        let (username, password, peer_ip): (String, String, SocketAddr) = rpc_params.parse()?;
+        rpc_context
+            .add_node(username, password, peer_ip)
+            .map_err(JsonrpseeError::to_call_error)
+            .await
+    })?;
+
+    module.register_async_method("removenode", |rpc_params, rpc_context| async move {
+        let (username, password, peer_ip): (String, String, SocketAddr) = rpc_params.parse()?;
+        rpc_context
+            .remove_node(username, password, peer_ip)
+            .map_err(JsonrpseeError::to_call_error)
+            .await
+    })?;
+
+    module.register_async_method("getownedrecords", |rpc_params, rpc_context| async move {
+        let (username, password, address): (String, String, Address<N>) = rpc_params.parse()?;
+        rpc_context
+            .get_owned_records(username, password, address)
+            .map_err(JsonrpseeError::to_call_error)
+            .await
+    })?;
+
+    module.register_async_method("getspendingtransactions", |rpc_params, rpc_context| async move {
+        let (username, password, address): (String, String, Address<N>) = rpc_params.parse()?;
+        rpc_context
+            .get_spending_transactions(username, password, address)
+            .map_err(JsonrpseeError::to_call_error)
+            .await
+    })?;
+
     module.register_async_method("sendtransaction", |rpc_params, rpc_context| async move {
         let string = std::mem::take(&mut rpc_params.parse::<[String; 1]>()?[0]);
         rpc_context.send_transaction(string).map_err(JsonrpseeError::to_call_error).await
@@ -20,7 +20,7 @@
 
 use crate::{RpcContext, RpcError, RpcFunctions};
 use snarkos_environment::Environment;
-use snarkos_network::{ProverRequest, PeersRequest};
+use snarkos_network::{helpers::PeerAddr, DisconnectReason, LedgerRequest, PeersRequest, ProverRequest};
 use snarkos_storage::Metadata;
 use snarkvm::{
     dpc::{Address, AleoAmount, Block, BlockHeader, Blocks, Network, Record, Transaction, Transactions, Transition},
@@ -31,7 +31,7 @@ use tokio::sync::oneshot;
 use serde_json::Value;
 use time::OffsetDateTime;
 
-use std::{cmp::max, net::SocketAddr};
+use std::{cmp::max, net::SocketAddr, time::Duration};
 
 #[async_trait::async_trait]
 impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
@@ -137,7 +137,7 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
             .memory_pool
             .read()
             .await
-            .transactions()
+            .select_transactions(E::MAXIMUM_BLOCK_TRANSACTIONS_SIZE)
             .iter()
             .filter(|transaction| {
                 for serial_number in transaction.serial_numbers() {
@@ -217,6 +217,53 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
         Ok(self.peers.connected_peers().await)
     }
 
+    /// Returns the connection and traffic statistics of each connected peer.
+    async fn get_peer_info(&self) -> Result<Value, RpcError> {
+        let mut peer_info = Vec::new();
+        for (peer_ip, stats) in self.peers.peer_stats().await {
+            peer_info.push(serde_json::json!({
+                "ip": peer_ip,
+                "node_type": stats.node_type,
+                "state": stats.state,
+                "version": stats.version,
+                "direction": stats.direction,
+                "connected_since": stats.connected_since,
+                "last_seen": stats.last_seen,
+                "latency_in_millis": stats.latency_in_millis,
+                "block_height": stats.block_height,
+                "bytes_sent": stats.bytes_sent,
+                "bytes_received": stats.bytes_received,
+                "messages_sent": stats.messages_sent,
+                "messages_received": stats.messages_received,
+                "is_prover": self.peers.is_prover(&peer_ip).await,
+                "is_pool_server": self.peers.is_pool_server(&peer_ip).await,
+            }));
+        }
+
+        Ok(Value::Array(peer_info))
+    }
+
+    /// Returns the currently banned peers, along with the expiry and number of their bans.
+    async fn get_banned_peers(&self) -> Result<Value, RpcError> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let banned_peers: Vec<_> = self
+            .peers
+            .peer_book()
+            .to_entries()
+            .into_iter()
+            .filter(|(_, entry)| entry.is_banned(now))
+            .map(|(peer_ip, entry)| {
+                serde_json::json!({
+                    "ip": peer_ip,
+                    "banned_until": entry.banned_until,
+                    "num_bans": entry.num_bans,
+                })
+            })
+            .collect();
+
+        Ok(Value::Array(banned_peers))
+    }
+
     /// Returns the current state of this node.
     async fn get_node_state(&self) -> Result<Value, RpcError> {
         let candidate_peers = self.peers.candidate_peers().await;
@@ -247,6 +294,143 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
         }))
     }
 
+    /// Returns the approximate number of keys and size of each map of the ledger storage.
+    async fn get_storage_info(&self) -> Result<Value, RpcError> {
+        let stats = self.ledger.storage_stats()?;
+        let maps: Vec<_> = stats
+            .maps
+            .iter()
+            .map(|map| {
+                serde_json::json!({
+                    "name": map.map_id.name(),
+                    "num_keys": map.num_keys,
+                    "size": map.size,
+                })
+            })
+            .collect();
+
+        Ok(serde_json::json!({
+            "maps": maps,
+            "num_keys": stats.num_keys(),
+            "size": stats.size(),
+            "sst_files_size": stats.sst_files_size,
+            "wal_files_size": stats.wal_files_size,
+        }))
+    }
+
+    /// Creates an online backup of the ledger and operator storage, given the RPC credentials.
+    async fn backup(&self, username: String, password: String) -> Result<Value, RpcError> {
+        self.authenticate(&username, &password)?;
+        match &self.backups {
+            Some(backups) => Ok(serde_json::to_value(backups.backup().await?)?),
+            None => Err(RpcError::Message("Backups are not configured; start the node with `--backup-dir`".to_string())),
+        }
+    }
+
+    /// Disconnects from the given peer, given the RPC credentials.
+    async fn disconnect_peer(&self, username: String, password: String, peer_ip: SocketAddr) -> Result<bool, RpcError> {
+        self.authenticate(&username, &password)?;
+        if !self.peers.is_connected_to(peer_ip).await {
+            return Err(RpcError::Message(format!("Not connected to {}", peer_ip)));
+        }
+        self.route_to_ledger(LedgerRequest::Disconnect(peer_ip, DisconnectReason::NoReasonGiven))
+            .await?;
+        Ok(true)
+    }
+
+    /// Bans the given peer for the given number of seconds, disconnecting from it if connected, given the RPC credentials.
+    async fn ban_peer(&self, username: String, password: String, peer_ip: SocketAddr, duration_in_secs: u64) -> Result<bool, RpcError> {
+        self.authenticate(&username, &password)?;
+        self.route_to_peers(PeersRequest::BanPeer(peer_ip, Duration::from_secs(duration_in_secs)))
+            .await?;
+        if self.peers.is_connected_to(peer_ip).await {
+            self.route_to_ledger(LedgerRequest::Disconnect(peer_ip, DisconnectReason::NoReasonGiven))
+                .await?;
+        }
+        Ok(true)
+    }
+
+    /// Lifts the ban of the given peer, given the RPC credentials.
+    async fn unban(&self, username: String, password: String, peer_ip: SocketAddr) -> Result<bool, RpcError> {
+        self.authenticate(&username, &password)?;
+        self.route_to_peers(PeersRequest::UnbanPeer(peer_ip)).await?;
+        Ok(true)
+    }
+
+    /// Returns the currently banned peers, along with the origin and remaining duration of their bans, given the RPC credentials.
+    async fn list_banned(&self, username: String, password: String) -> Result<Value, RpcError> {
+        self.authenticate(&username, &password)?;
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let banned_peers: Vec<_> = self
+            .peers
+            .peer_book()
+            .to_entries()
+            .into_iter()
+            .filter(|(_, entry)| entry.is_banned(now))
+            .map(|(peer_ip, entry)| {
+                serde_json::json!({
+                    "ip": peer_ip,
+                    "banned_until": entry.banned_until,
+                    "expires_in": entry.banned_until.map(|banned_until| banned_until - now),
+                    "num_bans": entry.num_bans,
+                    "source": entry.ban_source,
+                })
+            })
+            .collect();
+
+        Ok(Value::Array(banned_peers))
+    }
+
+    /// Adds the given peer as a trusted node, which persists across restarts, given the RPC credentials.
+    async fn add_node(&self, username: String, password: String, peer_ip: SocketAddr) -> Result<bool, RpcError> {
+        self.authenticate(&username, &password)?;
+        self.route_to_peers(PeersRequest::AddNode(peer_ip)).await?;
+        Ok(true)
+    }
+
+    /// Removes the given peer from the trusted nodes, given the RPC credentials.
+    async fn remove_node(&self, username: String, password: String, peer_ip: SocketAddr) -> Result<bool, RpcError> {
+        self.authenticate(&username, &password)?;
+        self.route_to_peers(PeersRequest::RemoveNode(peer_ip)).await?;
+        Ok(true)
+    }
+
+    /// Returns the indexed records owned by the given address, and the transactions that spent them, if any, given the RPC credentials.
+    async fn get_owned_records(&self, username: String, password: String, address: Address<N>) -> Result<Value, RpcError> {
+        self.authenticate(&username, &password)?;
+        let mut records = Vec::new();
+        for indexed_record in self.ledger.get_owned_records(&address)? {
+            let spent_by = self.ledger.get_record_spend(&indexed_record.record.commitment())?;
+            records.push(serde_json::json!({
+                "block_height": indexed_record.block_height,
+                "record": indexed_record.record,
+                "spent_by": spent_by.map(|(block_height, transaction_id)| serde_json::json!({
+                    "block_height": block_height,
+                    "transaction_id": transaction_id,
+                })),
+                "transaction_id": indexed_record.transaction_id,
+            }));
+        }
+        Ok(serde_json::json!(records))
+    }
+
+    /// Returns the transactions that spent the indexed records owned by the given address, given the RPC credentials.
+    async fn get_spending_transactions(&self, username: String, password: String, address: Address<N>) -> Result<Value, RpcError> {
+        self.authenticate(&username, &password)?;
+        let mut spends = Vec::new();
+        for indexed_record in self.ledger.get_owned_records(&address)? {
+            let commitment = indexed_record.record.commitment();
+            if let Some((block_height, transaction_id)) = self.ledger.get_record_spend(&commitment)? {
+                spends.push(serde_json::json!({
+                    "block_height": block_height,
+                    "commitment": commitment,
+                    "transaction_id": transaction_id,
+                }));
+            }
+        }
+        Ok(serde_json::json!(spends))
+    }
+
     /// Returns the transaction ID. If the given transaction is valid, it is added to the memory pool and propagated to all peers.
     async fn send_transaction(&self, transaction_hex: String) -> Result<N::TransactionID, RpcError> {
         let transaction: Transaction<N> = FromBytes::from_bytes_le(&hex::decode(transaction_hex)?)?;
@@ -258,30 +442,33 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcContext<N, E> {
         Ok(transaction.transaction_id())
     }
 
+    /// Connects to the given peers, given by IP or hostname, returning an error if any of the connections fails.
     async fn connect(&self, peers: Vec<String>) -> Result<bool, RpcError> {
-        for peer_ip in &peers {
-            let (router, _handler) = oneshot::channel();
-            let addr: Result<SocketAddr, std::net::AddrParseError> = peer_ip.parse();
-            let res = match addr {
-                Ok(addr) => addr,
-                Err(error) => {
-                    return Err(RpcError::Message(error.to_string()));
-                }
-            };
-            if let Err(error) = self
-                .peers
-                .router()
-                .send(PeersRequest::Connect(
-                    res,
-                    self.ledger.clone(),
-                    self.ledger_router.clone(),
-                    self.operator_router.clone(),
-                    self.prover_router.clone(),
-                    router,
-                ))
-                .await
-            {
-                warn!("Connect {}", error);
+        let mut handlers = Vec::with_capacity(peers.len());
+        for peer_addr in &peers {
+            // Resolve the peer, which may be given by hostname, to its first IP.
+            let peer_addr: PeerAddr = peer_addr.parse()?;
+            let peer_ip = self.peers.resolve(&peer_addr).await?[0];
+
+            let (router, handler) = oneshot::channel();
+            self.route_to_peers(PeersRequest::Connect(
+                peer_ip,
+                self.ledger.clone(),
+                self.ledger_router.clone(),
+                self.operator_router.clone(),
+                self.prover_router.clone(),
+                router,
+            ))
+            .await?;
+            handlers.push((peer_ip, handler));
+        }
+
+        // Wait for the result of each connection.
+        for (peer_ip, handler) in handlers {
+            match handler.await {
+                Ok(Ok(())) => (),
+                Ok(Err(error)) => return Err(RpcError::Message(format!("Failed to connect to {}: {}", peer_ip, error))),
+                Err(_) => return Err(RpcError::Message(format!("Failed to connect to {}", peer_ip))),
             }
         }
         Ok(true)
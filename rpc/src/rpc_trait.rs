@@ -87,9 +87,50 @@ pub trait RpcFunctions<N: Network> {
     #[doc = include_str!("../documentation/public_endpoints/getconnectedpeers.md")]
     async fn get_connected_peers(&self) -> Result<Vec<SocketAddr>, RpcError>;
 
+    #[doc = include_str!("../documentation/public_endpoints/getpeerinfo.md")]
+    async fn get_peer_info(&self) -> Result<serde_json::Value, RpcError>;
+
+    #[doc = include_str!("../documentation/public_endpoints/getbannedpeers.md")]
+    async fn get_banned_peers(&self) -> Result<serde_json::Value, RpcError>;
+
     #[doc = include_str!("../documentation/public_endpoints/getnodestate.md")]
     async fn get_node_state(&self) -> Result<serde_json::Value, RpcError>;
 
+    #[doc = include_str!("../documentation/public_endpoints/getstorageinfo.md")]
+    async fn get_storage_info(&self) -> Result<serde_json::Value, RpcError>;
+
+    #[doc = include_str!("../documentation/private_endpoints/backup.md")]
+    async fn backup(&self, username: String, password: String) -> Result<serde_json::Value, RpcError>;
+
+    #[doc = include_str!("../documentation/private_endpoints/disconnectpeer.md")]
+    async fn disconnect_peer(&self, username: String, password: String, peer_ip: SocketAddr) -> Result<bool, RpcError>;
+
+    #[doc = include_str!("../documentation/private_endpoints/banpeer.md")]
+    async fn ban_peer(&self, username: String, password: String, peer_ip: SocketAddr, duration_in_secs: u64) -> Result<bool, RpcError>;
+
+    #[doc = include_str!("../documentation/private_endpoints/unban.md")]
+    async fn unban(&self, username: String, password: String, peer_ip: SocketAddr) -> Result<bool, RpcError>;
+
+    #[doc = include_str!("../documentation/private_endpoints/listbanned.md")]
+    async fn list_banned(&self, username: String, password: String) -> Result<serde_json::Value, RpcError>;
+
+    #[doc = include_str!("../documentation/private_endpoints/addnode.md")]
+    async fn add_node(&self, username: String, password: String, peer_ip: SocketAddr) -> Result<bool, RpcError>;
+
+    #[doc = include_str!("../documentation/private_endpoints/removenode.md")]
+    async fn remove_node(&self, username: String, password: String, peer_ip: SocketAddr) -> Result<bool, RpcError>;
+
+    #[doc = include_str!("../documentation/private_endpoints/getownedrecords.md")]
+    async fn get_owned_records(&self, username: String, password: String, address: Address<N>) -> Result<serde_json::Value, RpcError>;
+
+    #[doc = include_str!("../documentation/private_endpoints/getspendingtransactions.md")]
+    async fn get_spending_transactions(
+        &self,
+        username: String,
+        password: String,
+        address: Address<N>,
+    ) -> Result<serde_json::Value, RpcError>;
+
     #[doc = include_str!("../documentation/public_endpoints/sendtransaction.md")]
     async fn send_transaction(&self, transaction_bytes: String) -> Result<N::TransactionID, RpcError>;
 
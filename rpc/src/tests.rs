@@ -16,9 +16,9 @@
 
 use crate::{initialize_rpc_server, rpc_trait::RpcFunctions, RpcContext};
 use snarkos_environment::{helpers::State, Client, CurrentNetwork, Environment};
-use snarkos_network::{ledger::Ledger, Operator, Peers, Prover};
+use snarkos_network::{helpers::StaticResolver, ledger::Ledger, Operator, Peers, Prover};
 use snarkos_storage::{
-    storage::{rocksdb::RocksDB, Storage},
+    storage::{rocksdb::RocksDB, MapId, Storage},
     LedgerState,
 };
 use snarkvm::{
@@ -42,6 +42,8 @@ use std::{
     fs,
     net::SocketAddr,
     path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
 
 fn temp_dir() -> std::path::PathBuf {
@@ -67,13 +69,19 @@ async fn new_rpc_context<N: Network, E: Environment, S: Storage, P: AsRef<Path>>
     E::status().update(State::Ready);
 
     // Derive the storage paths.
-    let (ledger_path, prover_path, operator_storage_path) = (path.as_ref().to_path_buf(), temp_dir(), temp_dir());
+    let (ledger_path, prover_path, operator_storage_path, peer_book_path) =
+        (path.as_ref().to_path_buf(), temp_dir(), temp_dir(), temp_dir());
 
     // Initialize a new instance for managing peers.
-    let peers = Peers::new(node_addr, None).await;
+    // Resolve the hostnames of the tests to this node.
+    let resolver = StaticResolver::default();
+    resolver.insert("node.example.com", vec![node_addr.ip()]);
+    let peers = Peers::open::<S, _>(&peer_book_path, node_addr, None, None, Some(Arc::new(resolver)))
+        .await
+        .expect("Failed to initialize peers");
 
     // Initialize a new instance for managing the ledger.
-    let ledger = Ledger::<N, E>::open::<S, _>(&ledger_path, peers.router())
+    let ledger = Ledger::<N, E>::open::<S, _>(&ledger_path, None, vec![], false, Default::default(), peers.router())
         .await
         .expect("Failed to initialize ledger");
 
@@ -113,6 +121,7 @@ async fn new_rpc_context<N: Network, E: Environment, S: Storage, P: AsRef<Path>>
         operator,
         prover.router(),
         prover.memory_pool(),
+        None,
     )
 }
 
@@ -527,6 +536,40 @@ async fn test_get_node_state() {
     assert_eq!(response, expected);
 }
 
+#[tokio::test]
+async fn test_get_storage_info() {
+    // Initialize a new RPC server and create an associated client.
+    let rpc_server_addr = new_rpc_server::<CurrentNetwork, Client<CurrentNetwork>, RocksDB>(None).await;
+    let rpc_client = new_rpc_client(rpc_server_addr);
+
+    // Send the request to the server.
+    let response: serde_json::Value = rpc_client.request("getstorageinfo", None).await.expect("Invalid response");
+
+    // Check that every map is reported, including the block header of the genesis block.
+    let maps = response["maps"].as_array().expect("Invalid maps");
+    assert_eq!(maps.len(), MapId::ALL.len());
+    let block_headers = maps.iter().find(|map| map["name"] == "block_headers").expect("Missing block headers");
+    assert!(block_headers["num_keys"].as_u64().unwrap() >= 1);
+    assert!(response["size"].as_u64().unwrap() >= block_headers["size"].as_u64().unwrap());
+}
+
+#[tokio::test]
+async fn test_backup() {
+    // Initialize a new RPC server and create an associated client.
+    let rpc_server_addr = new_rpc_server::<CurrentNetwork, Client<CurrentNetwork>, RocksDB>(None).await;
+    let rpc_client = new_rpc_client(rpc_server_addr);
+
+    // Ensure a request with invalid credentials is rejected.
+    let params = rpc_params!["root", "invalid"];
+    let response: Result<serde_json::Value, _> = rpc_client.request("backup", params).await;
+    assert!(response.unwrap_err().to_string().contains("Invalid RPC credentials"));
+
+    // Ensure a request with valid credentials is rejected, as backups are not configured.
+    let params = rpc_params!["root", "pass"];
+    let response: Result<serde_json::Value, _> = rpc_client.request("backup", params).await;
+    assert!(response.unwrap_err().to_string().contains("Backups are not configured"));
+}
+
 #[tokio::test]
 async fn test_get_transaction() {
     /// Additional metadata included with a transaction response
@@ -611,6 +654,152 @@ async fn test_get_connected_peers() {
     assert!(response.is_empty());
 }
 
+#[tokio::test]
+async fn test_get_peer_info() {
+    // Initialize a new RPC server and create an associated client.
+    let rpc_server_addr = new_rpc_server::<CurrentNetwork, Client<CurrentNetwork>, RocksDB>(None).await;
+    let rpc_client = new_rpc_client(rpc_server_addr);
+
+    // Send the request to the server.
+    let response: Vec<serde_json::Value> = rpc_client.request("getpeerinfo", None).await.expect("Invalid response");
+
+    // Check that no peers are reported, as none are connected.
+    assert!(response.is_empty());
+}
+
+#[tokio::test]
+async fn test_get_banned_peers() {
+    // Initialize a new RPC server and create an associated client.
+    let rpc_server_context = new_rpc_context::<CurrentNetwork, Client<CurrentNetwork>, RocksDB, PathBuf>(temp_dir()).await;
+    let rpc_server_addr = new_rpc_server::<_, _, RocksDB>(Some(rpc_server_context.clone())).await;
+    let rpc_client = new_rpc_client(rpc_server_addr);
+
+    // Send the request to the server.
+    let response: Vec<serde_json::Value> = rpc_client.request("getbannedpeers", None).await.expect("Invalid response");
+    assert!(response.is_empty());
+
+    // Ban a peer twice, as a repeat offender.
+    let peer_ip: SocketAddr = "127.0.0.1:4132".parse().unwrap();
+    let peer_book = rpc_server_context.peers.peer_book();
+    for _ in 0..2 {
+        peer_book
+            .ban_repeat_offender(peer_ip, Duration::from_secs(60), Duration::from_secs(3600))
+            .expect("Failed to ban peer");
+    }
+
+    // Check the banned peer.
+    let response: Vec<serde_json::Value> = rpc_client.request("getbannedpeers", None).await.expect("Invalid response");
+    assert_eq!(response.len(), 1);
+    assert_eq!(response[0]["ip"], peer_ip.to_string());
+    assert_eq!(response[0]["num_bans"], 2);
+}
+
+#[tokio::test]
+async fn test_connect() {
+    // Initialize a new RPC server and create an associated client.
+    let rpc_server_addr = new_rpc_server::<CurrentNetwork, Client<CurrentNetwork>, RocksDB>(None).await;
+    let rpc_client = new_rpc_client(rpc_server_addr);
+
+    // Ensure the result of the connection is returned, as the node may not connect to itself.
+    let params = rpc_params!["127.0.0.1:8888"];
+    let response: Result<bool, _> = rpc_client.request("connect", params).await;
+    assert!(response.unwrap_err().to_string().contains("self-connect"));
+
+    // Ensure a hostname is resolved, here to the node itself.
+    let params = rpc_params!["node.example.com:8888"];
+    let response: Result<bool, _> = rpc_client.request("connect", params).await;
+    assert!(response.unwrap_err().to_string().contains("self-connect"));
+
+    // Ensure a hostname that does not resolve is rejected.
+    let params = rpc_params!["unknown.example.com:8888"];
+    let response: Result<bool, _> = rpc_client.request("connect", params).await;
+    assert!(response.unwrap_err().to_string().contains("Failed to resolve"));
+}
+
+#[tokio::test]
+async fn test_disconnect_peer() {
+    // Initialize a new RPC server and create an associated client.
+    let rpc_server_addr = new_rpc_server::<CurrentNetwork, Client<CurrentNetwork>, RocksDB>(None).await;
+    let rpc_client = new_rpc_client(rpc_server_addr);
+
+    // Ensure a request with invalid credentials is rejected.
+    let params = rpc_params!["root", "invalid", "127.0.0.1:4132"];
+    let response: Result<bool, _> = rpc_client.request("disconnectpeer", params).await;
+    assert!(response.unwrap_err().to_string().contains("Invalid RPC credentials"));
+
+    // Ensure a request for a peer that is not connected is rejected.
+    let params = rpc_params!["root", "pass", "127.0.0.1:4132"];
+    let response: Result<bool, _> = rpc_client.request("disconnectpeer", params).await;
+    assert!(response.unwrap_err().to_string().contains("Not connected"));
+}
+
+#[tokio::test]
+async fn test_ban_peer() {
+    // Initialize a new RPC server and create an associated client.
+    let rpc_server_context = new_rpc_context::<CurrentNetwork, Client<CurrentNetwork>, RocksDB, PathBuf>(temp_dir()).await;
+    let rpc_server_addr = new_rpc_server::<_, _, RocksDB>(Some(rpc_server_context.clone())).await;
+    let rpc_client = new_rpc_client(rpc_server_addr);
+
+    // Ensure a request with invalid credentials is rejected.
+    let peer_ip: SocketAddr = "127.0.0.1:4132".parse().unwrap();
+    let params = rpc_params!["root", "invalid", peer_ip, 3600];
+    let response: Result<bool, _> = rpc_client.request("banpeer", params).await;
+    assert!(response.unwrap_err().to_string().contains("Invalid RPC credentials"));
+
+    // Ban the peer, and wait for the peers to process the request.
+    let params = rpc_params!["root", "pass", peer_ip, 3600];
+    let response: bool = rpc_client.request("banpeer", params).await.expect("Invalid response");
+    assert!(response);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(rpc_server_context.peers.is_restricted(peer_ip).await);
+
+    // Check the banned peer.
+    let response: Vec<serde_json::Value> = rpc_client
+        .request("listbanned", rpc_params!["root", "pass"])
+        .await
+        .expect("Invalid response");
+    assert_eq!(response.len(), 1);
+    assert_eq!(response[0]["ip"], peer_ip.to_string());
+    assert_eq!(response[0]["source"], "Manual");
+    assert!(response[0]["expires_in"].as_i64().unwrap() <= 3600);
+
+    // Lift the ban of the peer.
+    let params = rpc_params!["root", "pass", peer_ip];
+    let response: bool = rpc_client.request("unban", params).await.expect("Invalid response");
+    assert!(response);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!rpc_server_context.peers.is_restricted(peer_ip).await);
+
+    let response: Vec<serde_json::Value> = rpc_client
+        .request("listbanned", rpc_params!["root", "pass"])
+        .await
+        .expect("Invalid response");
+    assert!(response.is_empty());
+}
+
+#[tokio::test]
+async fn test_add_node() {
+    // Initialize a new RPC server and create an associated client.
+    let rpc_server_context = new_rpc_context::<CurrentNetwork, Client<CurrentNetwork>, RocksDB, PathBuf>(temp_dir()).await;
+    let rpc_server_addr = new_rpc_server::<_, _, RocksDB>(Some(rpc_server_context.clone())).await;
+    let rpc_client = new_rpc_client(rpc_server_addr);
+
+    // Add the peer as a trusted node.
+    let peer_ip: SocketAddr = "127.0.0.1:4132".parse().unwrap();
+    let params = rpc_params!["root", "pass", peer_ip];
+    let response: bool = rpc_client.request("addnode", params).await.expect("Invalid response");
+    assert!(response);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(rpc_server_context.peers.trusted_nodes().await.contains(&peer_ip));
+
+    // Remove the peer from the trusted nodes.
+    let params = rpc_params!["root", "pass", peer_ip];
+    let response: bool = rpc_client.request("removenode", params).await.expect("Invalid response");
+    assert!(response);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!rpc_server_context.peers.trusted_nodes().await.contains(&peer_ip));
+}
+
 #[tokio::test]
 async fn test_send_transaction() {
     let mut rng = ChaChaRng::seed_from_u64(123456789);
@@ -0,0 +1,127 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_environment::{helpers::NodeLists, Environment};
+use snarkos_network::{helpers::PeerAddr, Peers};
+use snarkvm::dpc::Network;
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::{collections::HashSet, fs, net::SocketAddr, path::Path};
+
+///
+/// The node config file, in TOML format, which replaces the built-in lists of nodes, e.g.
+///
+/// ```toml
+/// beacon_nodes = ["10.0.0.1:4132"]
+/// sync_nodes = ["10.0.0.2:4132", "sync.example.com:4132"]
+/// trusted_nodes = ["pool.example.com:4132"]
+/// dns_seeds = ["seed.example.com"]
+/// ```
+///
+/// A list that is not given keeps the built-in list of the environment.
+///
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NodeConfig {
+    /// The beacon nodes to bootstrap the node server with.
+    pub beacon_nodes: Option<HashSet<PeerAddr>>,
+    /// The sync nodes to bootstrap the node server with.
+    pub sync_nodes: Option<HashSet<PeerAddr>>,
+    /// The nodes to maintain connections with.
+    pub trusted_nodes: Option<HashSet<PeerAddr>>,
+    /// The DNS seeds, as hostnames with an optional port, whose records are peers to bootstrap the node server with.
+    pub dns_seeds: Option<Vec<String>>,
+}
+
+impl NodeConfig {
+    /// Loads the node config file at the given path.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|error| anyhow!("Failed to read the node config {}: {}", path.display(), error))?;
+        toml::from_str(&contents).map_err(|error| anyhow!("Failed to parse the node config {}: {}", path.display(), error))
+    }
+
+    /// Returns the node config with the lists of the given config, which take precedence, merged into it.
+    pub fn merge(self, overrides: Self) -> Self {
+        Self {
+            beacon_nodes: overrides.beacon_nodes.or(self.beacon_nodes),
+            sync_nodes: overrides.sync_nodes.or(self.sync_nodes),
+            trusted_nodes: overrides.trusted_nodes.or(self.trusted_nodes),
+            dns_seeds: overrides.dns_seeds.or(self.dns_seeds),
+        }
+    }
+
+    /// Replaces the lists of nodes and the DNS seeds with the configured ones, or else the built-in ones.
+    /// The hostnames of the beacon and sync nodes are resolved once, skipping those that fail to resolve.
+    pub async fn apply<N: Network, E: Environment>(&self, peers: &Peers<N, E>) -> Result<()> {
+        let node_lists = E::node_lists();
+        node_lists.set_beacon_nodes(Self::resolve(peers, &self.beacon_nodes, E::BEACON_NODES).await);
+        node_lists.set_sync_nodes(Self::resolve(peers, &self.sync_nodes, E::SYNC_NODES).await);
+        self.apply_trusted_nodes(peers).await;
+
+        let dns_seeds = match &self.dns_seeds {
+            Some(dns_seeds) => dns_seeds.iter().map(String::as_str).collect(),
+            None => E::DNS_SEEDS.to_vec(),
+        };
+        let dns_seeds = dns_seeds
+            .into_iter()
+            .map(|dns_seed| PeerAddr::parse_with_default_port(dns_seed, E::DEFAULT_NODE_PORT))
+            .collect::<Result<_>>()?;
+        peers.set_dns_seeds(dns_seeds).await;
+        Ok(())
+    }
+
+    /// Replaces the trusted nodes with the configured ones, or else the built-in ones.
+    /// The hostnames of the trusted nodes are resolved again whenever they are not connected.
+    pub async fn apply_trusted_nodes<N: Network, E: Environment>(&self, peers: &Peers<N, E>) {
+        let trusted_nodes = match &self.trusted_nodes {
+            Some(trusted_nodes) => trusted_nodes.clone(),
+            None => NodeLists::parse(E::TRUSTED_NODES).into_iter().map(PeerAddr::Ip).collect(),
+        };
+
+        let (mut trusted_ips, mut trusted_hosts) = (HashSet::new(), HashSet::new());
+        for trusted_node in trusted_nodes {
+            match trusted_node {
+                PeerAddr::Ip(ip) => trusted_ips.insert(ip),
+                host => trusted_hosts.insert(host),
+            };
+        }
+        E::node_lists().set_trusted_nodes(trusted_ips);
+        peers.set_trusted_hosts(trusted_hosts).await;
+    }
+
+    /// Returns the IPs of the given nodes, or else of the given built-in nodes.
+    async fn resolve<N: Network, E: Environment>(
+        peers: &Peers<N, E>,
+        nodes: &Option<HashSet<PeerAddr>>,
+        default_nodes: &[&str],
+    ) -> HashSet<SocketAddr> {
+        match nodes {
+            Some(nodes) => {
+                let mut node_ips = HashSet::with_capacity(nodes.len());
+                for node in nodes {
+                    match peers.resolve(node).await {
+                        Ok(ips) => node_ips.extend(ips),
+                        Err(error) => warn!("Failed to resolve {}: {}", node, error),
+                    }
+                }
+                node_ips
+            }
+            None => NodeLists::parse(default_nodes),
+        }
+    }
+}
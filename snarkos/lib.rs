@@ -24,6 +24,9 @@ extern crate thiserror;
 #[macro_use]
 extern crate tracing;
 
+pub mod config;
+pub use config::*;
+
 pub(crate) mod display;
 pub(crate) use display::*;
 
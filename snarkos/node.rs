@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{Display, Server, Updater};
+use crate::{Display, NodeConfig, Server, Updater};
 use snarkos_environment::{
     helpers::NodeType,
     Client,
@@ -29,7 +29,8 @@ use snarkos_environment::{
     ProverTrial,
     SyncNode,
 };
-use snarkos_storage::storage::rocksdb::RocksDB;
+use snarkos_network::helpers::PeerAddr;
+use snarkos_storage::storage::{export::Compression, rocksdb::RocksDB, schema, MapId, Storage};
 use snarkvm::dpc::prelude::*;
 
 use anyhow::{anyhow, Result};
@@ -43,9 +44,9 @@ use tracing_subscriber::EnvFilter;
 #[derive(Debug, Parser)]
 #[clap(name = "snarkos", author = "The Aleo Team <hello@aleo.org>")]
 pub struct Node {
-    /// Specify the IP address and port of a peer to connect to.
+    /// Specify the IP address or hostname, and port, of a peer to connect to.
     #[clap(long = "connect")]
-    pub connect: Option<String>,
+    pub connect: Option<PeerAddr>,
     /// Specify this as a mining node, with the given miner address.
     #[clap(long = "miner")]
     pub miner: Option<String>,
@@ -55,9 +56,10 @@ pub struct Node {
     /// Specify this as a prover node, with the given prover address.
     #[clap(long = "prover")]
     pub prover: Option<String>,
-    /// Specify the pool that a prover node is contributing to.
+    /// Specify the pool that a prover node is contributing to, by IP address or hostname, and port.
+    /// A hostname is resolved again whenever the prover reconnects to the pool.
     #[clap(long = "pool")]
-    pub pool: Option<SocketAddr>,
+    pub pool: Option<PeerAddr>,
     /// Specify the network of this node.
     #[clap(default_value = "2", long = "network")]
     pub network: u16,
@@ -85,6 +87,54 @@ pub struct Node {
     /// If the flag is set, the node will not initialize the RPC server.
     #[clap(long)]
     pub norpc: bool,
+    /// Specify the number of most recent blocks to keep transactions for, discarding older ones.
+    #[clap(long = "prune")]
+    pub prune: Option<u32>,
+    /// Specify a view key or private key to index the owned records of, which may be repeated.
+    #[clap(long = "index")]
+    pub index: Vec<String>,
+    /// If the flag is set, the RPC server and peers read from the ledger directly, instead of a secondary reader.
+    #[clap(long = "shared-reader")]
+    pub shared_reader: bool,
+    /// Specify the number of recent blocks to cache in the ledger.
+    #[clap(default_value = "256", long = "block-cache")]
+    pub block_cache: usize,
+    /// Specify the number of recent block headers to cache in the ledger.
+    #[clap(default_value = "4096", long = "header-cache")]
+    pub header_cache: usize,
+    /// Specify the number of recent serialized blocks to cache in the ledger, to serve block requests with.
+    #[clap(default_value = "256", long = "block-bytes-cache")]
+    pub block_bytes_cache: usize,
+    /// Specify the directory to create online backups of the ledger and operator storage in.
+    #[clap(parse(from_os_str), long = "backup-dir")]
+    pub backup_dir: Option<PathBuf>,
+    /// Specify the interval in seconds between scheduled backups.
+    #[clap(long = "backup-interval", requires = "backup_dir")]
+    pub backup_interval: Option<u64>,
+    /// Specify the number of most recent backups to keep, discarding older ones.
+    #[clap(long = "backup-keep", requires = "backup_dir")]
+    pub backup_keep: Option<usize>,
+    /// If the flag is set, the node encrypts its connections with supporting peers, using a persistent identity key.
+    /// The identity of a peer is recorded on first contact, and a changed identity is only refused for trusted,
+    /// beacon and sync nodes; connections with other peers are encrypted, but not authenticated.
+    #[clap(long = "noise")]
+    pub noise: bool,
+    /// Specify the node config file, which replaces the built-in lists of beacon, sync and trusted nodes.
+    /// On Unix-family systems, SIGHUP reloads the trusted nodes from it.
+    #[clap(parse(from_os_str), long = "config")]
+    pub config: Option<PathBuf>,
+    /// Specify a beacon node to bootstrap with, which may be repeated, replacing the beacon nodes of the node config.
+    #[clap(long = "beacon-node")]
+    pub beacon_nodes: Vec<PeerAddr>,
+    /// Specify a sync node to bootstrap with, which may be repeated, replacing the sync nodes of the node config.
+    #[clap(long = "sync-node")]
+    pub sync_nodes: Vec<PeerAddr>,
+    /// Specify a node to maintain a connection with, which may be repeated, replacing the trusted nodes of the node config.
+    #[clap(long = "trusted-node")]
+    pub trusted_nodes: Vec<PeerAddr>,
+    /// Specify a DNS seed to bootstrap with, which may be repeated, replacing the DNS seeds of the node config.
+    #[clap(long = "dns-seed")]
+    pub dns_seeds: Vec<String>,
     #[clap(hide = true, long)]
     pub trial: bool,
     #[clap(hide = true, long)]
@@ -168,6 +218,53 @@ impl Node {
         }
     }
 
+    /// Returns the storage path of the peer book.
+    pub(crate) fn peer_book_storage_path(&self, _local_ip: SocketAddr) -> PathBuf {
+        if cfg!(feature = "test") {
+            // Tests may use any available ports, and removes the storage artifacts afterwards,
+            // so that there is no need to adhere to a specific number assignment logic.
+            PathBuf::from(format!("/tmp/snarkos-test-peers-{}", _local_ip.port()))
+        } else {
+            // The peer book is kept beside the ledger, so that restoring a ledger backup does not replace it.
+            aleo_std::aleo_ledger_dir(self.network, self.dev).with_extension("peers")
+        }
+    }
+
+    /// Returns the path of the identity key of the node.
+    pub(crate) fn identity_path(&self, _local_ip: SocketAddr) -> PathBuf {
+        if cfg!(feature = "test") {
+            // Tests may use any available ports, and removes the storage artifacts afterwards,
+            // so that there is no need to adhere to a specific number assignment logic.
+            PathBuf::from(format!("/tmp/snarkos-test-identity-{}", _local_ip.port()))
+        } else {
+            // The identity is kept beside the ledger, so that restoring a ledger backup does not replace it.
+            aleo_std::aleo_ledger_dir(self.network, self.dev).with_extension("identity")
+        }
+    }
+
+    /// Returns the node config, loaded from the node config file, if any, with the command-line flags merged into it.
+    pub(crate) fn node_config(&self) -> Result<NodeConfig> {
+        let node_config = match &self.config {
+            Some(path) => NodeConfig::load(path)?,
+            None => NodeConfig::default(),
+        };
+        Ok(node_config.merge(self.node_config_overrides()))
+    }
+
+    /// Returns the lists of nodes given by the command-line flags, which take precedence over the node config file.
+    fn node_config_overrides(&self) -> NodeConfig {
+        let to_set = |nodes: &Vec<PeerAddr>| match nodes.is_empty() {
+            true => None,
+            false => Some(nodes.iter().cloned().collect()),
+        };
+        NodeConfig {
+            beacon_nodes: to_set(&self.beacon_nodes),
+            sync_nodes: to_set(&self.sync_nodes),
+            trusted_nodes: to_set(&self.trusted_nodes),
+            dns_seeds: (!self.dns_seeds.is_empty()).then(|| self.dns_seeds.clone()),
+        }
+    }
+
     async fn start_server<N: Network, E: Environment>(&self, address: &Option<String>) -> Result<()> {
         println!("{}", crate::display::welcome_message());
 
@@ -184,11 +281,11 @@ impl Node {
         println!("{}", crate::display::notification_message::<N>(address));
 
         // Initialize the node's server.
-        let server = Server::<N, E>::initialize(self, address, self.pool).await?;
+        let server = Server::<N, E>::initialize(self, address, self.pool.clone()).await?;
 
         // Initialize signal handling; it also maintains ownership of the Server
         // in order for it to not go out of scope.
-        handle_signals(server.clone());
+        handle_signals(server.clone(), self);
 
         // Initialize the display, if enabled.
         if self.display {
@@ -197,8 +294,13 @@ impl Node {
         };
 
         // Connect to a peer if one was given as an argument.
-        if let Some(peer_ip) = &self.connect {
-            let _ = server.connect_to(peer_ip.parse().unwrap()).await;
+        if let Some(peer_addr) = &self.connect {
+            match server.peers().resolve(peer_addr).await {
+                Ok(peer_ips) => {
+                    let _ = server.connect_to(peer_ips[0]).await;
+                }
+                Err(error) => warn!("Failed to resolve {}: {}", peer_addr, error),
+            }
         }
 
         // Note: Do not move this. The pending await must be here otherwise
@@ -246,6 +348,8 @@ pub enum Command {
     Experimental(Experimental),
     #[clap(name = "miner", about = "Miner commands and settings")]
     Miner(MinerSubcommand),
+    #[clap(name = "ledger", about = "Ledger commands and maintenance")]
+    Ledger(LedgerSubcommand),
 }
 
 impl Command {
@@ -255,6 +359,7 @@ impl Command {
             Self::Update(command) => command.parse(),
             Self::Experimental(command) => command.parse(),
             Self::Miner(command) => command.parse(),
+            Self::Ledger(command) => command.parse(),
         }
     }
 }
@@ -412,6 +517,349 @@ impl NewAccount {
     }
 }
 
+#[derive(Debug, Parser)]
+pub struct LedgerSubcommand {
+    #[clap(subcommand)]
+    commands: LedgerCommands,
+}
+
+impl LedgerSubcommand {
+    pub fn parse(self) -> Result<String> {
+        match self.commands {
+            LedgerCommands::Verify(command) => command.parse(),
+            LedgerCommands::Migrate(command) => command.parse(),
+            LedgerCommands::Stats(command) => command.parse(),
+            LedgerCommands::Restore(command) => command.parse(),
+            LedgerCommands::Export(command) => command.parse(),
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+pub enum LedgerCommands {
+    #[clap(name = "verify", about = "Verifies the integrity of the ledger in storage.")]
+    Verify(LedgerVerify),
+    #[clap(name = "migrate", about = "Migrates the ledger in storage to the latest schema version.")]
+    Migrate(LedgerMigrate),
+    #[clap(name = "stats", about = "Reports the approximate number of keys and size of each map of the ledger in storage.")]
+    Stats(LedgerStats),
+    #[clap(name = "restore", about = "Restores the ledger and operator storage from an online backup.")]
+    Restore(LedgerRestore),
+    #[clap(name = "export", about = "Exports the ledger in storage to a compressed and checksummed storage file.")]
+    Export(LedgerExport),
+}
+
+#[derive(Debug, Parser)]
+pub struct LedgerVerify {
+    /// Specify the network of the ledger to verify.
+    #[clap(default_value = "2", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node to verify.
+    #[clap(long)]
+    pub dev: Option<u16>,
+    /// If the flag is set, the inconsistencies that are found will be repaired.
+    #[clap(long)]
+    pub repair: bool,
+}
+
+impl LedgerVerify {
+    pub fn parse(self) -> Result<String> {
+        // Construct the path to the ledger in storage.
+        let path = aleo_std::aleo_ledger_dir(self.network, self.dev);
+        if !path.exists() {
+            return Ok(format!("No ledger files were found in storage. ({})", path.display()));
+        }
+
+        // Verify the ledger, which is only supported for the current network.
+        if self.network != CurrentNetwork::NETWORK_ID {
+            return Err(anyhow!("Unable to verify a ledger of unsupported network {}", self.network));
+        }
+        let report = snarkos_storage::LedgerState::<CurrentNetwork>::verify::<RocksDB, _>(&path, self.repair)?;
+
+        // Print the inconsistencies that were found.
+        let mut output = "".to_string();
+        for issue in &report.issues {
+            output += &format!(" {:>12}  {}\n", "Issue".red().bold(), issue);
+        }
+
+        match report.latest_block_height {
+            Some(block_height) => output += &format!("\n {:>12}  {}\n", "Block Height".cyan().bold(), block_height),
+            None => output += &format!("\n {:>12}  {}\n", "Block Height".cyan().bold(), "none"),
+        }
+
+        if report.is_consistent() {
+            output += &format!(" {:>12}  The ledger is consistent. ({})\n", "Verified".green().bold(), path.display());
+        } else if report.repaired {
+            output += &format!(" {:>12}  {} issues in the ledger. ({})\n", "Repaired".green().bold(), report.issues.len(), path.display());
+        } else {
+            let repairable = report.issues.iter().filter(|issue| issue.is_repairable()).count();
+            output += &format!(
+                " {:>12}  {} issues in the ledger, {} of which can be repaired in place; use `--repair` to repair them. ({})\n",
+                "Found".yellow().bold(),
+                report.issues.len(),
+                repairable,
+                path.display()
+            );
+        }
+
+        Ok(output)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct LedgerMigrate {
+    /// Specify the network of the ledger to migrate.
+    #[clap(default_value = "2", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node to migrate.
+    #[clap(long)]
+    pub dev: Option<u16>,
+    /// If the flag is set, the pending migrations are listed without being applied.
+    #[clap(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+impl LedgerMigrate {
+    pub fn parse(self) -> Result<String> {
+        // Construct the path to the ledger in storage.
+        let path = aleo_std::aleo_ledger_dir(self.network, self.dev);
+        if !path.exists() {
+            return Ok(format!("No ledger files were found in storage. ({})", path.display()));
+        }
+
+        // Open the storage without migrating it, in read-only mode for a dry run.
+        let storage = RocksDB::open_unchecked(&path, self.network, self.dry_run)?;
+        let version = schema::schema_version(&storage)?.unwrap_or(schema::SCHEMA_VERSION);
+
+        let migrations = match self.dry_run {
+            true => schema::pending_migrations(&storage)?,
+            false => schema::migrate(&storage)?,
+        };
+
+        // Print the migrations.
+        let mut output = "".to_string();
+        for migration in &migrations {
+            let status = match self.dry_run {
+                true => "Pending".yellow().bold(),
+                false => "Migrated".green().bold(),
+            };
+            output += &format!(" {:>12}  Version {}: {}\n", status, migration.version, migration.description);
+        }
+
+        if migrations.is_empty() {
+            output += &format!(" {:>12}  The ledger is at schema version {}. ({})\n", "Current".green().bold(), version, path.display());
+        } else if self.dry_run {
+            output += &format!(
+                "\n {:>12}  {} migrations from schema version {} to {}; run without `--dry-run` to apply them. ({})\n",
+                "Found".yellow().bold(),
+                migrations.len(),
+                version,
+                schema::SCHEMA_VERSION,
+                path.display()
+            );
+        } else {
+            output += &format!(
+                "\n {:>12}  The ledger was migrated from schema version {} to {}. ({})\n",
+                "Migrated".green().bold(),
+                version,
+                schema::SCHEMA_VERSION,
+                path.display()
+            );
+        }
+
+        Ok(output)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct LedgerStats {
+    /// Specify the network of the ledger to report on.
+    #[clap(default_value = "2", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node to report on.
+    #[clap(long)]
+    pub dev: Option<u16>,
+}
+
+impl LedgerStats {
+    pub fn parse(self) -> Result<String> {
+        // Construct the path to the ledger in storage.
+        let path = aleo_std::aleo_ledger_dir(self.network, self.dev);
+        if !path.exists() {
+            return Ok(format!("No ledger files were found in storage. ({})", path.display()));
+        }
+
+        // Open the storage in read-only mode, so the ledger may remain in use by a running node.
+        let storage = RocksDB::open_unchecked(&path, CurrentNetwork::NETWORK_ID, true)?;
+        let stats = storage.stats()?;
+
+        // Print the statistics of each map, followed by the totals.
+        let mut output = format!(" {:>24}  {:>14}  {:>12}\n", "Map".cyan().bold(), "Keys".cyan().bold(), "Size".cyan().bold());
+        for map in &stats.maps {
+            output += &format!(" {:>24}  {:>14}  {:>12}\n", map.map_id.name(), map.num_keys, format_size(map.size));
+        }
+        output += &format!("\n {:>24}  {:>14}  {:>12}\n", "Total".green().bold(), stats.num_keys(), format_size(stats.size()));
+        output += &format!(" {:>24}  {:>14}  {:>12}\n", "SST Files".green().bold(), "", format_size(stats.sst_files_size));
+        output += &format!(" {:>24}  {:>14}  {:>12}\n", "WAL Files".green().bold(), "", format_size(stats.wal_files_size));
+        output += &format!("\n The key counts and sizes are approximate. ({})\n", path.display());
+
+        Ok(output)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct LedgerRestore {
+    /// Specify the network of the ledger to restore.
+    #[clap(default_value = "2", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node to restore.
+    #[clap(long)]
+    pub dev: Option<u16>,
+    /// Specify the directory the backups were created in.
+    #[clap(parse(from_os_str), long = "backup-dir")]
+    pub backup_dir: PathBuf,
+    /// Specify the ID of the ledger backup to restore, instead of the most recent one.
+    #[clap(long = "ledger-backup-id")]
+    pub ledger_backup_id: Option<u32>,
+    /// Specify the ID of the operator backup to restore, instead of the most recent one.
+    #[clap(long = "operator-backup-id")]
+    pub operator_backup_id: Option<u32>,
+    /// If the flag is set, the available backups are listed without being restored.
+    #[clap(long)]
+    pub list: bool,
+    /// If the flag is set, the existing storage is replaced by the backup.
+    #[clap(long)]
+    pub force: bool,
+}
+
+impl LedgerRestore {
+    pub fn parse(self) -> Result<String> {
+        // The ledger and operator storage number their backups independently.
+        let directories = [
+            (
+                "Ledger",
+                self.backup_dir.join("ledger"),
+                aleo_std::aleo_ledger_dir(self.network, self.dev),
+                self.ledger_backup_id,
+            ),
+            (
+                "Operator",
+                self.backup_dir.join("operator"),
+                aleo_std::aleo_operator_dir(self.network, self.dev),
+                self.operator_backup_id,
+            ),
+        ];
+
+        // List the available backups.
+        if self.list {
+            let mut output = "".to_string();
+            for (name, backup_path, _, _) in &directories {
+                for backup in RocksDB::backups(backup_path)? {
+                    output += &format!(
+                        " {:>12}  Backup {} of {} files ({}), created at {}\n",
+                        name.cyan().bold(),
+                        backup.backup_id,
+                        backup.num_files,
+                        format_size(backup.size),
+                        backup.timestamp
+                    );
+                }
+            }
+            if output.is_empty() {
+                output = format!("No backups were found. ({})\n", self.backup_dir.display());
+            }
+            return Ok(output);
+        }
+
+        // Ensure the existing storage is only replaced deliberately, as the node must not be running.
+        for (_, backup_path, path, _) in &directories {
+            if backup_path.exists() && path.exists() && !self.force {
+                return Err(anyhow!(
+                    "The storage at {} already exists; stop the node and use `--force` to replace it",
+                    path.display()
+                ));
+            }
+        }
+
+        let mut output = "".to_string();
+        for (name, backup_path, path, backup_id) in &directories {
+            // A node that is not an operator has no backups of the operator storage.
+            if !backup_path.exists() {
+                continue;
+            }
+            let backup = RocksDB::restore_backup(backup_path, path, *backup_id)?;
+            output += &format!(
+                " {:>12}  {} backup {} to {}\n",
+                "Restored".green().bold(),
+                name,
+                backup.backup_id,
+                path.display()
+            );
+        }
+        if output.is_empty() {
+            return Err(anyhow!("No backups were found in {}", self.backup_dir.display()));
+        }
+
+        Ok(output)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct LedgerExport {
+    /// Specify the network of the ledger to export.
+    #[clap(default_value = "2", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node to export.
+    #[clap(long)]
+    pub dev: Option<u16>,
+    /// Specify the path of the storage file to export the ledger to.
+    #[clap(parse(from_os_str), long = "output")]
+    pub output: PathBuf,
+    /// Specify the compression of the storage file, as one of 'none', 'lz4' or 'zstd'.
+    #[clap(default_value = "lz4", long = "compression")]
+    pub compression: Compression,
+}
+
+impl LedgerExport {
+    pub fn parse(self) -> Result<String> {
+        // Construct the path to the ledger in storage.
+        let path = aleo_std::aleo_ledger_dir(self.network, self.dev);
+        if !path.exists() {
+            return Ok(format!("No ledger files were found in storage. ({})", path.display()));
+        }
+
+        // Open the storage in read-only mode, so the ledger may remain in use by a running node.
+        let storage = RocksDB::open(&path, self.network, true)?;
+        storage.export_maps(&self.output, MapId::ALL, self.compression)?;
+
+        let size = std::fs::metadata(&self.output)?.len();
+        Ok(format!(
+            " {:>12}  The ledger was exported to {} ({}, {} compression). ({})\n",
+            "Exported".green().bold(),
+            self.output.display(),
+            format_size(size),
+            self.compression,
+            path.display()
+        ))
+    }
+}
+
+/// Returns the given number of bytes in a human-readable form.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} {}", bytes, UNITS[0]),
+        _ => format!("{:.2} {}", size, UNITS[unit]),
+    }
+}
+
 #[derive(Debug, Parser)]
 pub struct MinerSubcommand {
     #[clap(subcommand)]
@@ -492,8 +940,61 @@ impl MinerStats {
 
 // This function is responsible for handling OS signals in order
 // for the node to be able to intercept them and perform a clean shutdown.
-// Note: Only Ctrl-C is supported; it should work on both Unix-family systems and Windows.
-pub fn handle_signals<N: Network, E: Environment>(server: Server<N, E>) {
+// Note: Ctrl-C should work on both Unix-family systems and Windows, while
+// SIGUSR1 creates an online backup on Unix-family systems, if backups are configured,
+// and SIGHUP reloads the trusted nodes on Unix-family systems, if a node config file is given.
+#[cfg_attr(not(unix), allow(unused_variables))]
+pub fn handle_signals<N: Network, E: Environment>(server: Server<N, E>, node: &Node) {
+    #[cfg(unix)]
+    if let Some(backups) = server.backups() {
+        E::resources().register_task(
+            None, // No need to provide an id, as the task will run indefinitely.
+            tokio::task::spawn(async move {
+                let mut signals = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined1()) {
+                    Ok(signals) => signals,
+                    Err(error) => {
+                        error!("tokio::signal::unix::signal encountered an error: {}", error);
+                        return;
+                    }
+                };
+                while signals.recv().await.is_some() {
+                    if let Err(error) = backups.backup().await {
+                        error!("Failed to create the requested backup: {}", error);
+                    }
+                }
+            }),
+        );
+    }
+
+    #[cfg(unix)]
+    if let Some(path) = node.config.clone() {
+        let node_config_overrides = node.node_config_overrides();
+        let peers = server.peers();
+        E::resources().register_task(
+            None, // No need to provide an id, as the task will run indefinitely.
+            tokio::task::spawn(async move {
+                let mut signals = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+                    Ok(signals) => signals,
+                    Err(error) => {
+                        error!("tokio::signal::unix::signal encountered an error: {}", error);
+                        return;
+                    }
+                };
+                while signals.recv().await.is_some() {
+                    match NodeConfig::load(&path) {
+                        Ok(node_config) => {
+                            // The heartbeat connects to the trusted nodes that were added.
+                            let node_config = node_config.merge(node_config_overrides.clone());
+                            node_config.apply_trusted_nodes(&peers).await;
+                            info!("Reloaded the trusted nodes from {}", path.display());
+                        }
+                        Err(error) => error!("Failed to reload the node config: {}", error),
+                    }
+                }
+            }),
+        );
+    }
+
     E::resources().register_task(
         None, // No need to provide an id, as the task will run indefinitely.
         tokio::task::spawn(async move {
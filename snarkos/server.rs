@@ -20,14 +20,18 @@ use snarkos_environment::{
     Environment,
 };
 use snarkos_network::{
+    backups::{BackupConfig, Backups},
+    helpers::{NodeIdentity, PeerAddr},
     ledger::{Ledger, LedgerReader, LedgerRequest, LedgerRouter},
     operator::{Operator, OperatorRouter},
     peers::{Peers, PeersRequest, PeersRouter},
-    prover::{Prover, ProverRouter},
+    prover::{Prover, ProverRequest, ProverRouter},
 };
-use snarkos_storage::storage::rocksdb::RocksDB;
+use snarkos_storage::{storage::rocksdb::RocksDB, IndexerKey, LedgerCacheConfig};
 use snarkvm::prelude::*;
 
+#[cfg(feature = "rpc")]
+use snarkos_network::helpers::MemoryPool;
 #[cfg(feature = "rpc")]
 use snarkos_rpc::{initialize_rpc_server, RpcContext};
 
@@ -38,7 +42,7 @@ use snarkos_metrics as metrics;
 use tokio::sync::RwLock;
 
 use anyhow::Result;
-use std::{net::SocketAddr, sync::Arc, time::Duration};
+use std::{net::SocketAddr, str::FromStr, sync::Arc, time::Duration};
 use tokio::{net::TcpListener, sync::oneshot, task};
 
 ///
@@ -56,6 +60,8 @@ pub struct Server<N: Network, E: Environment> {
     operator: Arc<Operator<N, E>>,
     /// The prover of the node.
     prover: Arc<Prover<N, E>>,
+    /// The backups of the node, if they are configured.
+    backups: Option<Arc<Backups<N, E>>>,
 }
 
 impl<N: Network, E: Environment> Server<N, E> {
@@ -63,7 +69,7 @@ impl<N: Network, E: Environment> Server<N, E> {
     /// Starts the connection listener for peers.
     ///
     #[inline]
-    pub async fn initialize(node: &Node, address: Option<Address<N>>, pool_ip: Option<SocketAddr>) -> Result<Self> {
+    pub async fn initialize(node: &Node, address: Option<Address<N>>, pool: Option<PeerAddr>) -> Result<Self> {
         // Initialize a new TCP listener at the given IP.
         let (local_ip, listener) = match TcpListener::bind(node.node).await {
             Ok(listener) => (listener.local_addr().expect("Failed to fetch the local IP"), listener),
@@ -76,11 +82,44 @@ impl<N: Network, E: Environment> Server<N, E> {
         let operator_storage_path = node.operator_storage_path(local_ip);
         // Initialize the prover storage path.
         let prover_storage_path = node.prover_storage_path(local_ip);
+        // Initialize the peer book storage path.
+        let peer_book_storage_path = node.peer_book_storage_path(local_ip);
+
+        // Initialize the identity key of the node, if its connections are encrypted.
+        let identity = match node.noise {
+            true => {
+                let identity = NodeIdentity::open(node.identity_path(local_ip))?;
+                info!("Encrypting connections with supporting peers as {}", identity.public_key());
+                Some(identity)
+            }
+            false => None,
+        };
 
         // Initialize a new instance for managing peers.
-        let peers = Peers::new(local_ip, None).await;
+        let peers = Peers::open::<RocksDB, _>(&peer_book_storage_path, local_ip, None, identity, None).await?;
+        // Replace the built-in lists of nodes and DNS seeds with those of the node config.
+        node.node_config()?.apply(&peers).await?;
+        // Resolve the pool, which may be given by hostname.
+        let pool_ip = match &pool {
+            Some(pool) => Some(peers.resolve(pool).await?[0]),
+            None => None,
+        };
+        // Initialize the keys to index the owned records of.
+        let indexer_keys = node.index.iter().map(|key| IndexerKey::from_str(key)).collect::<Result<Vec<_>>>()?;
         // Initialize a new instance for managing the ledger.
-        let ledger = Ledger::<N, E>::open::<RocksDB, _>(&ledger_storage_path, peers.router()).await?;
+        let ledger = Ledger::<N, E>::open::<RocksDB, _>(
+            &ledger_storage_path,
+            node.prune,
+            indexer_keys,
+            node.shared_reader,
+            LedgerCacheConfig {
+                blocks: node.block_cache,
+                block_headers: node.header_cache,
+                serialized_blocks: node.block_bytes_cache,
+            },
+            peers.router(),
+        )
+        .await?;
         // Initialize a new instance for managing the prover.
         let prover = Prover::open::<RocksDB, _>(
             &prover_storage_path,
@@ -104,10 +143,23 @@ impl<N: Network, E: Environment> Server<N, E> {
             prover.router(),
         )
         .await?;
+        // Initialize a new instance for managing the backups, if they are configured.
+        let backups = match &node.backup_dir {
+            Some(directory) => {
+                let config = BackupConfig {
+                    directory: directory.clone(),
+                    interval: node.backup_interval.map(Duration::from_secs),
+                    num_backups_to_keep: node.backup_keep,
+                };
+                Some(Backups::open(config, ledger.clone(), operator.clone()).await)
+            }
+            None => None,
+        };
 
         // TODO (howardwu): This is a hack for the prover.
         //  Check that the prover is connected to the pool before sending a PoolRegister message.
-        if let Some(pool_ip) = pool_ip {
+        if let (Some(pool), Some(mut pool_ip)) = (pool, pool_ip) {
+            let peers = peers.clone();
             let peers_router = peers.router();
             let ledger_reader = ledger.reader();
             let ledger_router = ledger.router();
@@ -121,6 +173,20 @@ impl<N: Network, E: Environment> Server<N, E> {
                     // Notify the outer function that the task is ready.
                     let _ = router.send(());
                     loop {
+                        // Resolve the pool again before reconnecting, as it may be behind dynamic DNS.
+                        if !peers.is_connected_to(pool_ip).await {
+                            match peers.resolve(&pool).await {
+                                Ok(pool_ips) if !pool_ips.contains(&pool_ip) => {
+                                    pool_ip = pool_ips[0];
+                                    if let Err(error) = prover_router.send(ProverRequest::PoolAddress(pool_ip)).await {
+                                        trace!("[PoolAddress] {}", error);
+                                    }
+                                }
+                                Ok(_) => (),
+                                Err(error) => warn!("Failed to resolve the pool {}: {}", pool, error),
+                            }
+                        }
+
                         // Initialize the connection process.
                         let (router, handler) = oneshot::channel();
                         // Route a `Connect` request to the pool.
@@ -177,6 +243,7 @@ impl<N: Network, E: Environment> Server<N, E> {
             operator.router(),
             prover.router(),
             prover.memory_pool(),
+            backups.clone(),
         )
         .await;
 
@@ -193,6 +260,7 @@ impl<N: Network, E: Environment> Server<N, E> {
             ledger,
             operator,
             prover,
+            backups,
         })
     }
 
@@ -211,6 +279,11 @@ impl<N: Network, E: Environment> Server<N, E> {
         self.ledger.clone()
     }
 
+    /// Returns the backups of this node, if they are configured.
+    pub fn backups(&self) -> Option<Arc<Backups<N, E>>> {
+        self.backups.clone()
+    }
+
     ///
     /// Sends a connection request to the given IP address.
     ///
@@ -232,8 +305,8 @@ impl<N: Network, E: Environment> Server<N, E> {
             ))
             .await?;
 
-        // Wait until the connection task is initialized.
-        handler.await.map(|_| ()).map_err(|e| e.into())
+        // Wait until the connection task is initialized, and return the result of the connection.
+        handler.await?
     }
 
     ///
@@ -344,6 +417,10 @@ impl<N: Network, E: Environment> Server<N, E> {
                     if let Err(error) = peers_router.send(request).await {
                         error!("Failed to send heartbeat to peers: {}", error)
                     }
+                    // Transmit a heartbeat request to the prover.
+                    if let Err(error) = prover_router.send(ProverRequest::Heartbeat).await {
+                        error!("Failed to send heartbeat to prover: {}", error)
+                    }
                     // Sleep for `E::HEARTBEAT_IN_SECS` seconds.
                     tokio::time::sleep(Duration::from_secs(E::HEARTBEAT_IN_SECS)).await;
                 }
@@ -369,6 +446,7 @@ impl<N: Network, E: Environment> Server<N, E> {
         operator_router: OperatorRouter<N>,
         prover_router: ProverRouter<N>,
         memory_pool: Arc<RwLock<MemoryPool<N>>>,
+        backups: Option<Arc<Backups<N, E>>>,
     ) {
         if !node.norpc {
             // Initialize a new instance of the RPC server.
@@ -383,6 +461,7 @@ impl<N: Network, E: Environment> Server<N, E> {
                 operator_router,
                 prover_router,
                 memory_pool,
+                backups,
             );
             let (rpc_server_addr, rpc_server_handle) = initialize_rpc_server::<N, E>(node.rpc, rpc_context).await;
 
@@ -20,14 +20,66 @@
 #[macro_use]
 extern crate tracing;
 
+/// Declares a test for each of the given generic test functions and each storage backend.
+#[cfg(test)]
+macro_rules! test_storage_backends {
+    ($($test:ident),* $(,)?) => {
+        mod rocksdb {
+            $(
+                #[test]
+                fn $test() {
+                    super::$test::<crate::storage::rocksdb::RocksDB>()
+                }
+            )*
+        }
+
+        mod memory {
+            $(
+                #[test]
+                fn $test() {
+                    super::$test::<crate::storage::memory::MemoryStorage>()
+                }
+            )*
+        }
+    };
+}
+
+/// Declares a test for each of the given generic test functions and each storage backend that persists its
+/// contents once closed, as an in-memory storage is dropped along with its last instance.
+#[cfg(test)]
+macro_rules! test_persistent_storage_backends {
+    ($($test:ident),* $(,)?) => {
+        mod persistent {
+            mod rocksdb {
+                $(
+                    #[test]
+                    fn $test() {
+                        super::super::$test::<crate::storage::rocksdb::RocksDB>()
+                    }
+                )*
+            }
+        }
+    };
+}
+
 mod helpers;
 pub use helpers::BlockLocators;
 
 pub(crate) mod state;
 pub use state::{
+    BanSource,
+    CacheStats,
+    IndexedRecord,
+    IndexerKey,
+    LedgerCacheConfig,
+    LedgerCacheStats,
+    LedgerIssue,
+    LedgerReport,
     LedgerState,
     Metadata,
     OperatorState,
+    PeerBook,
+    PeerEntry,
     ProverState,
     MAXIMUM_BLOCK_LOCATORS,
     MAXIMUM_LINEAR_BLOCK_LOCATORS,
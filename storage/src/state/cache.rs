@@ -0,0 +1,276 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkvm::dpc::prelude::*;
+
+use anyhow::Result;
+use bytes::Bytes;
+use lru::LruCache;
+use parking_lot::Mutex;
+use std::{
+    fmt,
+    hash::Hash,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+///
+/// The capacities of the caches of recent blocks in the ledger, where a capacity of 0 disables a cache.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LedgerCacheConfig {
+    /// The number of blocks to cache.
+    pub blocks: usize,
+    /// The number of block headers to cache.
+    pub block_headers: usize,
+    /// The number of serialized blocks to cache.
+    pub serialized_blocks: usize,
+}
+
+impl Default for LedgerCacheConfig {
+    fn default() -> Self {
+        Self {
+            blocks: 256,
+            block_headers: 4096,
+            serialized_blocks: 256,
+        }
+    }
+}
+
+///
+/// The number of lookups that were answered by a cache, and the number that were not.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Returns the fraction of lookups that were answered by the cache, or 0 if there were none.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            lookups => self.hits as f64 / lookups as f64,
+        }
+    }
+}
+
+///
+/// The statistics of the caches of recent blocks in the ledger.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LedgerCacheStats {
+    pub blocks: CacheStats,
+    pub block_headers: CacheStats,
+    pub serialized_blocks: CacheStats,
+}
+
+///
+/// A least-recently-used cache, which counts its hits and misses.
+///
+/// Each invalidation starts a new epoch, and a value is only inserted if it was read
+/// from storage in the current epoch, so a concurrent invalidation is never undone.
+///
+pub(crate) struct Cache<K: Hash + Eq, V: Clone> {
+    /// The cached entries, or `None` if the cache is disabled, along with the current epoch.
+    entries: Mutex<(Option<LruCache<K, V>>, u64)>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<K: Hash + Eq, V: Clone> Cache<K, V> {
+    /// Initializes a new cache with the given capacity.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new((Self::lru(capacity), 0)),
+            hits: Default::default(),
+            misses: Default::default(),
+        }
+    }
+
+    /// Returns an empty LRU cache with the given capacity, or `None` if the capacity is 0.
+    fn lru(capacity: usize) -> Option<LruCache<K, V>> {
+        match capacity {
+            0 => None,
+            capacity => Some(LruCache::new(capacity)),
+        }
+    }
+
+    /// Returns the current epoch of the cache, which is to be read before the value it caches.
+    pub(crate) fn epoch(&self) -> u64 {
+        self.entries.lock().1
+    }
+
+    /// Returns a copy of the value of the given key, if it is cached.
+    pub(crate) fn get(&self, key: &K) -> Option<V> {
+        let value = self.entries.lock().0.as_mut().and_then(|entries| entries.get(key).cloned());
+        match value.is_some() {
+            true => self.hits.fetch_add(1, Ordering::Relaxed),
+            false => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        value
+    }
+
+    /// Caches the given value of the given key, if it was read in the given epoch.
+    pub(crate) fn insert(&self, epoch: u64, key: K, value: V) {
+        let mut entries = self.entries.lock();
+        if entries.1 == epoch {
+            if let Some(entries) = &mut entries.0 {
+                entries.put(key, value);
+            }
+        }
+    }
+
+    /// Returns the value of the given key from the cache, or reads and caches it if it is missing.
+    pub(crate) fn get_or_read(&self, key: K, read: impl FnOnce() -> Result<V>) -> Result<V> {
+        let epoch = self.epoch();
+        match self.get(&key) {
+            Some(value) => Ok(value),
+            None => {
+                let value = read()?;
+                self.insert(epoch, key, value.clone());
+                Ok(value)
+            }
+        }
+    }
+
+    /// Removes the value of the given key from the cache, and starts a new epoch.
+    pub(crate) fn remove(&self, key: &K) {
+        let mut entries = self.entries.lock();
+        if let Some(entries) = &mut entries.0 {
+            entries.pop(key);
+        }
+        entries.1 += 1;
+    }
+
+    /// Removes every cached value, and starts a new epoch.
+    pub(crate) fn clear(&self) {
+        let mut entries = self.entries.lock();
+        if let Some(entries) = &mut entries.0 {
+            entries.clear();
+        }
+        entries.1 += 1;
+    }
+
+    /// Replaces the cache with an empty one of the given capacity, and starts a new epoch.
+    pub(crate) fn resize(&self, capacity: usize) {
+        let mut entries = self.entries.lock();
+        entries.0 = Self::lru(capacity);
+        entries.1 += 1;
+    }
+
+    /// Returns the number of hits and misses of the cache.
+    pub(crate) fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl<V: Clone> Cache<u32, V> {
+    /// Returns the values of the given range of block heights (inclusive) from the cache,
+    /// or reads and caches the entire range if any of them is missing.
+    pub(crate) fn get_or_read_range(
+        &self,
+        start_block_height: u32,
+        end_block_height: u32,
+        read: impl FnOnce() -> Result<Vec<V>>,
+    ) -> Result<Vec<V>> {
+        let epoch = self.epoch();
+
+        // An invalid range is left to be rejected by the read.
+        if start_block_height <= end_block_height {
+            let values = self.entries.lock().0.as_mut().and_then(|entries| {
+                (start_block_height..=end_block_height)
+                    .map(|block_height| entries.get(&block_height).cloned())
+                    .collect::<Option<Vec<_>>>()
+            });
+
+            // The lookups are only hits if the entire range is cached, as it is read otherwise.
+            let num_lookups = (end_block_height - start_block_height) as u64 + 1;
+            match values {
+                Some(values) => {
+                    self.hits.fetch_add(num_lookups, Ordering::Relaxed);
+                    return Ok(values);
+                }
+                None => self.misses.fetch_add(num_lookups, Ordering::Relaxed),
+            };
+        }
+
+        let values = read()?;
+        for (block_height, value) in (start_block_height..).zip(&values) {
+            self.insert(epoch, block_height, value.clone());
+        }
+        Ok(values)
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> fmt::Debug for Cache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cache").field("stats", &self.stats()).finish()
+    }
+}
+
+///
+/// The caches of recent blocks in the ledger, keyed by block height.
+///
+#[derive(Debug)]
+pub(crate) struct LedgerCaches<N: Network> {
+    pub(crate) blocks: Cache<u32, Block<N>>,
+    pub(crate) block_headers: Cache<u32, BlockHeader<N>>,
+    pub(crate) serialized_blocks: Cache<u32, Bytes>,
+}
+
+impl<N: Network> LedgerCaches<N> {
+    /// Initializes the caches with the given capacities.
+    pub(crate) fn new(config: LedgerCacheConfig) -> Self {
+        Self {
+            blocks: Cache::new(config.blocks),
+            block_headers: Cache::new(config.block_headers),
+            serialized_blocks: Cache::new(config.serialized_blocks),
+        }
+    }
+
+    /// Replaces the caches with empty ones of the given capacities.
+    pub(crate) fn resize(&self, config: LedgerCacheConfig) {
+        self.blocks.resize(config.blocks);
+        self.block_headers.resize(config.block_headers);
+        self.serialized_blocks.resize(config.serialized_blocks);
+    }
+
+    /// Removes the block of the given block height, as its transactions have been pruned.
+    pub(crate) fn remove_block(&self, block_height: u32) {
+        self.blocks.remove(&block_height);
+        self.serialized_blocks.remove(&block_height);
+    }
+
+    /// Removes every cached block, as the blocks at their heights may have changed.
+    pub(crate) fn clear(&self) {
+        self.blocks.clear();
+        self.block_headers.clear();
+        self.serialized_blocks.clear();
+    }
+
+    /// Returns the statistics of the caches.
+    pub(crate) fn stats(&self) -> LedgerCacheStats {
+        LedgerCacheStats {
+            blocks: self.blocks.stats(),
+            block_headers: self.block_headers.stats(),
+            serialized_blocks: self.serialized_blocks.stats(),
+        }
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::storage::{DataMap, Map, MapId, Storage};
+use snarkvm::dpc::prelude::*;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, str::FromStr};
+
+///
+/// A key the record indexer uses to trial-decrypt the record ciphertexts in each block.
+///
+/// A view key is sufficient to index the owned records, while a private key
+/// additionally allows the indexer to identify the transactions that spend them.
+///
+#[derive(Clone)]
+pub enum IndexerKey<N: Network> {
+    ViewKey(ViewKey<N>),
+    PrivateKey(PrivateKey<N>),
+}
+
+impl<N: Network> IndexerKey<N> {
+    /// Returns the view key of the indexer key.
+    pub fn view_key(&self) -> ViewKey<N> {
+        match self {
+            Self::ViewKey(view_key) => view_key.clone(),
+            Self::PrivateKey(private_key) => ViewKey::from_private_key(private_key),
+        }
+    }
+
+    /// Returns the address of the indexer key.
+    pub fn address(&self) -> Address<N> {
+        Address::from_view_key(&self.view_key())
+    }
+
+    /// Returns the compute key of the indexer key, if it is a private key.
+    fn compute_key(&self) -> Option<ComputeKey<N>> {
+        match self {
+            Self::ViewKey(_) => None,
+            Self::PrivateKey(private_key) => Some(private_key.to_compute_key()),
+        }
+    }
+}
+
+impl<N: Network> FromStr for IndexerKey<N> {
+    type Err = anyhow::Error;
+
+    fn from_str(key: &str) -> Result<Self, Self::Err> {
+        if let Ok(private_key) = PrivateKey::from_str(key) {
+            Ok(Self::PrivateKey(private_key))
+        } else if let Ok(view_key) = ViewKey::from_str(key) {
+            Ok(Self::ViewKey(view_key))
+        } else {
+            Err(anyhow!("Invalid indexer key, expected a private key or a view key"))
+        }
+    }
+}
+
+impl<N: Network> fmt::Debug for IndexerKey<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Avoid leaking the key material into the logs.
+        write!(f, "IndexerKey({})", self.address())
+    }
+}
+
+///
+/// A record owned by one of the indexed addresses.
+///
+/// *Attention*: This data structure is intended for usage in storage only.
+/// Modifications to its layout will impact how indexed records are represented in storage.
+///
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct IndexedRecord<N: Network> {
+    /// The block height the record was created at.
+    pub block_height: u32,
+    /// The ID of the transaction that created the record.
+    pub transaction_id: N::TransactionID,
+    /// The decrypted record.
+    pub record: Record<N>,
+    /// The serial number of the record, if it is known to the indexer.
+    pub serial_number: Option<N::SerialNumber>,
+}
+
+#[derive(Clone, Debug)]
+#[allow(clippy::type_complexity)]
+pub(crate) struct IndexerState<N: Network> {
+    /// The keys to trial-decrypt the record ciphertexts with.
+    keys: Vec<IndexerKey<N>>,
+    /// The latest block height that has been indexed for each address.
+    addresses: DataMap<Address<N>, u32>,
+    /// The records owned by the indexed addresses.
+    records: DataMap<N::Commitment, (Address<N>, IndexedRecord<N>)>,
+    /// The serial numbers of the owned records, mapped to their commitments.
+    serial_numbers: DataMap<N::SerialNumber, N::Commitment>,
+    /// The block height and transaction ID that spent each owned record.
+    spends: DataMap<N::Commitment, (u32, N::TransactionID)>,
+}
+
+impl<N: Network> IndexerState<N> {
+    /// Initializes a new instance of `IndexerState`.
+    pub(crate) fn open<S: Storage>(storage: &S) -> Result<Self> {
+        Ok(Self {
+            keys: Vec::new(),
+            addresses: storage.open_map(MapId::IndexedAddresses)?,
+            records: storage.open_map(MapId::IndexedRecords)?,
+            serial_numbers: storage.open_map(MapId::IndexedSerialNumbers)?,
+            spends: storage.open_map(MapId::IndexedSpends)?,
+        })
+    }
+
+    /// Sets the keys to trial-decrypt the record ciphertexts with.
+    pub(crate) fn set_keys(&mut self, keys: Vec<IndexerKey<N>>) {
+        self.keys = keys;
+    }
+
+    /// Returns the keys to trial-decrypt the record ciphertexts with.
+    pub(crate) fn keys(&self) -> &[IndexerKey<N>] {
+        &self.keys
+    }
+
+    /// Returns the latest block height that has been indexed for the given address, if any.
+    pub(crate) fn get_indexed_block_height(&self, address: &Address<N>) -> Result<Option<u32>> {
+        self.addresses.get(address)
+    }
+
+    /// Returns the records owned by the given address.
+    pub(crate) fn get_records(&self, address: &Address<N>) -> Vec<IndexedRecord<N>> {
+        self.records
+            .values()
+            .filter(|(owner, _)| owner == address)
+            .map(|(_, record)| record)
+            .collect()
+    }
+
+    /// Returns the block height and transaction ID that spent the given record, if it has been spent.
+    pub(crate) fn get_spend(&self, commitment: &N::Commitment) -> Result<Option<(u32, N::TransactionID)>> {
+        self.spends.get(commitment)
+    }
+
+    /// Indexes the given block for the given keys.
+    pub(crate) fn add_block(&self, block: &Block<N>, keys: &[IndexerKey<N>], batch: Option<usize>) -> Result<()> {
+        // The serial numbers of the records found in this block, as they are not in storage until the batch is executed.
+        let mut new_serial_numbers = HashMap::new();
+
+        for transaction in block.transactions().iter() {
+            let transaction_id = transaction.transaction_id();
+
+            // Record the spends of any owned records.
+            for serial_number in transaction.serial_numbers() {
+                let commitment = match new_serial_numbers.get(serial_number) {
+                    Some(commitment) => Some(*commitment),
+                    None => self.serial_numbers.get(serial_number)?,
+                };
+                if let Some(commitment) = commitment {
+                    self.spends.insert(&commitment, &(block.height(), transaction_id), batch)?;
+                }
+            }
+
+            // Trial-decrypt the record ciphertexts with each key.
+            for key in keys {
+                let address = key.address();
+                let compute_key = key.compute_key();
+
+                for record in transaction.to_decrypted_records(&key.view_key().into()) {
+                    if record.owner() != address || record.is_dummy() {
+                        continue;
+                    }
+
+                    let commitment = record.commitment();
+                    let serial_number = match &compute_key {
+                        Some(compute_key) => Some(record.to_serial_number(compute_key)?),
+                        None => None,
+                    };
+
+                    // Insert the serial number.
+                    if let Some(serial_number) = serial_number {
+                        self.serial_numbers.insert(&serial_number, &commitment, batch)?;
+                        new_serial_numbers.insert(serial_number, commitment);
+                    }
+                    // Insert the record.
+                    let indexed_record = IndexedRecord {
+                        block_height: block.height(),
+                        transaction_id,
+                        record,
+                        serial_number,
+                    };
+                    self.records.insert(&commitment, &(address, indexed_record), batch)?;
+                }
+            }
+        }
+
+        // Update the indexed block height of each address.
+        for key in keys {
+            self.addresses.insert(&key.address(), &block.height(), batch)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the given block from the index.
+    pub(crate) fn remove_block(&self, block: &Block<N>, batch: Option<usize>) -> Result<()> {
+        for transaction in block.transactions().iter() {
+            // Remove the spends of any owned records.
+            for serial_number in transaction.serial_numbers() {
+                if let Some(commitment) = self.serial_numbers.get(serial_number)? {
+                    self.spends.remove(&commitment, batch)?;
+                }
+            }
+
+            // Remove the owned records.
+            for commitment in transaction.commitments() {
+                if let Some((_, indexed_record)) = self.records.get(commitment)? {
+                    if let Some(serial_number) = indexed_record.serial_number {
+                        self.serial_numbers.remove(&serial_number, batch)?;
+                    }
+                    self.records.remove(commitment, batch)?;
+                }
+            }
+        }
+
+        // Update the indexed block height of each address.
+        let previous_block_height = block.height().saturating_sub(1);
+        for (address, block_height) in self.addresses.iter() {
+            if block_height > previous_block_height {
+                self.addresses.insert(&address, &previous_block_height, batch)?;
+            }
+        }
+
+        Ok(())
+    }
+}
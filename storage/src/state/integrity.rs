@@ -0,0 +1,153 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkvm::dpc::prelude::*;
+
+use std::fmt;
+
+///
+/// An inconsistency found between the maps of the ledger storage.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LedgerIssue<N: Network> {
+    /// The block height is missing from the block heights map.
+    MissingBlockHash(u32),
+    /// The block hash of the block height is missing from the block headers map.
+    MissingBlockHeader(u32, N::BlockHash),
+    /// The block header of the block hash contains a different block height.
+    MismatchedBlockHeight(u32, N::BlockHash, u32),
+    /// The block header of the block height contains a different ledger root than the recomputed ledger tree.
+    MismatchedLedgerRoot(u32, N::LedgerRoot, N::LedgerRoot),
+    /// The ledger root of the block height is missing from the ledger roots map.
+    MissingLedgerRoot(u32, N::LedgerRoot),
+    /// The block hash of the block height is missing from the block transactions map.
+    MissingBlockTransactions(u32, N::BlockHash),
+    /// The transaction ID of the block hash is missing from the transactions map.
+    MissingTransaction(N::BlockHash, N::TransactionID),
+    /// The metadata of the transaction ID refers to a different block or transaction index.
+    MismatchedTransactionMetadata(N::TransactionID),
+    /// The transition ID of the transaction ID is missing from the transitions map.
+    MissingTransition(N::TransactionID, N::TransitionID),
+    /// The transition ID refers to a different transaction ID or transition index.
+    MismatchedTransition(N::TransitionID),
+    /// The serial number of the transition ID is missing from the serial numbers map.
+    MissingSerialNumber(N::TransitionID, N::SerialNumber),
+    /// The commitment of the transition ID is missing from the commitments map.
+    MissingCommitment(N::TransitionID, N::Commitment),
+    /// The block height is not part of the canonical chain.
+    OrphanedBlockHeight(u32),
+    /// The block header is not part of the canonical chain.
+    OrphanedBlockHeader(N::BlockHash),
+    /// The block transactions are not part of the canonical chain.
+    OrphanedBlockTransactions(N::BlockHash),
+    /// The transaction is not part of the canonical chain.
+    OrphanedTransaction(N::TransactionID),
+    /// The transition is not part of the canonical chain.
+    OrphanedTransition(N::TransitionID),
+    /// The serial number is not part of the canonical chain.
+    OrphanedSerialNumber(N::SerialNumber),
+    /// The commitment is not part of the canonical chain.
+    OrphanedCommitment(N::Commitment),
+    /// The ledger root is not part of the canonical chain.
+    OrphanedLedgerRoot(N::LedgerRoot, u32),
+}
+
+impl<N: Network> LedgerIssue<N> {
+    /// Returns `true` if the issue can be repaired without removing blocks from the canonical chain.
+    pub fn is_repairable(&self) -> bool {
+        !matches!(
+            self,
+            Self::MissingBlockHash(..)
+                | Self::MissingBlockHeader(..)
+                | Self::MismatchedBlockHeight(..)
+                | Self::MismatchedLedgerRoot(..)
+                | Self::MissingBlockTransactions(..)
+                | Self::MissingTransaction(..)
+                | Self::MissingTransition(..)
+        )
+    }
+}
+
+impl<N: Network> fmt::Display for LedgerIssue<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingBlockHash(block_height) => write!(f, "Block {} is missing from the block heights map", block_height),
+            Self::MissingBlockHeader(block_height, block_hash) => {
+                write!(f, "Block {} ({}) is missing from the block headers map", block_height, block_hash)
+            }
+            Self::MismatchedBlockHeight(block_height, block_hash, found) => {
+                write!(f, "Block {} ({}) has a block header for block {}", block_height, block_hash, found)
+            }
+            Self::MismatchedLedgerRoot(block_height, expected, found) => {
+                write!(f, "Block {} has ledger root {}, expected {}", block_height, found, expected)
+            }
+            Self::MissingLedgerRoot(block_height, ledger_root) => {
+                write!(f, "Ledger root {} for block {} is missing from the ledger roots map", ledger_root, block_height)
+            }
+            Self::MissingBlockTransactions(block_height, block_hash) => {
+                write!(f, "Block {} ({}) is missing from the block transactions map", block_height, block_hash)
+            }
+            Self::MissingTransaction(block_hash, transaction_id) => {
+                write!(f, "Transaction {} in block {} is missing from the transactions map", transaction_id, block_hash)
+            }
+            Self::MismatchedTransactionMetadata(transaction_id) => write!(f, "Transaction {} has mismatched metadata", transaction_id),
+            Self::MissingTransition(transaction_id, transition_id) => {
+                write!(f, "Transition {} in transaction {} is missing from the transitions map", transition_id, transaction_id)
+            }
+            Self::MismatchedTransition(transition_id) => {
+                write!(f, "Transition {} refers to a mismatched transaction or index", transition_id)
+            }
+            Self::MissingSerialNumber(transition_id, serial_number) => {
+                write!(f, "Serial number {} in transition {} is missing from the serial numbers map", serial_number, transition_id)
+            }
+            Self::MissingCommitment(transition_id, commitment) => {
+                write!(f, "Commitment {} in transition {} is missing from the commitments map", commitment, transition_id)
+            }
+            Self::OrphanedBlockHeight(block_height) => write!(f, "Block {} is not part of the canonical chain", block_height),
+            Self::OrphanedBlockHeader(block_hash) => write!(f, "Block header {} is not part of the canonical chain", block_hash),
+            Self::OrphanedBlockTransactions(block_hash) => {
+                write!(f, "Block transactions {} are not part of the canonical chain", block_hash)
+            }
+            Self::OrphanedTransaction(transaction_id) => write!(f, "Transaction {} is not part of the canonical chain", transaction_id),
+            Self::OrphanedTransition(transition_id) => write!(f, "Transition {} is not part of the canonical chain", transition_id),
+            Self::OrphanedSerialNumber(serial_number) => write!(f, "Serial number {} is not part of the canonical chain", serial_number),
+            Self::OrphanedCommitment(commitment) => write!(f, "Commitment {} is not part of the canonical chain", commitment),
+            Self::OrphanedLedgerRoot(ledger_root, block_height) => {
+                write!(f, "Ledger root {} for block {} is not part of the canonical chain", ledger_root, block_height)
+            }
+        }
+    }
+}
+
+///
+/// The outcome of verifying the integrity of the ledger storage.
+///
+#[derive(Clone, Debug)]
+pub struct LedgerReport<N: Network> {
+    /// The latest block height of the intact canonical chain, if any.
+    pub latest_block_height: Option<u32>,
+    /// The inconsistencies found in the ledger storage.
+    pub issues: Vec<LedgerIssue<N>>,
+    /// Indicates whether the inconsistencies were repaired.
+    pub repaired: bool,
+}
+
+impl<N: Network> LedgerReport<N> {
+    /// Returns `true` if no inconsistencies were found.
+    pub fn is_consistent(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
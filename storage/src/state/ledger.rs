@@ -18,26 +18,36 @@
 use crate::storage::rocksdb::RocksDB;
 use crate::{
     helpers::BlockLocators,
-    storage::{DataMap, Map, MapId, Storage},
+    state::{
+        cache::{LedgerCacheConfig, LedgerCacheStats, LedgerCaches},
+        indexer::{IndexedRecord, IndexerKey, IndexerState},
+        integrity::{LedgerIssue, LedgerReport},
+    },
+    storage::{BackupInfo, DataMap, Map, MapId, Snapshot, Storage, StorageStats},
 };
 use snarkos_environment::helpers::Resource;
 use snarkvm::dpc::prelude::*;
 
 use anyhow::{anyhow, Result};
+use bytes::Bytes;
 use circular_queue::CircularQueue;
 use itertools::Itertools;
 use parking_lot::RwLock;
 use rand::{CryptoRng, Rng};
 use rayon::prelude::*;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, HashSet},
+    borrow::Borrow,
+    collections::{BTreeMap, HashMap, HashSet},
     path::Path,
     sync::{atomic::AtomicBool, Arc},
     thread,
 };
 use time::OffsetDateTime;
-use tokio::sync::oneshot::{self, error::TryRecvError};
+use tokio::sync::{
+    oneshot::{self, error::TryRecvError},
+    watch,
+};
 
 /// The maximum number of linear block locators.
 pub const MAXIMUM_LINEAR_BLOCK_LOCATORS: u32 = 64;
@@ -72,6 +82,10 @@ impl<N: Network> Metadata<N> {
     }
 }
 
+/// The parameters a coinbase transaction is created for, as
+/// (previous block hash, block height, recipient, coinbase reward, is_public).
+type CoinbaseCacheKey<N> = (<N as Network>::BlockHash, u32, Address<N>, AleoAmount, bool);
+
 #[derive(Debug)]
 pub struct LedgerState<N: Network> {
     /// The current ledger tree of block hashes.
@@ -82,15 +96,23 @@ pub struct LedgerState<N: Network> {
     latest_block_hashes_and_headers: RwLock<CircularQueue<(N::BlockHash, BlockHeader<N>)>>,
     /// The block locators from the latest block of the ledger.
     latest_block_locators: RwLock<BlockLocators<N>>,
+    /// The notifier of changes to the latest block of the ledger := (block_height, block_hash).
+    latest_block_notifier: watch::Sender<(u32, N::BlockHash)>,
+    /// The caches of recent blocks, block headers and serialized blocks.
+    caches: LedgerCaches<N>,
     /// The ledger root corresponding to each block height.
     ledger_roots: DataMap<N::LedgerRoot, u32>,
     /// The blocks of the ledger in storage.
     blocks: BlockState<N>,
+    /// The records owned by the indexed addresses.
+    indexer: IndexerState<N>,
     /// The indicator bit and tracker for a ledger in read-only mode.
     /// Used to ensure the database operations aren't interrupted by a shutdown.
     map_lock: Arc<RwLock<()>>,
-    /// Coinbase cache.
-    coinbase_cache: RwLock<(Option<Transaction<N>>, Option<Record<N>>)>,
+    /// The coinbase transaction and record of the latest block template, with the parameters it was created for.
+    coinbase_cache: RwLock<Option<(CoinbaseCacheKey<N>, Transaction<N>, Record<N>)>>,
+    /// The number of most recent blocks to retain the transaction bodies for, if pruning is enabled.
+    prune_depth: Option<u32>,
     read_only: (bool, RwLock<Block<N>>),
 }
 
@@ -106,6 +128,57 @@ impl<N: Network> LedgerState<N> {
         Self::open_writer_with_increment::<S, P>(path, 100_000)
     }
 
+    ///
+    /// Opens a new writable instance of `LedgerState` from the given storage path,
+    /// which discards the transaction bodies of blocks older than the given prune depth,
+    /// and indexes the records owned by the given indexer keys.
+    ///
+    /// Block headers, block hashes, ledger roots, serial numbers and commitments are
+    /// retained for all blocks, as they are required for validation and the ledger tree.
+    ///
+    pub fn open_writer_with_options<S: Storage, P: AsRef<Path>>(
+        path: P,
+        prune_depth: Option<u32>,
+        indexer_keys: Vec<IndexerKey<N>>,
+    ) -> Result<Self> {
+        // Ensure the prune depth does not interfere with the ability to revert to a fork.
+        if let Some(prune_depth) = prune_depth {
+            if prune_depth < N::ALEO_MAXIMUM_FORK_DEPTH {
+                return Err(anyhow!("The prune depth must be at least {} blocks", N::ALEO_MAXIMUM_FORK_DEPTH));
+            }
+        }
+
+        let mut ledger = Self::open_writer::<S, P>(path)?;
+        ledger.prune_depth = prune_depth;
+        ledger.indexer.set_keys(indexer_keys);
+
+        // Index the blocks that were added since each key was last indexed, before their transactions may be pruned.
+        ledger.backfill_indexer()?;
+
+        // Discard the transaction bodies that have fallen outside of the prune depth since the last run.
+        if let Some(prune_depth) = prune_depth {
+            let target_block_height = ledger.latest_block_height().saturating_sub(prune_depth);
+            let mut pruned_block_height = ledger.blocks.get_pruned_block_height()?.unwrap_or(0);
+
+            while pruned_block_height < target_block_height {
+                // Compute the end block height (inclusive) for this iteration, to bound the size of each batch.
+                let end_block_height = std::cmp::min(pruned_block_height.saturating_add(1_000), target_block_height);
+
+                // Perform all the associated storage operations as an atomic batch.
+                let batch = ledger.ledger_roots.prepare_batch();
+                ledger.blocks.prune_to_block_height(end_block_height, Some(batch))?;
+                ledger.ledger_roots.execute_batch(batch)?;
+
+                debug!("Pruned the ledger up to block {}", end_block_height);
+                pruned_block_height = end_block_height;
+            }
+
+            info!("Ledger is pruning transactions older than {} blocks", prune_depth);
+        }
+
+        Ok(ledger)
+    }
+
     /// This function is hidden, as it's intended to be used directly in tests only.
     /// The `validation_increment` parameter determines the number of blocks to be
     /// handled during the incremental validation process.
@@ -122,10 +195,14 @@ impl<N: Network> LedgerState<N> {
             latest_block: RwLock::new(N::genesis_block().clone()),
             latest_block_hashes_and_headers: RwLock::new(CircularQueue::with_capacity(MAXIMUM_LINEAR_BLOCK_LOCATORS as usize)),
             latest_block_locators: Default::default(),
+            latest_block_notifier: watch::channel((0, N::genesis_block().hash())).0,
+            caches: LedgerCaches::new(Default::default()),
             ledger_roots: storage.open_map(MapId::LedgerRoots)?,
+            indexer: IndexerState::open(&storage)?,
             blocks: BlockState::open(storage)?,
             map_lock: Default::default(),
-            coinbase_cache: RwLock::new((None, None)),
+            coinbase_cache: Default::default(),
+            prune_depth: None,
             read_only: (is_read_only, RwLock::new(N::genesis_block().clone())),
         };
 
@@ -239,6 +316,8 @@ impl<N: Network> LedgerState<N> {
         ledger.regenerate_ledger_tree()?;
         assert_eq!(ledger.ledger_tree.read().root(), latest_ledger_root);
 
+        ledger.notify_latest_block();
+
         info!("Ledger successfully loaded at block {}", ledger.latest_block_height());
         Ok(ledger)
     }
@@ -262,10 +341,14 @@ impl<N: Network> LedgerState<N> {
             latest_block: RwLock::new(N::genesis_block().clone()),
             latest_block_hashes_and_headers: RwLock::new(CircularQueue::with_capacity(MAXIMUM_LINEAR_BLOCK_LOCATORS as usize)),
             latest_block_locators: Default::default(),
+            latest_block_notifier: watch::channel((0, N::genesis_block().hash())).0,
+            caches: LedgerCaches::new(Default::default()),
             ledger_roots: storage.open_map(MapId::LedgerRoots)?,
+            indexer: IndexerState::open(&storage)?,
             blocks: BlockState::open(storage)?,
             map_lock: Default::default(),
-            coinbase_cache: RwLock::new((None, None)),
+            coinbase_cache: Default::default(),
+            prune_depth: None,
             read_only: (is_read_only, RwLock::new(N::genesis_block().clone())),
         });
 
@@ -305,12 +388,313 @@ impl<N: Network> LedgerState<N> {
         ledger.regenerate_latest_ledger_state()?;
         // Update the ledger tree state.
         ledger.regenerate_ledger_tree()?;
+        ledger.notify_latest_block();
         // As the ledger is in read-only mode, proceed to start a process to keep the reader in sync.
         let resource = ledger.initialize_reader_heartbeat(latest_block)?;
         trace!("[Read-Only] Ledger successfully loaded at block {}", ledger.latest_block_height());
         Ok((ledger, resource))
     }
 
+    ///
+    /// Verifies the integrity of the ledger at the given storage path, without modifying it
+    /// unless `repair` is `true`. This walks every map of the ledger, checks their cross-references,
+    /// and recomputes the ledger tree root at every block height.
+    ///
+    /// If `repair` is `true`, inconsistent index entries are rewritten, orphaned entries are removed,
+    /// and the canonical chain is truncated to the last block height that is intact.
+    ///
+    pub fn verify<S: Storage, P: AsRef<Path>>(path: P, repair: bool) -> Result<LedgerReport<N>> {
+        let ledger = Self::open_unchecked::<S, P>(path, !repair)?;
+        let blocks = &ledger.blocks;
+        let transactions = &blocks.transactions;
+
+        let pruned_block_height = blocks.get_pruned_block_height()?;
+        let mut issues = Vec::new();
+
+        // Perform all repairs as an atomic batch, so that an interrupted repair leaves the ledger untouched.
+        let batch = match repair {
+            true => Some(ledger.ledger_roots.prepare_batch()),
+            false => None,
+        };
+
+        // The serial numbers and commitments of canonical blocks that are rewritten by a repair.
+        let mut repaired_serial_numbers = HashSet::new();
+        let mut repaired_commitments = HashSet::new();
+
+        // The canonical entries that are expected to exist in each map.
+        let mut canonical_block_hashes = HashSet::new();
+        let mut canonical_transaction_ids = HashSet::new();
+        let mut canonical_transition_ids = HashSet::new();
+        let mut canonical_ledger_roots = HashMap::new();
+
+        // Walk the canonical chain from genesis, recomputing the ledger tree at every block height.
+        let mut ledger_tree = LedgerTree::<N>::new()?;
+        let mut latest_block_height = None;
+        let maximum_block_height = blocks.block_heights.keys().max().unwrap_or(0);
+        for block_height in 0..=maximum_block_height {
+            let issues_before_block = issues.len();
+
+            // Check the block hash and block header.
+            let block_hash = match blocks.block_heights.get(&block_height)? {
+                Some(block_hash) => block_hash,
+                None => {
+                    issues.push(LedgerIssue::MissingBlockHash(block_height));
+                    break;
+                }
+            };
+            let block_header = match blocks.block_headers.get(&block_hash)? {
+                Some(block_header) => block_header,
+                None => {
+                    issues.push(LedgerIssue::MissingBlockHeader(block_height, block_hash));
+                    break;
+                }
+            };
+            if block_header.height() != block_height {
+                issues.push(LedgerIssue::MismatchedBlockHeight(block_height, block_hash, block_header.height()));
+                break;
+            }
+
+            // Check the ledger root against the recomputed ledger tree.
+            let ledger_root = ledger_tree.root();
+            if block_header.previous_ledger_root() != ledger_root {
+                issues.push(LedgerIssue::MismatchedLedgerRoot(block_height, ledger_root, block_header.previous_ledger_root()));
+                break;
+            }
+
+            // Check the block transactions.
+            let transaction_ids = match blocks.block_transactions.get(&block_hash)? {
+                Some(transaction_ids) => transaction_ids,
+                None => {
+                    issues.push(LedgerIssue::MissingBlockTransactions(block_height, block_hash));
+                    break;
+                }
+            };
+
+            // The genesis block is never pruned.
+            let is_pruned = block_height > 0 && pruned_block_height.map_or(false, |pruned_height| block_height <= pruned_height);
+            let mut transition_ids = Vec::new();
+            let mut serial_numbers = Vec::new();
+            let mut commitments = Vec::new();
+            let mut is_intact = true;
+            // The transactions and transitions of a pruned block are expected to be missing.
+            let unpruned_transaction_ids = match is_pruned {
+                true => &[][..],
+                false => &transaction_ids[..],
+            };
+            for (index, transaction_id) in unpruned_transaction_ids.iter().enumerate() {
+                // Check the transaction and its metadata.
+                let (ledger_root, transaction_transition_ids, metadata) = match transactions.transactions.get(transaction_id)? {
+                    Some(transaction) => transaction,
+                    None => {
+                        issues.push(LedgerIssue::MissingTransaction(block_hash, *transaction_id));
+                        is_intact = false;
+                        continue;
+                    }
+                };
+                let expected_metadata = Metadata::<N>::new(block_height, block_hash, block_header.timestamp(), index as u16);
+                if metadata != expected_metadata {
+                    issues.push(LedgerIssue::MismatchedTransactionMetadata(*transaction_id));
+                    if let Some(batch) = batch {
+                        let transaction = (ledger_root, transaction_transition_ids.clone(), expected_metadata);
+                        transactions.transactions.insert(transaction_id, &transaction, Some(batch))?;
+                    }
+                }
+
+                for (index, transition_id) in transaction_transition_ids.iter().enumerate() {
+                    transition_ids.push(*transition_id);
+
+                    // Check the transition.
+                    let (candidate_transaction_id, candidate_index, transition) = match transactions.transitions.get(transition_id)? {
+                        Some(transition) => transition,
+                        None => {
+                            issues.push(LedgerIssue::MissingTransition(*transaction_id, *transition_id));
+                            is_intact = false;
+                            continue;
+                        }
+                    };
+                    if candidate_transaction_id != *transaction_id || candidate_index as usize != index {
+                        issues.push(LedgerIssue::MismatchedTransition(*transition_id));
+                        if let Some(batch) = batch {
+                            let transition = (*transaction_id, index as u8, transition.clone());
+                            transactions.transitions.insert(transition_id, &transition, Some(batch))?;
+                        }
+                    }
+
+                    // Check the serial numbers and commitments of the transition.
+                    for serial_number in transition.serial_numbers() {
+                        if transactions.serial_numbers.get(serial_number)? != Some(*transition_id) {
+                            issues.push(LedgerIssue::MissingSerialNumber(*transition_id, *serial_number));
+                            if let Some(batch) = batch {
+                                transactions.serial_numbers.insert(serial_number, transition_id, Some(batch))?;
+                                serial_numbers.push(*serial_number);
+                            }
+                        }
+                    }
+                    for commitment in transition.commitments() {
+                        if transactions.commitments.get(commitment)? != Some(*transition_id) {
+                            issues.push(LedgerIssue::MissingCommitment(*transition_id, *commitment));
+                            if let Some(batch) = batch {
+                                transactions.commitments.insert(commitment, transition_id, Some(batch))?;
+                                commitments.push(*commitment);
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Stop at the first block that cannot be reconstructed from storage.
+            if !is_intact {
+                break;
+            }
+
+            // Check the ledger root entry.
+            if ledger.ledger_roots.get(&ledger_root)? != Some(block_height) {
+                issues.push(LedgerIssue::MissingLedgerRoot(block_height, ledger_root));
+                if let Some(batch) = batch {
+                    ledger.ledger_roots.insert(&ledger_root, &block_height, Some(batch))?;
+                }
+            }
+
+            // Mark the block as canonical.
+            ledger_tree.add(&block_hash)?;
+            canonical_block_hashes.insert(block_hash);
+            canonical_transaction_ids.extend(transaction_ids);
+            canonical_transition_ids.extend(transition_ids);
+            repaired_serial_numbers.extend(serial_numbers);
+            repaired_commitments.extend(commitments);
+            canonical_ledger_roots.insert(ledger_root, block_height);
+            latest_block_height = Some(block_height);
+
+            if issues.len() > issues_before_block {
+                debug!("Found {} issues in block {}", issues.len() - issues_before_block, block_height);
+            }
+        }
+
+        // Check for orphaned entries in each map.
+        for block_height in blocks.block_heights.keys() {
+            if latest_block_height.map_or(true, |latest_block_height| block_height > latest_block_height) {
+                issues.push(LedgerIssue::OrphanedBlockHeight(block_height));
+                if let Some(batch) = batch {
+                    blocks.block_heights.remove(&block_height, Some(batch))?;
+                }
+            }
+        }
+        for block_hash in blocks.block_headers.keys() {
+            if !canonical_block_hashes.contains(&block_hash) {
+                issues.push(LedgerIssue::OrphanedBlockHeader(block_hash));
+                if let Some(batch) = batch {
+                    blocks.block_headers.remove(&block_hash, Some(batch))?;
+                }
+            }
+        }
+        for block_hash in blocks.block_transactions.keys() {
+            if !canonical_block_hashes.contains(&block_hash) {
+                issues.push(LedgerIssue::OrphanedBlockTransactions(block_hash));
+                if let Some(batch) = batch {
+                    blocks.block_transactions.remove(&block_hash, Some(batch))?;
+                }
+            }
+        }
+        for transaction_id in transactions.transactions.keys() {
+            if !canonical_transaction_ids.contains(&transaction_id) {
+                issues.push(LedgerIssue::OrphanedTransaction(transaction_id));
+                if let Some(batch) = batch {
+                    transactions.transactions.remove(&transaction_id, Some(batch))?;
+                }
+            }
+        }
+        for transition_id in transactions.transitions.keys() {
+            if !canonical_transition_ids.contains(&transition_id) {
+                issues.push(LedgerIssue::OrphanedTransition(transition_id));
+                if let Some(batch) = batch {
+                    transactions.transitions.remove(&transition_id, Some(batch))?;
+                }
+            }
+        }
+        // The serial numbers and commitments of pruned blocks are retained, while their transitions are not.
+        let is_retained = |transition_id: &N::TransitionID| -> Result<bool> {
+            Ok(pruned_block_height.is_some() && !transactions.transitions.contains_key(transition_id)?)
+        };
+        for (serial_number, transition_id) in transactions.serial_numbers.iter() {
+            // Skip the serial numbers that have been repaired to reference a canonical transition.
+            if repaired_serial_numbers.contains(&serial_number) {
+                continue;
+            }
+            if !canonical_transition_ids.contains(&transition_id) && !is_retained(&transition_id)? {
+                issues.push(LedgerIssue::OrphanedSerialNumber(serial_number));
+                if let Some(batch) = batch {
+                    transactions.serial_numbers.remove(&serial_number, Some(batch))?;
+                }
+            }
+        }
+        for (commitment, transition_id) in transactions.commitments.iter() {
+            // Skip the commitments that have been repaired to reference a canonical transition.
+            if repaired_commitments.contains(&commitment) {
+                continue;
+            }
+            if !canonical_transition_ids.contains(&transition_id) && !is_retained(&transition_id)? {
+                issues.push(LedgerIssue::OrphanedCommitment(commitment));
+                if let Some(batch) = batch {
+                    transactions.commitments.remove(&commitment, Some(batch))?;
+                }
+            }
+        }
+        for (ledger_root, block_height) in ledger.ledger_roots.iter() {
+            if !canonical_ledger_roots.contains_key(&ledger_root) {
+                issues.push(LedgerIssue::OrphanedLedgerRoot(ledger_root, block_height));
+                if let Some(batch) = batch {
+                    ledger.ledger_roots.remove(&ledger_root, Some(batch))?;
+                }
+            }
+        }
+
+        // Execute the pending storage batch, which only holds operations if there are issues to repair.
+        if let Some(batch) = batch {
+            if issues.iter().any(LedgerIssue::is_repairable) {
+                ledger.ledger_roots.execute_batch(batch)?;
+            }
+        }
+
+        Ok(LedgerReport {
+            latest_block_height,
+            issues,
+            repaired: repair,
+        })
+    }
+
+    ///
+    /// Sets the capacities of the caches of recent blocks, which discards their current contents.
+    ///
+    pub fn set_cache_config(&self, config: LedgerCacheConfig) {
+        self.caches.resize(config);
+    }
+
+    /// Returns the hits and misses of the caches of recent blocks.
+    pub fn cache_stats(&self) -> LedgerCacheStats {
+        self.caches.stats()
+    }
+
+    /// Returns the approximate number of keys and size of each map of the ledger storage.
+    pub fn storage_stats(&self) -> Result<StorageStats> {
+        self.ledger_roots.storage_stats()
+    }
+
+    ///
+    /// Creates an incremental backup of the ledger storage in the given directory, while the ledger remains in use,
+    /// and discards all but the given number of most recent backups, if any.
+    ///
+    pub fn backup<P: AsRef<Path>>(&self, path: P, num_backups_to_keep: Option<usize>) -> Result<BackupInfo> {
+        self.ledger_roots.backup(path.as_ref(), num_backups_to_keep)
+    }
+
+    ///
+    /// Returns a receiver that is notified each time the latest block of the ledger changes,
+    /// which allows the holders of a shared ledger to invalidate their caches without polling.
+    ///
+    pub fn subscribe(&self) -> watch::Receiver<(u32, N::BlockHash)> {
+        self.latest_block_notifier.subscribe()
+    }
+
     /// Returns `true` if the ledger is in read-only mode.
     pub fn is_read_only(&self) -> bool {
         self.read_only.0
@@ -371,6 +755,39 @@ impl<N: Network> LedgerState<N> {
         Ok(*ledger_root == self.latest_ledger_root() || self.ledger_roots.contains_key(ledger_root)?)
     }
 
+    /// Returns the prune depth of the ledger, if pruning is enabled.
+    pub fn prune_depth(&self) -> Option<u32> {
+        self.prune_depth
+    }
+
+    /// Returns the highest block height whose transaction bodies have been pruned, if any.
+    pub fn get_pruned_block_height(&self) -> Result<Option<u32>> {
+        self.blocks.get_pruned_block_height()
+    }
+
+    /// Returns `true` if the transaction bodies of the given block height have been pruned.
+    pub fn is_pruned(&self, block_height: u32) -> Result<bool> {
+        self.blocks.is_pruned(block_height)
+    }
+
+    /// Returns the latest block height that has been indexed for the given address, if it is indexed.
+    pub fn get_indexed_block_height(&self, address: &Address<N>) -> Result<Option<u32>> {
+        self.indexer.get_indexed_block_height(address)
+    }
+
+    /// Returns the indexed records owned by the given address.
+    pub fn get_owned_records(&self, address: &Address<N>) -> Result<Vec<IndexedRecord<N>>> {
+        match self.indexer.get_indexed_block_height(address)? {
+            Some(_) => Ok(self.indexer.get_records(address)),
+            None => Err(anyhow!("Address {} is not indexed", address)),
+        }
+    }
+
+    /// Returns the block height and transaction ID that spent the given indexed record, if it has been spent.
+    pub fn get_record_spend(&self, commitment: &N::Commitment) -> Result<Option<(u32, N::TransactionID)>> {
+        self.indexer.get_spend(commitment)
+    }
+
     /// Returns `true` if the given block height exists in storage.
     pub fn contains_block_height(&self, block_height: u32) -> Result<bool> {
         self.blocks.contains_block_height(block_height)
@@ -443,12 +860,16 @@ impl<N: Network> LedgerState<N> {
 
     /// Returns the block header for the given block height.
     pub fn get_block_header(&self, block_height: u32) -> Result<BlockHeader<N>> {
-        self.blocks.get_block_header(block_height)
+        self.caches
+            .block_headers
+            .get_or_read(block_height, || self.blocks.get_block_header(block_height))
     }
 
     /// Returns the block headers from the given `start_block_height` to `end_block_height` (inclusive).
     pub fn get_block_headers(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<BlockHeader<N>>> {
-        self.blocks.get_block_headers(start_block_height, end_block_height)
+        self.caches.block_headers.get_or_read_range(start_block_height, end_block_height, || {
+            self.blocks.get_block_headers(start_block_height, end_block_height)
+        })
     }
 
     /// Returns the transactions from the block of the given block height.
@@ -456,14 +877,30 @@ impl<N: Network> LedgerState<N> {
         self.blocks.get_block_transactions(block_height)
     }
 
-    /// Returns the block for a given block height.
+    /// Returns the block for a given block height, read from a consistent snapshot of storage.
     pub fn get_block(&self, block_height: u32) -> Result<Block<N>> {
-        self.blocks.get_block(block_height)
+        self.caches
+            .blocks
+            .get_or_read(block_height, || self.blocks.snapshot().get_block(block_height))
     }
 
-    /// Returns the blocks from the given `start_block_height` to `end_block_height` (inclusive).
+    /// Returns the blocks from the given `start_block_height` to `end_block_height` (inclusive),
+    /// read from a consistent snapshot of storage.
     pub fn get_blocks(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<Block<N>>> {
-        self.blocks.get_blocks(start_block_height, end_block_height)
+        self.caches.blocks.get_or_read_range(start_block_height, end_block_height, || {
+            self.blocks.snapshot().get_blocks(start_block_height, end_block_height)
+        })
+    }
+
+    /// Returns the serialized blocks from the given `start_block_height` to `end_block_height` (inclusive),
+    /// which may be sent to peers without serializing the blocks again.
+    pub fn get_serialized_blocks(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<Bytes>> {
+        self.caches.serialized_blocks.get_or_read_range(start_block_height, end_block_height, || {
+            self.get_blocks(start_block_height, end_block_height)?
+                .iter()
+                .map(|block| Ok(Bytes::from(bincode::serialize(block)?)))
+                .collect()
+        })
     }
 
     /// Returns the ledger root in the block header of the given block height.
@@ -596,12 +1033,6 @@ impl<N: Network> LedgerState<N> {
         Ok(true)
     }
 
-    pub fn invalidate_coinbase_cache(&self) {
-        let mut w = self.coinbase_cache.write();
-        *w = (None, None);
-        info!("Coinbase cache invalidated");
-    }
-
     /// Returns a block template based on the latest state of the ledger.
     pub fn get_block_template<R: Rng + CryptoRng>(
         &self,
@@ -681,18 +1112,25 @@ impl<N: Network> LedgerState<N> {
         // Calculate the final coinbase reward (including the transaction fees).
         coinbase_reward = coinbase_reward.add(transaction_fees);
 
-        let coinbase: (Transaction<N>, Record<N>);
+        // Reuse the cached coinbase transaction only if it was created for the same parameters.
+        let coinbase_cache_key = (previous_block_hash, block_height, recipient, coinbase_reward, is_public);
+        let cached_coinbase = match &*self.coinbase_cache.read() {
+            Some((key, transaction, record)) if *key == coinbase_cache_key => Some((transaction.clone(), record.clone())),
+            _ => None,
+        };
 
-        if self.coinbase_cache.read().0.is_none() {
-            coinbase = Transaction::<N>::new_coinbase(recipient, coinbase_reward, is_public, rng)?;
-            let mut w = self.coinbase_cache.write();
-            *w = (Some(coinbase.0.clone()), Some(coinbase.1.clone()));
-            info!("Created new coinbase transaction {}", coinbase.0.transaction_id());
-        } else {
-            let cache = self.coinbase_cache.read().clone();
-            coinbase = (cache.0.unwrap(), cache.1.unwrap());
-            info!("Using cached coinbase transaction {}", coinbase.0.transaction_id());
-        }
+        let coinbase = match cached_coinbase {
+            Some(coinbase) => {
+                trace!("Using cached coinbase transaction {}", coinbase.0.transaction_id());
+                coinbase
+            }
+            None => {
+                let coinbase = Transaction::<N>::new_coinbase(recipient, coinbase_reward, is_public, rng)?;
+                *self.coinbase_cache.write() = Some((coinbase_cache_key, coinbase.0.clone(), coinbase.1.clone()));
+                debug!("Created new coinbase transaction {}", coinbase.0.transaction_id());
+                coinbase
+            }
+        };
 
         transactions.push(coinbase.0);
 
@@ -865,9 +1303,25 @@ impl<N: Network> LedgerState<N> {
         self.ledger_roots
             .insert(&block.previous_ledger_root(), &block.height(), Some(batch))?;
 
+        // Index the records owned by the indexer keys.
+        if !self.indexer.keys().is_empty() {
+            self.indexer.add_block(block, self.indexer.keys(), Some(batch))?;
+        }
+
+        // Discard the transaction bodies of the blocks that fall outside of the prune depth.
+        if let Some(prune_depth) = self.prune_depth {
+            self.blocks
+                .prune_to_block_height(block_height.saturating_sub(prune_depth), Some(batch))?;
+        }
+
         // Execute the pending storage batch.
         self.ledger_roots.execute_batch(batch)?;
 
+        // Evict the block that was pruned from the caches.
+        if let Some(prune_depth) = self.prune_depth {
+            self.caches.remove_block(block_height.saturating_sub(prune_depth));
+        }
+
         // Update the in-memory objects.
         self.ledger_tree.write().add(&block.hash())?;
         self.latest_block_hashes_and_headers
@@ -875,6 +1329,7 @@ impl<N: Network> LedgerState<N> {
             .push((block.hash(), block.header().clone()));
         *self.latest_block_locators.write() = self.get_block_locators(block.height())?;
         *self.latest_block.write() = block.clone();
+        self.notify_latest_block();
 
         Ok(())
     }
@@ -916,6 +1371,7 @@ impl<N: Network> LedgerState<N> {
                     // Update the internal storage state of the ledger.
                     self.blocks.remove_block(current_block_height, Some(batch))?;
                     self.ledger_roots.remove(&block.previous_ledger_root(), Some(batch))?;
+                    self.indexer.remove_block(block, Some(batch))?;
                     // Decrement the current block height, and update the current block.
                     current_block_height = current_block_height.saturating_sub(1);
                     current_block = blocks.get(&current_block_height);
@@ -936,12 +1392,16 @@ impl<N: Network> LedgerState<N> {
         // Execute the pending storage batch.
         self.ledger_roots.execute_batch(batch)?;
 
+        // Invalidate the caches, as the reverted block heights may be reused by other blocks.
+        self.caches.clear();
+
         // Update the latest block.
         *self.latest_block.write() = self.get_block(current_block_height)?;
         // Regenerate the latest ledger state.
         self.regenerate_latest_ledger_state()?;
         // Regenerate the ledger tree.
         self.regenerate_ledger_tree()?;
+        self.notify_latest_block();
 
         // Return the removed blocks, in increasing order (i.e. 1, 2, 3...).
         Ok(blocks.values().skip(1).cloned().collect())
@@ -951,25 +1411,31 @@ impl<N: Network> LedgerState<N> {
     /// Returns a ledger proof for the given commitment.
     ///
     pub fn get_ledger_inclusion_proof(&self, commitment: N::Commitment) -> Result<LedgerProof<N>> {
+        // Read from a consistent snapshot of storage, as a concurrent block may be added or reverted.
+        let blocks = self.blocks.snapshot();
+
         // TODO (raychu86): Add getter functions.
-        let commitment_transition_id = match self.blocks.transactions.commitments.get(&commitment)? {
+        let commitment_transition_id = match blocks.read(&self.blocks.transactions.commitments, &commitment)? {
             Some(transition_id) => transition_id,
             None => return Err(anyhow!("commitment {} missing from commitments map", commitment)),
         };
 
-        let transaction_id = match self.blocks.transactions.transitions.get(&commitment_transition_id)? {
+        let transaction_id = match blocks.read(&self.blocks.transactions.transitions, &commitment_transition_id)? {
             Some((transaction_id, _, _)) => transaction_id,
+            None if blocks.get_pruned_block_height()?.is_some() => {
+                return Err(anyhow!("commitment {} may belong to a block that has been pruned", commitment));
+            }
             None => return Err(anyhow!("transition id {} missing from transactions map", commitment_transition_id)),
         };
 
-        let transaction = self.get_transaction(&transaction_id)?;
+        let transaction = blocks.get_transaction(&transaction_id)?;
 
-        let block_hash = match self.blocks.transactions.transactions.get(&transaction_id)? {
+        let block_hash = match blocks.read(&self.blocks.transactions.transactions, &transaction_id)? {
             Some((_, _, metadata)) => metadata.block_hash,
             None => return Err(anyhow!("transaction id {} missing from transactions map", transaction_id)),
         };
 
-        let block_header = match self.blocks.block_headers.get(&block_hash)? {
+        let block_header = match blocks.read(&self.blocks.block_headers, &block_hash)? {
             Some(block_header) => block_header,
             None => return Err(anyhow!("Block {} missing from block headers map", block_hash)),
         };
@@ -978,7 +1444,7 @@ impl<N: Network> LedgerState<N> {
         let local_proof = transaction.to_local_proof(commitment)?;
 
         let transaction_id = local_proof.transaction_id();
-        let transactions = self.get_block_transactions(block_header.height())?;
+        let transactions = blocks.get_block_transactions(block_header.height())?;
 
         // Compute the transactions inclusion proof.
         let transactions_inclusion_proof = {
@@ -992,7 +1458,7 @@ impl<N: Network> LedgerState<N> {
         let block_header_root = block_header.to_header_root()?;
 
         // Determine the previous block hash.
-        let previous_block_hash = self.get_previous_block_hash(self.get_block_height(&block_hash)?)?;
+        let previous_block_hash = blocks.get_previous_block_hash(blocks.get_block_height(&block_hash)?)?;
 
         // Generate the record proof.
         let record_proof = RecordProof::new(
@@ -1006,12 +1472,105 @@ impl<N: Network> LedgerState<N> {
         )?;
 
         // Generate the ledger root inclusion proof.
-        let ledger_root = self.ledger_tree.read().root();
-        let ledger_root_inclusion_proof = self.ledger_tree.read().to_ledger_inclusion_proof(&block_hash)?;
+        let ledger_tree = self.ledger_tree.read();
+        let ledger_root = ledger_tree.root();
+        let ledger_root_inclusion_proof = ledger_tree.to_ledger_inclusion_proof(&block_hash)?;
 
         LedgerProof::new(ledger_root, ledger_root_inclusion_proof, record_proof)
     }
 
+    /// Opens an instance of `LedgerState` from the given storage path, without initializing or validating its state.
+    fn open_unchecked<S: Storage, P: AsRef<Path>>(path: P, is_read_only: bool) -> Result<Self> {
+        // Open storage.
+        let context = N::NETWORK_ID;
+        let storage = S::open(path, context, is_read_only)?;
+
+        Ok(Self {
+            ledger_tree: RwLock::new(LedgerTree::<N>::new()?),
+            latest_block: RwLock::new(N::genesis_block().clone()),
+            latest_block_hashes_and_headers: RwLock::new(CircularQueue::with_capacity(MAXIMUM_LINEAR_BLOCK_LOCATORS as usize)),
+            latest_block_locators: Default::default(),
+            latest_block_notifier: watch::channel((0, N::genesis_block().hash())).0,
+            caches: LedgerCaches::new(Default::default()),
+            ledger_roots: storage.open_map(MapId::LedgerRoots)?,
+            indexer: IndexerState::open(&storage)?,
+            blocks: BlockState::open(storage)?,
+            map_lock: Default::default(),
+            coinbase_cache: Default::default(),
+            prune_depth: None,
+            read_only: (is_read_only, RwLock::new(N::genesis_block().clone())),
+        })
+    }
+
+    /// Indexes the blocks that were added since each indexer key was last indexed.
+    fn backfill_indexer(&self) -> Result<()> {
+        let latest_block_height = self.latest_block_height();
+
+        // Determine the block height to resume indexing from for each key.
+        let mut keys = Vec::with_capacity(self.indexer.keys().len());
+        for key in self.indexer.keys() {
+            let start_block_height = match self.indexer.get_indexed_block_height(&key.address())? {
+                Some(block_height) => block_height.saturating_add(1),
+                None => 0,
+            };
+            keys.push((start_block_height, key.clone()));
+        }
+
+        let start_block_height = match keys.iter().map(|(block_height, _)| *block_height).min() {
+            Some(block_height) if block_height <= latest_block_height => block_height,
+            _ => return Ok(()),
+        };
+
+        info!("Indexing records from block {} to {}", start_block_height, latest_block_height);
+
+        let mut block_height = start_block_height;
+        while block_height <= latest_block_height {
+            // Compute the end block height (inclusive) for this iteration, to bound the size of each batch.
+            let end_block_height = std::cmp::min(block_height.saturating_add(1_000), latest_block_height);
+
+            // Perform all the associated storage operations as an atomic batch.
+            let batch = self.ledger_roots.prepare_batch();
+            let mut number_of_pruned_blocks = 0;
+            for current_block_height in block_height..=end_block_height {
+                // The transactions of a pruned block are no longer available to be indexed.
+                if self.is_pruned(current_block_height)? {
+                    number_of_pruned_blocks += 1;
+                    continue;
+                }
+
+                let block = self.get_block(current_block_height)?;
+                let block_keys: Vec<_> = keys
+                    .iter()
+                    .filter(|(start_block_height, _)| *start_block_height <= current_block_height)
+                    .map(|(_, key)| key.clone())
+                    .collect();
+                self.indexer.add_block(&block, &block_keys, Some(batch))?;
+            }
+
+            if number_of_pruned_blocks > 0 {
+                warn!(
+                    "Skipped the indexing of {} pruned blocks between blocks {} and {}",
+                    number_of_pruned_blocks, block_height, end_block_height
+                );
+            }
+
+            // Nothing was staged if every block of this iteration has been pruned.
+            if number_of_pruned_blocks <= end_block_height - block_height {
+                self.ledger_roots.execute_batch(batch)?;
+                debug!("Indexed records up to block {}", end_block_height);
+            }
+            block_height = end_block_height.saturating_add(1);
+        }
+
+        Ok(())
+    }
+
+    /// Notifies the subscribers of the latest block of the ledger.
+    fn notify_latest_block(&self) {
+        let latest_block = self.latest_block.read();
+        self.latest_block_notifier.send_replace((latest_block.height(), latest_block.hash()));
+    }
+
     /// Updates the latest block hashes and block headers.
     fn regenerate_latest_ledger_state(&self) -> Result<()> {
         // Compute the start block height and end block height (inclusive).
@@ -1111,6 +1670,17 @@ impl<N: Network> LedgerState<N> {
                             latest_block_height
                         );
 
+                        // Only consider a quick update if the latest height is actually greater than the current height.
+                        // If the last known top block hash still exists at the expected height, there was no rollback
+                        // beyond it, which means the cached blocks remain valid.
+                        let is_extension = latest_block_height > current_block_height
+                            && matches!(ledger.get_block_hash(current_block_height), Ok(block_hash) if block_hash == current_block_hash);
+
+                        // Invalidate the caches before reading from them, as the writer may have reverted blocks.
+                        if !is_extension {
+                            ledger.caches.clear();
+                        }
+
                         // Update the last seen block.
                         let latest_block = ledger.get_block(latest_block_height);
                         match &latest_block {
@@ -1121,19 +1691,12 @@ impl<N: Network> LedgerState<N> {
                         // A flag indicating whether a fast ledger tree update is feasible.
                         let mut quick_update = false;
 
-                        // Only consider an update if the latest height is actually greater than the current height.
-                        if latest_block_height > current_block_height {
-                            // If the last known top block hash still exists at the expected height, there was no rollback
-                            // beyond it, which means we only need to update the ledger tree with the new hashes.
-                            if let Ok(found_block_hash) = ledger.get_block_hash(current_block_height) {
-                                if found_block_hash == current_block_hash {
-                                    // Update the ledger tree.
-                                    if let Err(error) = ledger.update_ledger_tree(current_block_height, latest_block_height) {
-                                        warn!("[Read-Only] {}", error);
-                                    } else {
-                                        quick_update = true;
-                                    }
-                                }
+                        // If there was no rollback, we only need to update the ledger tree with the new hashes.
+                        if is_extension {
+                            if let Err(error) = ledger.update_ledger_tree(current_block_height, latest_block_height) {
+                                warn!("[Read-Only] {}", error);
+                            } else {
+                                quick_update = true;
                             }
                         }
 
@@ -1154,6 +1717,7 @@ impl<N: Network> LedgerState<N> {
                         if let Ok(block) = latest_block {
                             *ledger.read_only.1.write() = block;
                         }
+                        ledger.notify_latest_block();
                     }
                 }
                 thread::sleep(std::time::Duration::from_millis(100));
@@ -1306,6 +1870,9 @@ impl<N: Network> LedgerState<N> {
         // Execute the pending storage batch.
         self.ledger_roots.execute_batch(batch)?;
 
+        // Invalidate the caches, as the cleared block heights may be reused by other blocks.
+        self.caches.clear();
+
         Ok(current_block_height)
     }
 
@@ -1333,6 +1900,15 @@ impl<N: Network> LedgerState<N> {
         Ok(())
     }
 
+    ///
+    /// Discards the transaction bodies of all blocks up to the given block height (inclusive),
+    /// regardless of the prune depth.
+    ///
+    #[cfg(test)]
+    pub(crate) fn prune_to_block_height(&self, block_height: u32) -> Result<()> {
+        self.blocks.prune_to_block_height(block_height, None)
+    }
+
     #[cfg(any(test, feature = "test"))]
     pub fn storage(&self) -> &RocksDB {
         self.ledger_roots.storage()
@@ -1344,6 +1920,7 @@ struct BlockState<N: Network> {
     block_heights: DataMap<u32, N::BlockHash>,
     block_headers: DataMap<N::BlockHash, BlockHeader<N>>,
     block_transactions: DataMap<N::BlockHash, Vec<N::TransactionID>>,
+    pruned_block_height: DataMap<(), u32>,
     transactions: TransactionState<N>,
 }
 
@@ -1354,10 +1931,27 @@ impl<N: Network> BlockState<N> {
             block_heights: storage.open_map(MapId::BlockHeights)?,
             block_headers: storage.open_map(MapId::BlockHeaders)?,
             block_transactions: storage.open_map(MapId::BlockTransactions)?,
+            pruned_block_height: storage.open_map(MapId::PrunedHeight)?,
             transactions: TransactionState::open(storage)?,
         })
     }
 
+    /// Returns a reader of the current state of storage.
+    fn reader(&self) -> BlockReader<'_, N> {
+        BlockReader {
+            blocks: self,
+            snapshot: None,
+        }
+    }
+
+    /// Returns a reader of a snapshot of the current storage, which observes a single point in time.
+    fn snapshot(&self) -> BlockReader<'_, N> {
+        BlockReader {
+            blocks: self,
+            snapshot: Some(self.block_heights.snapshot()),
+        }
+    }
+
     /// Returns `true` if the given block height exists in storage.
     fn contains_block_height(&self, block_height: u32) -> Result<bool> {
         self.block_heights.contains_key(&block_height)
@@ -1383,24 +1977,56 @@ impl<N: Network> BlockState<N> {
         self.transactions.contains_commitment(commitment)
     }
 
+    /// Returns the highest block height whose transaction bodies have been pruned, if any.
+    fn get_pruned_block_height(&self) -> Result<Option<u32>> {
+        self.reader().get_pruned_block_height()
+    }
+
+    /// Returns `true` if the transaction bodies of the given block height have been pruned.
+    /// The genesis block is never pruned.
+    fn is_pruned(&self, block_height: u32) -> Result<bool> {
+        self.reader().is_pruned(block_height)
+    }
+
     /// Returns the record ciphertext for a given commitment.
     fn get_ciphertext(&self, commitment: &N::Commitment) -> Result<N::RecordCiphertext> {
-        self.transactions.get_ciphertext(commitment)
+        match self.transactions.get_ciphertext(commitment) {
+            Ok(ciphertext) => Ok(ciphertext),
+            // The commitment is retained for pruned blocks, while its ciphertext is not.
+            Err(_) if self.contains_commitment(commitment)? && self.get_pruned_block_height()?.is_some() => {
+                Err(anyhow!("Ciphertext for commitment {} has been pruned", commitment))
+            }
+            Err(error) => Err(error),
+        }
     }
 
     /// Returns the transition for a given transition ID.
     fn get_transition(&self, transition_id: &N::TransitionID) -> Result<Transition<N>> {
-        self.transactions.get_transition(transition_id)
+        match self.transactions.get_transition(transition_id) {
+            Ok(transition) => Ok(transition),
+            Err(_) if self.get_pruned_block_height()?.is_some() => {
+                Err(anyhow!("Transition {} does not exist in storage or has been pruned", transition_id))
+            }
+            Err(error) => Err(error),
+        }
     }
 
     /// Returns the transaction for a given transaction ID.
     fn get_transaction(&self, transaction_id: &N::TransactionID) -> Result<Transaction<N>> {
-        self.transactions.get_transaction(transaction_id)
+        self.reader().get_transaction(transaction_id)
     }
 
     /// Returns the transaction metadata for a given transaction ID.
     fn get_transaction_metadata(&self, transaction_id: &N::TransactionID) -> Result<Metadata<N>> {
-        self.transactions.get_transaction_metadata(transaction_id)
+        match self.transactions.get_transaction_metadata(transaction_id) {
+            Ok(metadata) => Ok(metadata),
+            // The transactions of pruned blocks are no longer in storage.
+            Err(_) if self.get_pruned_block_height()?.is_some() => Err(anyhow!(
+                "Transaction {} does not exist in storage or has been pruned",
+                transaction_id
+            )),
+            Err(error) => Err(error),
+        }
     }
 
     /// Returns the cumulative weight up to a given block height (inclusive) for the canonical chain.
@@ -1410,50 +2036,27 @@ impl<N: Network> BlockState<N> {
 
     /// Returns the block height for the given block hash.
     fn get_block_height(&self, block_hash: &N::BlockHash) -> Result<u32> {
-        match self.block_headers.get(block_hash)? {
-            Some(block_header) => Ok(block_header.height()),
-            None => return Err(anyhow!("Block {} missing from block headers map", block_hash)),
-        }
+        self.reader().get_block_height(block_hash)
     }
 
     /// Returns the block hash for the given block height.
     fn get_block_hash(&self, block_height: u32) -> Result<N::BlockHash> {
-        self.get_previous_block_hash(block_height + 1)
+        self.reader().get_block_hash(block_height)
     }
 
     /// Returns the block hashes from the given `start_block_height` to `end_block_height` (inclusive).
     fn get_block_hashes(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<N::BlockHash>> {
-        // Ensure the starting block height is less than the ending block height.
-        if start_block_height > end_block_height {
-            return Err(anyhow!("Invalid starting and ending block heights"));
-        }
-
-        (start_block_height..=end_block_height)
-            .into_par_iter()
-            .map(|height| self.get_block_hash(height))
-            .collect()
+        self.reader().get_block_hashes(start_block_height, end_block_height)
     }
 
     /// Returns the previous block hash for the given block height.
     fn get_previous_block_hash(&self, block_height: u32) -> Result<N::BlockHash> {
-        match block_height == 0 {
-            true => Ok(N::genesis_block().previous_block_hash()),
-            false => match self.block_heights.get(&(block_height - 1))? {
-                Some(block_hash) => Ok(block_hash),
-                None => return Err(anyhow!("Block {} missing in block heights map", block_height - 1)),
-            },
-        }
+        self.reader().get_previous_block_hash(block_height)
     }
 
     /// Returns the block header for the given block height.
     fn get_block_header(&self, block_height: u32) -> Result<BlockHeader<N>> {
-        // Retrieve the block hash.
-        let block_hash = self.get_block_hash(block_height)?;
-
-        match self.block_headers.get(&block_hash)? {
-            Some(block_header) => Ok(block_header),
-            None => return Err(anyhow!("Block {} missing from block headers map", block_hash)),
-        }
+        self.reader().get_block_header(block_height)
     }
 
     /// Returns the block headers from the given `start_block_height` to `end_block_height` (inclusive).
@@ -1463,9 +2066,12 @@ impl<N: Network> BlockState<N> {
             return Err(anyhow!("Invalid starting and ending block heights"));
         }
 
-        (start_block_height..=end_block_height)
+        self.get_block_hashes(start_block_height, end_block_height)?
             .into_par_iter()
-            .map(|height| self.get_block_header(height))
+            .map(|block_hash| match self.block_headers.get(&block_hash)? {
+                Some(block_header) => Ok(block_header),
+                None => Err(anyhow!("Block {} missing from block headers map", block_hash)),
+            })
             .collect()
     }
 
@@ -1478,50 +2084,7 @@ impl<N: Network> BlockState<N> {
 
     /// Returns the transactions from the block of the given block height.
     fn get_block_transactions(&self, block_height: u32) -> Result<Transactions<N>> {
-        // Retrieve the block hash.
-        let block_hash = self.get_block_hash(block_height)?;
-
-        // Retrieve the block transaction IDs.
-        let transaction_ids = match self.block_transactions.get(&block_hash)? {
-            Some(transaction_ids) => transaction_ids,
-            None => return Err(anyhow!("Block {} missing from block transactions map", block_hash)),
-        };
-
-        // Retrieve the block transactions.
-        let transactions = {
-            let mut transactions = Vec::with_capacity(transaction_ids.len());
-            for transaction_id in transaction_ids.iter() {
-                transactions.push(self.transactions.get_transaction(transaction_id)?)
-            }
-            Transactions::from(&transactions)?
-        };
-
-        Ok(transactions)
-    }
-
-    /// Returns the block for a given block height.
-    fn get_block(&self, block_height: u32) -> Result<Block<N>> {
-        // Retrieve the previous block hash.
-        let previous_block_hash = self.get_previous_block_hash(block_height)?;
-        // Retrieve the block header.
-        let block_header = self.get_block_header(block_height)?;
-        // Retrieve the block transactions.
-        let transactions = self.get_block_transactions(block_height)?;
-
-        Ok(Block::from(previous_block_hash, block_header, transactions)?)
-    }
-
-    /// Returns the blocks from the given `start_block_height` to `end_block_height` (inclusive).
-    fn get_blocks(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<Block<N>>> {
-        // Ensure the starting block height is less than the ending block height.
-        if start_block_height > end_block_height {
-            return Err(anyhow!("Invalid starting and ending block heights"));
-        }
-
-        (start_block_height..=end_block_height)
-            .into_par_iter()
-            .map(|height| self.get_block(height))
-            .collect()
+        self.reader().get_block_transactions(block_height)
     }
 
     /// Returns the ledger root in the block header of the given block height.
@@ -1560,6 +2123,37 @@ impl<N: Network> BlockState<N> {
         }
     }
 
+    /// Discards the transaction bodies of all blocks up to the given block height (inclusive), excluding the genesis block.
+    fn prune_to_block_height(&self, block_height: u32, batch: Option<usize>) -> Result<()> {
+        // Determine the first block height that has not been pruned yet.
+        let start_block_height = match self.get_pruned_block_height()? {
+            Some(pruned_block_height) => pruned_block_height.saturating_add(1),
+            None => 1,
+        };
+
+        // Ensure there are blocks to prune.
+        if start_block_height > block_height {
+            return Ok(());
+        }
+
+        for current_block_height in start_block_height..=block_height {
+            // Retrieve the block hash.
+            let block_hash = self.get_block_hash(current_block_height)?;
+            // Retrieve the block transaction IDs.
+            let transaction_ids = match self.block_transactions.get(&block_hash)? {
+                Some(transaction_ids) => transaction_ids,
+                None => return Err(anyhow!("Block {} missing from block transactions map", block_hash)),
+            };
+            // Prune the transactions.
+            for transaction_id in transaction_ids.iter() {
+                self.transactions.prune_transaction(transaction_id, batch)?;
+            }
+        }
+
+        // Update the pruned block height.
+        self.pruned_block_height.insert(&(), &block_height, batch)
+    }
+
     /// Removes the given block height from storage.
     fn remove_block(&self, block_height: u32, batch: Option<usize>) -> Result<()> {
         // Ensure the block height is not the genesis block.
@@ -1604,6 +2198,209 @@ impl<N: Network> BlockState<N> {
     }
 }
 
+///
+/// A reader of the block state, which reads either from the current state of storage,
+/// or from a snapshot of storage that observes a single point in time.
+///
+struct BlockReader<'a, N: Network> {
+    blocks: &'a BlockState<N>,
+    snapshot: Option<Snapshot<'a>>,
+}
+
+impl<'a, N: Network> BlockReader<'a, N> {
+    /// Returns the value for the given key from the given map of the block state, if it exists.
+    fn read<K, V, Q>(&self, map: &DataMap<K, V>, key: &Q) -> Result<Option<V>>
+    where
+        K: Serialize + DeserializeOwned + Borrow<Q>,
+        V: Serialize + DeserializeOwned,
+        Q: Serialize + ?Sized,
+    {
+        match &self.snapshot {
+            Some(snapshot) => map.get_at(key, snapshot),
+            None => map.get(key),
+        }
+    }
+
+    /// Returns the highest block height whose transaction bodies have been pruned, if any.
+    fn get_pruned_block_height(&self) -> Result<Option<u32>> {
+        self.read(&self.blocks.pruned_block_height, &())
+    }
+
+    /// Returns `true` if the transaction bodies of the given block height have been pruned.
+    /// The genesis block is never pruned.
+    fn is_pruned(&self, block_height: u32) -> Result<bool> {
+        match self.get_pruned_block_height()? {
+            Some(pruned_block_height) => Ok(block_height != 0 && block_height <= pruned_block_height),
+            None => Ok(false),
+        }
+    }
+
+    /// Returns the transaction for a given transaction ID.
+    fn get_transaction(&self, transaction_id: &N::TransactionID) -> Result<Transaction<N>> {
+        match self.get_stored_transaction(transaction_id) {
+            Ok(transaction) => Ok(transaction),
+            // The transactions of pruned blocks are no longer in storage.
+            Err(_) if self.get_pruned_block_height()?.is_some() => Err(anyhow!(
+                "Transaction {} does not exist in storage or has been pruned",
+                transaction_id
+            )),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Returns the transaction for a given transaction ID, from the transactions in storage.
+    fn get_stored_transaction(&self, transaction_id: &N::TransactionID) -> Result<Transaction<N>> {
+        // Retrieve the transition IDs.
+        let (ledger_root, transition_ids) = match self.read(&self.blocks.transactions.transactions, transaction_id)? {
+            Some((ledger_root, transition_ids, _)) => (ledger_root, transition_ids),
+            None => return Err(anyhow!("Transaction {} does not exist in storage", transaction_id)),
+        };
+
+        // Retrieve the transitions.
+        let mut transitions = Vec::with_capacity(transition_ids.len());
+        for transition_id in transition_ids.iter() {
+            match self.read(&self.blocks.transactions.transitions, transition_id)? {
+                Some((_, _, transition)) => transitions.push(transition),
+                None => return Err(anyhow!("Transition {} missing in storage", transition_id)),
+            };
+        }
+
+        Transaction::from(*N::inner_circuit_id(), ledger_root, transitions)
+    }
+
+    /// Returns the block height for the given block hash.
+    fn get_block_height(&self, block_hash: &N::BlockHash) -> Result<u32> {
+        match self.read(&self.blocks.block_headers, block_hash)? {
+            Some(block_header) => Ok(block_header.height()),
+            None => return Err(anyhow!("Block {} missing from block headers map", block_hash)),
+        }
+    }
+
+    /// Returns the block hash for the given block height.
+    fn get_block_hash(&self, block_height: u32) -> Result<N::BlockHash> {
+        self.get_previous_block_hash(block_height + 1)
+    }
+
+    /// Returns the block hashes from the given `start_block_height` to `end_block_height` (inclusive).
+    fn get_block_hashes(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<N::BlockHash>> {
+        // Ensure the starting block height is less than the ending block height.
+        if start_block_height > end_block_height {
+            return Err(anyhow!("Invalid starting and ending block heights"));
+        }
+
+        let block_heights = &self.blocks.block_heights;
+        let range = start_block_height..=end_block_height;
+        let block_hashes: Vec<_> = match &self.snapshot {
+            Some(snapshot) => block_heights.range_at(range, snapshot)?.into_iter().map(|(_, hash)| hash).collect(),
+            None => block_heights.range(range)?.map(|(_, hash)| hash).collect(),
+        };
+
+        // Ensure every block height in the range is in the block heights map.
+        if block_hashes.len() as u64 != (end_block_height - start_block_height) as u64 + 1 {
+            let block_height = start_block_height + block_hashes.len() as u32;
+            return Err(anyhow!("Block {} missing in block heights map", block_height));
+        }
+
+        Ok(block_hashes)
+    }
+
+    /// Returns the previous block hash for the given block height.
+    fn get_previous_block_hash(&self, block_height: u32) -> Result<N::BlockHash> {
+        match block_height == 0 {
+            true => Ok(N::genesis_block().previous_block_hash()),
+            false => match self.read(&self.blocks.block_heights, &(block_height - 1))? {
+                Some(block_hash) => Ok(block_hash),
+                None => return Err(anyhow!("Block {} missing in block heights map", block_height - 1)),
+            },
+        }
+    }
+
+    /// Returns the block header for the given block height.
+    fn get_block_header(&self, block_height: u32) -> Result<BlockHeader<N>> {
+        // Retrieve the block hash.
+        let block_hash = self.get_block_hash(block_height)?;
+
+        match self.read(&self.blocks.block_headers, &block_hash)? {
+            Some(block_header) => Ok(block_header),
+            None => return Err(anyhow!("Block {} missing from block headers map", block_hash)),
+        }
+    }
+
+    /// Returns the transactions from the block of the given block height.
+    fn get_block_transactions(&self, block_height: u32) -> Result<Transactions<N>> {
+        // Retrieve the block hash.
+        let block_hash = self.get_block_hash(block_height)?;
+
+        self.get_block_transactions_by_hash(block_height, &block_hash)
+    }
+
+    /// Returns the transactions from the block of the given block height and block hash.
+    fn get_block_transactions_by_hash(&self, block_height: u32, block_hash: &N::BlockHash) -> Result<Transactions<N>> {
+        // Ensure the transactions of the block have not been pruned.
+        if self.is_pruned(block_height)? {
+            return Err(anyhow!("Block {} has been pruned", block_height));
+        }
+
+        // Retrieve the block transaction IDs.
+        let transaction_ids = match self.read(&self.blocks.block_transactions, block_hash)? {
+            Some(transaction_ids) => transaction_ids,
+            None => return Err(anyhow!("Block {} missing from block transactions map", block_hash)),
+        };
+
+        // Retrieve the block transactions.
+        let transactions = {
+            let mut transactions = Vec::with_capacity(transaction_ids.len());
+            for transaction_id in transaction_ids.iter() {
+                transactions.push(self.get_stored_transaction(transaction_id)?)
+            }
+            Transactions::from(&transactions)?
+        };
+
+        Ok(transactions)
+    }
+
+    /// Returns the block for a given block height.
+    fn get_block(&self, block_height: u32) -> Result<Block<N>> {
+        // Retrieve the previous block hash.
+        let previous_block_hash = self.get_previous_block_hash(block_height)?;
+        // Retrieve the block header.
+        let block_header = self.get_block_header(block_height)?;
+        // Retrieve the block transactions.
+        let transactions = self.get_block_transactions(block_height)?;
+
+        Ok(Block::from(previous_block_hash, block_header, transactions)?)
+    }
+
+    /// Returns the blocks from the given `start_block_height` to `end_block_height` (inclusive).
+    fn get_blocks(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<Block<N>>> {
+        // Ensure the starting block height is less than the ending block height.
+        if start_block_height > end_block_height {
+            return Err(anyhow!("Invalid starting and ending block heights"));
+        }
+
+        // Retrieve the block hashes, along with the previous block hash of each block.
+        let block_hashes = self.get_block_hashes(start_block_height, end_block_height)?;
+        let previous_block_hashes = std::iter::once(self.get_previous_block_hash(start_block_height)?)
+            .chain(block_hashes.iter().cloned())
+            .collect::<Vec<_>>();
+
+        (start_block_height..=end_block_height)
+            .into_par_iter()
+            .zip_eq(block_hashes.into_par_iter())
+            .zip(previous_block_hashes.into_par_iter())
+            .map(|((block_height, block_hash), previous_block_hash)| {
+                let block_header = match self.read(&self.blocks.block_headers, &block_hash)? {
+                    Some(block_header) => block_header,
+                    None => return Err(anyhow!("Block {} missing from block headers map", block_hash)),
+                };
+                let transactions = self.get_block_transactions_by_hash(block_height, &block_hash)?;
+
+                Ok(Block::from(previous_block_hash, block_header, transactions)?)
+            })
+            .collect()
+    }
+}
+
 #[derive(Clone, Debug)]
 #[allow(clippy::type_complexity)]
 struct TransactionState<N: Network> {
@@ -1671,26 +2468,6 @@ impl<N: Network> TransactionState<N> {
         }
     }
 
-    /// Returns the transaction for a given transaction ID.
-    fn get_transaction(&self, transaction_id: &N::TransactionID) -> Result<Transaction<N>> {
-        // Retrieve the transition IDs.
-        let (ledger_root, transition_ids) = match self.transactions.get(transaction_id)? {
-            Some((ledger_root, transition_ids, _)) => (ledger_root, transition_ids),
-            None => return Err(anyhow!("Transaction {} does not exist in storage", transaction_id)),
-        };
-
-        // Retrieve the transitions.
-        let mut transitions = Vec::with_capacity(transition_ids.len());
-        for transition_id in transition_ids.iter() {
-            match self.transitions.get(transition_id)? {
-                Some((_, _, transition)) => transitions.push(transition),
-                None => return Err(anyhow!("Transition {} missing in storage", transition_id)),
-            };
-        }
-
-        Transaction::from(*N::inner_circuit_id(), ledger_root, transitions)
-    }
-
     /// Returns the transaction metadata for a given transaction ID.
     fn get_transaction_metadata(&self, transaction_id: &N::TransactionID) -> Result<Metadata<N>> {
         // Retrieve the metadata from the transactions map.
@@ -1735,6 +2512,25 @@ impl<N: Network> TransactionState<N> {
         }
     }
 
+    /// Removes the given transaction ID and its transitions from storage,
+    /// retaining the serial numbers and commitments.
+    fn prune_transaction(&self, transaction_id: &N::TransactionID, batch: Option<usize>) -> Result<()> {
+        // Retrieve the transition IDs from the transaction.
+        let transition_ids = match self.transactions.get(transaction_id)? {
+            Some((_, transition_ids, _)) => transition_ids,
+            None => return Err(anyhow!("Transaction {} does not exist in storage", transaction_id)),
+        };
+
+        // Remove the transaction entry.
+        self.transactions.remove(transaction_id, batch)?;
+
+        // Remove the transitions.
+        for transition_id in transition_ids.iter() {
+            self.transitions.remove(transition_id, batch)?;
+        }
+        Ok(())
+    }
+
     /// Removes the given transaction ID from storage.
     fn remove_transaction(&self, transaction_id: &N::TransactionID, batch: Option<usize>) -> Result<()> {
         // Retrieve the transition IDs from the transaction.
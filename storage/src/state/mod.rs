@@ -14,12 +14,24 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+pub(crate) mod cache;
+pub use cache::{CacheStats, LedgerCacheConfig, LedgerCacheStats};
+
+pub(crate) mod indexer;
+pub use indexer::{IndexedRecord, IndexerKey};
+
+pub(crate) mod integrity;
+pub use integrity::{LedgerIssue, LedgerReport};
+
 pub(crate) mod ledger;
 pub use ledger::{LedgerState, Metadata, MAXIMUM_BLOCK_LOCATORS, MAXIMUM_LINEAR_BLOCK_LOCATORS, MAXIMUM_QUADRATIC_BLOCK_LOCATORS};
 
 pub(crate) mod operator;
 pub use operator::OperatorState;
 
+pub(crate) mod peer_book;
+pub use peer_book::{BanSource, PeerBook, PeerEntry};
+
 pub(crate) mod prover;
 pub use prover::ProverState;
 
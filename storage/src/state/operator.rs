@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::storage::{DataMap, Map, MapId, Storage};
+use crate::storage::{BackupInfo, DataMap, Map, MapId, Storage};
 use snarkvm::dpc::prelude::*;
 
 use anyhow::{anyhow, Result};
@@ -82,6 +82,11 @@ impl<N: Network> OperatorState<N> {
     pub fn get_provers(&self) -> Vec<Address<N>> {
         self.shares.get_provers()
     }
+
+    /// Creates an incremental backup of the operator storage in the given directory, keeping the given number of recent backups.
+    pub fn backup<P: AsRef<Path>>(&self, path: P, num_backups_to_keep: Option<usize>) -> Result<BackupInfo> {
+        self.shares.shares.backup(path.as_ref(), num_backups_to_keep)
+    }
 }
 
 #[derive(Clone, Debug)]
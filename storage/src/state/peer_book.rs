@@ -0,0 +1,351 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::storage::{DataMap, Map, MapId, Storage};
+use snarkos_environment::helpers::NodeType;
+use snarkvm::dpc::prelude::*;
+
+use anyhow::Result;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, collections::HashMap, marker::PhantomData, net::{IpAddr, SocketAddr}, path::Path, time::Duration};
+use time::OffsetDateTime;
+
+/// The number of seconds after which the contribution of the last connection to the score of a peer halves.
+const RECENCY_HALF_LIFE_IN_SECS: f64 = 24.0 * 60.0 * 60.0;
+
+/// The origin of the ban of a peer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BanSource {
+    /// The peer was restricted by the node, such as for staying silent for too long.
+    Restriction,
+    /// The peer was banned for repeatedly misbehaving.
+    Misbehavior,
+    /// The peer was banned by the operator of the node.
+    Manual,
+}
+
+///
+/// The history of a peer, as recorded in the peer book.
+///
+/// *Attention*: This data structure is intended for usage in storage only.
+/// Modifications to its layout will impact how peers are represented in storage.
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeerEntry {
+    /// The node type of the peer, once it has connected.
+    pub node_type: Option<NodeType>,
+    /// The UNIX timestamp of the last time the peer was connected.
+    pub last_seen: Option<i64>,
+    /// The number of successful connections with the peer.
+    pub num_successes: u32,
+    /// The number of failed connection attempts to the peer.
+    pub num_failures: u32,
+    /// The smoothed round-trip latency of the peer, in milliseconds.
+    pub latency_in_millis: Option<u64>,
+    /// The UNIX timestamp until which the peer is banned, if any.
+    pub banned_until: Option<i64>,
+    /// The origin of the ban that lasts until `banned_until`, if any.
+    pub ban_source: Option<BanSource>,
+    /// The number of times the peer has been banned for misbehaving.
+    pub num_bans: u32,
+    /// A flag indicating whether the node maintains a connection with the peer, as a trusted node.
+    pub is_trusted: bool,
+    /// The Noise identity key of the peer, as recorded on the first encrypted connection.
+    pub identity: Option<[u8; 32]>,
+}
+
+impl PeerEntry {
+    /// Returns `true` if the peer is banned at the given UNIX timestamp.
+    pub fn is_banned(&self, now: i64) -> bool {
+        matches!(self.banned_until, Some(banned_until) if banned_until > now)
+    }
+
+    /// Bans the peer until the given UNIX timestamp, unless it is already banned for longer.
+    fn extend_ban(&mut self, banned_until: i64, source: BanSource) {
+        if self.banned_until.map_or(true, |previous| banned_until >= previous) {
+            self.banned_until = Some(banned_until);
+            self.ban_source = Some(source);
+        }
+    }
+
+    ///
+    /// Returns the score of the peer at the given UNIX timestamp, where a higher score indicates
+    /// a more desirable peer to connect to. A banned peer has a score of zero.
+    ///
+    pub fn score(&self, now: i64) -> f64 {
+        if self.is_banned(now) {
+            return 0.0;
+        }
+
+        // The ratio of successful connections, which starts out neutral for a new peer.
+        let reliability = (self.num_successes as f64 + 1.0) / (self.num_successes as f64 + self.num_failures as f64 + 2.0);
+        // A peer that was connected recently is likely to still be reachable.
+        let recency = match self.last_seen {
+            Some(last_seen) => 0.5f64.powf(now.saturating_sub(last_seen).max(0) as f64 / RECENCY_HALF_LIFE_IN_SECS),
+            None => 0.0,
+        };
+        // A peer with a lower latency is preferred, and an unknown latency is treated as one second.
+        let responsiveness = 1000.0 / (1000.0 + self.latency_in_millis.unwrap_or(1000) as f64);
+
+        reliability * (1.0 + recency) * responsiveness
+    }
+}
+
+///
+/// The peers this node has learned of, along with their history, which persists across restarts.
+/// The entries are kept in memory, and an entry is only written to storage when it changes.
+///
+#[derive(Debug)]
+pub struct PeerBook<N: Network> {
+    /// The history of each peer, as persisted in storage.
+    peers: DataMap<SocketAddr, PeerEntry>,
+    /// The history of each peer, which is read without accessing storage.
+    entries: RwLock<HashMap<SocketAddr, PeerEntry>>,
+    _phantom: PhantomData<N>,
+}
+
+impl<N: Network> PeerBook<N> {
+    ///
+    /// Opens a new instance of `PeerBook` from the given storage path.
+    ///
+    pub fn open<S: Storage, P: AsRef<Path>>(path: P) -> Result<Self> {
+        // Open storage.
+        let context = N::NETWORK_ID;
+        let storage = S::open(path, context, false)?;
+
+        let peers: DataMap<SocketAddr, PeerEntry> = storage.open_map(MapId::PeerBook)?;
+        let entries = peers.iter().collect();
+
+        let peer_book = Self {
+            peers,
+            entries: RwLock::new(entries),
+            _phantom: PhantomData,
+        };
+
+        info!("Peer book successfully initialized");
+        Ok(peer_book)
+    }
+
+    /// Returns the entry of the given peer, if it exists.
+    pub fn get(&self, peer_ip: &SocketAddr) -> Result<Option<PeerEntry>> {
+        Ok(self.entries.read().get(peer_ip).cloned())
+    }
+
+    /// Returns every peer in the peer book, along with its entry.
+    pub fn to_entries(&self) -> Vec<(SocketAddr, PeerEntry)> {
+        self.entries
+            .read()
+            .iter()
+            .map(|(peer_ip, entry)| (*peer_ip, entry.clone()))
+            .collect()
+    }
+
+    ///
+    /// Returns `true` if a peer with the given IP is currently banned. Bans apply to the IP,
+    /// as a peer may connect from any port, and reconnect with another listener port.
+    ///
+    pub fn is_banned(&self, ip: IpAddr) -> bool {
+        let now = now();
+        self.entries
+            .read()
+            .iter()
+            .any(|(peer_ip, entry)| peer_ip.ip() == ip && entry.is_banned(now))
+    }
+
+    /// Returns up to the given number of peers that are not banned, ordered by descending score.
+    pub fn best_peers(&self, number: usize) -> Vec<SocketAddr> {
+        let now = now();
+        let mut peers = self
+            .entries
+            .read()
+            .iter()
+            .filter(|(_, entry)| !entry.is_banned(now))
+            .map(|(peer_ip, entry)| (*peer_ip, entry.score(now)))
+            .collect::<Vec<_>>();
+        peers.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+        peers.into_iter().take(number).map(|(peer_ip, _)| peer_ip).collect()
+    }
+
+    /// Returns the peers that were added as trusted nodes.
+    pub fn trusted_peers(&self) -> Vec<SocketAddr> {
+        self.entries
+            .read()
+            .iter()
+            .filter(|(_, entry)| entry.is_trusted)
+            .map(|(peer_ip, _)| *peer_ip)
+            .collect()
+    }
+
+    /// Adds the given peer to the peer book, if it does not exist.
+    pub fn add_peer(&self, peer_ip: SocketAddr) -> Result<()> {
+        self.update(peer_ip, |_| ())
+    }
+
+    /// Records a successful connection with the given peer.
+    pub fn record_success(&self, peer_ip: SocketAddr, node_type: NodeType) -> Result<()> {
+        self.update(peer_ip, |entry| {
+            entry.node_type = Some(node_type);
+            entry.last_seen = Some(now());
+            entry.num_successes = entry.num_successes.saturating_add(1);
+        })
+    }
+
+    /// Records a failed connection attempt to the given peer.
+    pub fn record_failure(&self, peer_ip: SocketAddr) -> Result<()> {
+        self.update(peer_ip, |entry| entry.num_failures = entry.num_failures.saturating_add(1))
+    }
+
+    /// Records that the given peer has disconnected, which is the last time it was seen.
+    pub fn record_disconnect(&self, peer_ip: SocketAddr) -> Result<()> {
+        self.update(peer_ip, |entry| entry.last_seen = Some(now()))
+    }
+
+    /// Records a round-trip latency measurement of the given peer.
+    pub fn record_latency(&self, peer_ip: SocketAddr, latency: Duration) -> Result<()> {
+        let latency = latency.as_millis() as u64;
+        self.update(peer_ip, |entry| {
+            // Smooth the measurements, so that a single slow round trip does not dominate.
+            entry.latency_in_millis = Some(match entry.latency_in_millis {
+                Some(previous) => (previous.saturating_mul(3).saturating_add(latency)) / 4,
+                None => latency,
+            });
+        })
+    }
+
+    /// Bans the given peer for the given duration and from the given source, unless it is already banned for longer.
+    pub fn ban(&self, peer_ip: SocketAddr, duration: Duration, source: BanSource) -> Result<()> {
+        let banned_until = now().saturating_add(duration.as_secs() as i64);
+        self.update(peer_ip, |entry| entry.extend_ban(banned_until, source))
+    }
+
+    ///
+    /// Bans the given peer for misbehaving, returning the duration of the ban. The first ban lasts
+    /// for the given base duration, which doubles with each repeat offense up to the given maximum.
+    ///
+    pub fn ban_repeat_offender(&self, peer_ip: SocketAddr, base: Duration, maximum: Duration) -> Result<Duration> {
+        let now = now();
+        let mut duration = base;
+        self.update(peer_ip, |entry| {
+            entry.num_bans = entry.num_bans.saturating_add(1);
+            let multiplier = 1u32.checked_shl(entry.num_bans - 1).unwrap_or(u32::MAX);
+            duration = base.saturating_mul(multiplier).min(maximum);
+
+            entry.extend_ban(now.saturating_add(duration.as_secs() as i64), BanSource::Misbehavior);
+        })?;
+        Ok(duration)
+    }
+
+    /// Lifts the bans of the peers with the given IP, if any.
+    pub fn unban(&self, ip: IpAddr) -> Result<()> {
+        let banned_peers = self
+            .entries
+            .read()
+            .iter()
+            .filter(|(peer_ip, entry)| peer_ip.ip() == ip && entry.banned_until.is_some())
+            .map(|(peer_ip, _)| *peer_ip)
+            .collect::<Vec<_>>();
+        for peer_ip in banned_peers {
+            self.update(peer_ip, |entry| {
+                entry.banned_until = None;
+                entry.ban_source = None;
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Sets whether the given peer is a trusted node, which persists across restarts.
+    pub fn set_trusted(&self, peer_ip: SocketAddr, is_trusted: bool) -> Result<()> {
+        self.update(peer_ip, |entry| entry.is_trusted = is_trusted)
+    }
+
+    ///
+    /// Records the Noise identity key of the given peer, returning `false` if the peer has
+    /// a different identity key on record, which is only replaced if `replace` is `true`.
+    ///
+    pub fn pin_identity(&self, peer_ip: SocketAddr, identity: [u8; 32], replace: bool) -> Result<bool> {
+        let mut is_pinned = true;
+        self.update(peer_ip, |entry| match entry.identity {
+            Some(previous) if previous != identity => {
+                is_pinned = false;
+                if replace {
+                    entry.identity = Some(identity);
+                }
+            }
+            _ => entry.identity = Some(identity),
+        })?;
+        Ok(is_pinned)
+    }
+
+    ///
+    /// Removes the lowest-scoring peers until at most the given number of peers remain,
+    /// returning the number of removed peers. Banned and trusted peers are kept, so that they persist.
+    ///
+    pub fn prune(&self, maximum_number_of_peers: usize) -> Result<usize> {
+        let mut entries = self.entries.write();
+
+        let num_excess_peers = entries.len().saturating_sub(maximum_number_of_peers);
+        if num_excess_peers == 0 {
+            return Ok(0);
+        }
+
+        let now = now();
+        let mut candidates = entries
+            .iter()
+            .filter(|(_, entry)| !entry.is_banned(now) && !entry.is_trusted)
+            .map(|(peer_ip, entry)| (*peer_ip, entry.score(now)))
+            .collect::<Vec<_>>();
+        candidates.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        candidates.truncate(num_excess_peers);
+
+        // The excess peers may all be banned or trusted, which leaves nothing to remove.
+        if candidates.is_empty() {
+            return Ok(0);
+        }
+
+        let batch = self.peers.prepare_batch();
+        for (peer_ip, _) in &candidates {
+            self.peers.remove(peer_ip, Some(batch))?;
+        }
+        self.peers.execute_batch(batch)?;
+
+        for (peer_ip, _) in &candidates {
+            entries.remove(peer_ip);
+        }
+        Ok(candidates.len())
+    }
+
+    ///
+    /// Applies the given update to the entry of the given peer, adding the peer if it does not exist.
+    /// The entry is only written to storage if it is new or has changed.
+    ///
+    fn update<F: FnOnce(&mut PeerEntry)>(&self, peer_ip: SocketAddr, update: F) -> Result<()> {
+        let mut entries = self.entries.write();
+        let previous = entries.get(&peer_ip);
+        let mut entry = previous.cloned().unwrap_or_default();
+        update(&mut entry);
+        if previous != Some(&entry) {
+            self.peers.insert(&peer_ip, &entry, None)?;
+            entries.insert(peer_ip, entry);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the current UNIX timestamp.
+fn now() -> i64 {
+    OffsetDateTime::now_utc().unix_timestamp()
+}
@@ -15,14 +15,19 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    storage::{rocksdb::RocksDB, Storage},
+    storage::{Map, MapId, Storage},
+    BanSource,
+    IndexerKey,
+    LedgerCacheConfig,
+    LedgerIssue,
     LedgerState,
+    PeerBook,
 };
-use snarkos_environment::CurrentNetwork;
+use snarkos_environment::{helpers::NodeType, CurrentNetwork};
 use snarkvm::dpc::prelude::*;
 
 use rand::{thread_rng, Rng};
-use std::sync::atomic::AtomicBool;
+use std::{net::SocketAddr, sync::atomic::AtomicBool, time::Duration};
 
 fn temp_dir() -> std::path::PathBuf {
     tempfile::tempdir().expect("Failed to open temporary directory").into_path()
@@ -33,10 +38,9 @@ fn create_new_ledger<N: Network, S: Storage>() -> LedgerState<N> {
     LedgerState::open_writer_with_increment::<S, _>(temp_dir(), 1).expect("Failed to initialize ledger")
 }
 
-#[test]
-fn test_genesis() {
+fn test_genesis<S: Storage>() {
     // Initialize a new ledger.
-    let ledger = create_new_ledger::<CurrentNetwork, RocksDB>();
+    let ledger = create_new_ledger::<CurrentNetwork, S>();
 
     // Retrieve the genesis block.
     let genesis = CurrentNetwork::genesis_block();
@@ -56,13 +60,12 @@ fn test_genesis() {
     assert_eq!(ledger_tree.root(), ledger.latest_ledger_root());
 }
 
-#[test]
-fn test_add_next_block() {
+fn test_add_next_block<S: Storage>() {
     let rng = &mut thread_rng();
     let terminator = AtomicBool::new(false);
 
     // Initialize a new ledger.
-    let ledger = create_new_ledger::<CurrentNetwork, RocksDB>();
+    let ledger = create_new_ledger::<CurrentNetwork, S>();
     assert_eq!(0, ledger.latest_block_height());
 
     // Initialize a new ledger tree.
@@ -104,13 +107,12 @@ fn test_add_next_block() {
     assert_eq!(Some(&(genesis.hash(), None)), block_locators.get(&genesis.height()));
 }
 
-#[test]
-fn test_remove_last_block() {
+fn test_remove_last_block<S: Storage>() {
     let rng = &mut thread_rng();
     let terminator = AtomicBool::new(false);
 
     // Initialize a new ledger.
-    let ledger = create_new_ledger::<CurrentNetwork, RocksDB>();
+    let ledger = create_new_ledger::<CurrentNetwork, S>();
     assert_eq!(0, ledger.latest_block_height());
 
     // Initialize a new ledger tree.
@@ -150,13 +152,12 @@ fn test_remove_last_block() {
     assert_eq!(ledger_tree.root(), ledger.latest_ledger_root());
 }
 
-#[test]
-fn test_remove_last_2_blocks() {
+fn test_remove_last_2_blocks<S: Storage>() {
     let rng = &mut thread_rng();
     let terminator = AtomicBool::new(false);
 
     // Initialize a new ledger.
-    let ledger = create_new_ledger::<CurrentNetwork, RocksDB>();
+    let ledger = create_new_ledger::<CurrentNetwork, S>();
     assert_eq!(0, ledger.latest_block_height());
 
     // Initialize a new ledger tree.
@@ -203,13 +204,12 @@ fn test_remove_last_2_blocks() {
     assert_eq!(ledger_tree.root(), ledger.latest_ledger_root());
 }
 
-#[test]
-fn test_get_block_locators() {
+fn test_get_block_locators<S: Storage>() {
     let rng = &mut thread_rng();
     let terminator = AtomicBool::new(false);
 
     // Initialize a new ledger.
-    let ledger = create_new_ledger::<CurrentNetwork, RocksDB>();
+    let ledger = create_new_ledger::<CurrentNetwork, S>();
     assert_eq!(0, ledger.latest_block_height());
 
     // Initialize a new ledger tree.
@@ -274,13 +274,12 @@ fn test_get_block_locators() {
     );
 }
 
-#[test]
-fn test_transaction_fees() {
+fn test_transaction_fees<S: Storage>() {
     let rng = &mut thread_rng();
     let terminator = AtomicBool::new(false);
 
     // Initialize a new ledger.
-    let ledger = create_new_ledger::<CurrentNetwork, RocksDB>();
+    let ledger = create_new_ledger::<CurrentNetwork, S>();
     assert_eq!(0, ledger.latest_block_height());
 
     // Initialize a new account.
@@ -347,3 +346,425 @@ fn test_transaction_fees() {
     assert_eq!(new_coinbase_record.value(), expected_block_reward);
     assert_eq!(output_record.value(), amount);
 }
+
+fn test_prune_depth_below_fork_depth<S: Storage>() {
+    // Ensure a prune depth that is shallower than the maximum fork depth is rejected.
+    let prune_depth = CurrentNetwork::ALEO_MAXIMUM_FORK_DEPTH - 1;
+    assert!(LedgerState::<CurrentNetwork>::open_writer_with_options::<S, _>(temp_dir(), Some(prune_depth), vec![]).is_err());
+}
+
+fn test_prune_blocks<S: Storage>() {
+    let rng = &mut thread_rng();
+    let terminator = AtomicBool::new(false);
+
+    // Initialize a new ledger.
+    let ledger = create_new_ledger::<CurrentNetwork, S>();
+
+    // Initialize a new account.
+    let account = Account::<CurrentNetwork>::new(&mut thread_rng());
+    let address = account.address();
+
+    // Mine the next two blocks.
+    let (block_1, _) = ledger
+        .mine_next_block(address, true, &[], &terminator, rng)
+        .expect("Failed to mine");
+    ledger.add_next_block(&block_1).expect("Failed to add next block to ledger");
+    let (block_2, _) = ledger
+        .mine_next_block(address, true, &[], &terminator, rng)
+        .expect("Failed to mine");
+    ledger.add_next_block(&block_2).expect("Failed to add next block to ledger");
+
+    // Prune the transaction bodies of block 1.
+    ledger.prune_to_block_height(1).expect("Failed to prune the ledger");
+    assert_eq!(Some(1), ledger.get_pruned_block_height().unwrap());
+    assert!(!ledger.is_pruned(0).unwrap());
+    assert!(ledger.is_pruned(1).unwrap());
+    assert!(!ledger.is_pruned(2).unwrap());
+
+    // Ensure the pruned block is reported as such, while its header and commitments are retained.
+    let error = ledger.get_block(1).unwrap_err();
+    assert!(error.to_string().contains("pruned"));
+    let transaction_id = block_1.transactions().transaction_ids().next().unwrap();
+    let error = ledger.get_transaction(&transaction_id).unwrap_err();
+    assert!(error.to_string().contains("pruned"));
+    assert!(!ledger.contains_transaction(&transaction_id).unwrap());
+    assert_eq!(block_1.header(), &ledger.get_block_header(1).unwrap());
+    for commitment in block_1.commitments() {
+        assert!(ledger.contains_commitment(commitment).unwrap());
+    }
+    for serial_number in block_1.serial_numbers() {
+        assert!(ledger.contains_serial_number(serial_number).unwrap());
+    }
+
+    // Ensure the genesis block and the unpruned block remain available.
+    assert_eq!(CurrentNetwork::genesis_block(), &ledger.get_block(0).unwrap());
+    assert_eq!(block_2, ledger.get_block(2).unwrap());
+}
+
+fn test_index_records<S: Storage>() {
+    let rng = &mut thread_rng();
+    let terminator = AtomicBool::new(false);
+
+    // Initialize a new account.
+    let account = Account::<CurrentNetwork>::new(&mut thread_rng());
+    let address = account.address();
+
+    // Initialize a new ledger that indexes the account.
+    let indexer_keys = vec![IndexerKey::PrivateKey(account.private_key().clone())];
+    let ledger = LedgerState::<CurrentNetwork>::open_writer_with_options::<S, _>(temp_dir(), None, indexer_keys)
+        .expect("Failed to initialize ledger");
+    assert_eq!(Some(0), ledger.get_indexed_block_height(&address).unwrap());
+    assert!(ledger.get_owned_records(&address).unwrap().is_empty());
+
+    // Mine the next block.
+    let (block, record) = ledger
+        .mine_next_block(address, true, &[], &terminator, rng)
+        .expect("Failed to mine");
+    ledger.add_next_block(&block).expect("Failed to add next block to ledger");
+
+    // Ensure the coinbase record is indexed, and is unspent.
+    let records = ledger.get_owned_records(&address).unwrap();
+    assert_eq!(1, records.len());
+    assert_eq!(record, records[0].record);
+    assert_eq!(1, records[0].block_height);
+    assert!(records[0].serial_number.is_some());
+    assert_eq!(None, ledger.get_record_spend(&record.commitment()).unwrap());
+    assert_eq!(Some(1), ledger.get_indexed_block_height(&address).unwrap());
+
+    // Ensure an address that is not indexed is rejected.
+    let other_address = Account::<CurrentNetwork>::new(rng).address();
+    assert!(ledger.get_owned_records(&other_address).is_err());
+
+    // Revert the block, and ensure the coinbase record is removed from the index.
+    ledger.revert_to_block_height(0).expect("Failed to revert the ledger");
+    assert!(ledger.get_owned_records(&address).unwrap().is_empty());
+    assert_eq!(Some(0), ledger.get_indexed_block_height(&address).unwrap());
+}
+
+fn test_verify<S: Storage>() {
+    let rng = &mut thread_rng();
+    let terminator = AtomicBool::new(false);
+    let directory = temp_dir();
+
+    let (block, orphaned_ledger_root) = {
+        // Initialize a new ledger.
+        let ledger = LedgerState::<CurrentNetwork>::open_writer::<S, _>(&directory).expect("Failed to initialize ledger");
+
+        // Mine the next block.
+        let address = Account::<CurrentNetwork>::new(rng).address();
+        let (block, _) = ledger
+            .mine_next_block(address, true, &[], &terminator, rng)
+            .expect("Failed to mine");
+        ledger.add_next_block(&block).expect("Failed to add next block to ledger");
+
+        (block, ledger.latest_ledger_root())
+    };
+
+    // Ensure a repair of a consistent ledger succeeds without modifying it.
+    let report = LedgerState::<CurrentNetwork>::verify::<S, _>(&directory, true).unwrap();
+    assert_eq!(Some(1), report.latest_block_height);
+    assert!(report.is_consistent());
+
+    // Corrupt the ledger roots map.
+    {
+        let storage = S::open(&directory, CurrentNetwork::NETWORK_ID, false).expect("Failed to open storage");
+        let ledger_roots = storage
+            .open_map::<<CurrentNetwork as Network>::LedgerRoot, u32>(MapId::LedgerRoots)
+            .unwrap();
+        ledger_roots.remove(&block.previous_ledger_root(), None).unwrap();
+        ledger_roots.insert(&orphaned_ledger_root, &5, None).unwrap();
+    }
+
+    // Ensure the inconsistencies are reported with their keys.
+    let report = LedgerState::<CurrentNetwork>::verify::<S, _>(&directory, false).unwrap();
+    assert_eq!(Some(1), report.latest_block_height);
+    assert_eq!(
+        vec![
+            LedgerIssue::MissingLedgerRoot(1, block.previous_ledger_root()),
+            LedgerIssue::OrphanedLedgerRoot(orphaned_ledger_root, 5)
+        ],
+        report.issues
+    );
+    assert!(!report.repaired);
+
+    // Ensure the verification did not modify the ledger.
+    let report = LedgerState::<CurrentNetwork>::verify::<S, _>(&directory, false).unwrap();
+    assert_eq!(2, report.issues.len());
+
+    // Repair the ledger, and ensure it is consistent afterwards.
+    let report = LedgerState::<CurrentNetwork>::verify::<S, _>(&directory, true).unwrap();
+    assert_eq!(2, report.issues.len());
+    assert!(report.repaired);
+    let report = LedgerState::<CurrentNetwork>::verify::<S, _>(&directory, false).unwrap();
+    assert!(report.is_consistent());
+
+    let ledger = LedgerState::<CurrentNetwork>::open_writer::<S, _>(&directory).expect("Failed to reopen ledger");
+    assert_eq!(block, ledger.latest_block());
+}
+
+fn test_block_template_coinbase<S: Storage>() {
+    let rng = &mut thread_rng();
+    let terminator = AtomicBool::new(false);
+
+    // Initialize a new ledger.
+    let ledger = create_new_ledger::<CurrentNetwork, S>();
+
+    // Initialize a new account.
+    let account = Account::<CurrentNetwork>::new(rng);
+    let private_key = account.private_key();
+    let view_key = account.view_key();
+    let address = account.address();
+
+    // Ensure the coinbase of the template pays the block reward.
+    let template = ledger.get_block_template(address, true, &[], rng).unwrap();
+    let coinbase_record: Record<CurrentNetwork> = template.coinbase_record().clone().into();
+    assert_eq!(Block::<CurrentNetwork>::block_reward(1), coinbase_record.value());
+    assert_eq!(address, coinbase_record.owner());
+
+    // Ensure the coinbase is reused for a template with the same parameters.
+    let template = ledger.get_block_template(address, true, &[], rng).unwrap();
+    let cached_coinbase_record: Record<CurrentNetwork> = template.coinbase_record().clone().into();
+    assert_eq!(coinbase_record, cached_coinbase_record);
+
+    // Ensure the coinbase is recreated for a different recipient.
+    let recipient = Account::<CurrentNetwork>::new(rng).address();
+    let template = ledger.get_block_template(recipient, true, &[], rng).unwrap();
+    let recipient_coinbase_record: Record<CurrentNetwork> = template.coinbase_record().clone().into();
+    assert_eq!(recipient, recipient_coinbase_record.owner());
+    assert_eq!(Block::<CurrentNetwork>::block_reward(1), recipient_coinbase_record.value());
+
+    // Ensure the coinbase is recreated for a different visibility.
+    let template = ledger.get_block_template(recipient, false, &[], rng).unwrap();
+    let private_coinbase_record: Record<CurrentNetwork> = template.coinbase_record().clone().into();
+    assert_ne!(recipient_coinbase_record.commitment(), private_coinbase_record.commitment());
+
+    // Mine the next block.
+    let (block, _) = ledger
+        .mine_next_block(address, true, &[], &terminator, rng)
+        .expect("Failed to mine");
+    ledger.add_next_block(&block).expect("Failed to add next block to ledger");
+
+    // Ensure the coinbase is recreated once the tip changes.
+    let template = ledger.get_block_template(address, true, &[], rng).unwrap();
+    let next_coinbase_record: Record<CurrentNetwork> = template.coinbase_record().clone().into();
+    assert_ne!(coinbase_record.commitment(), next_coinbase_record.commitment());
+    assert_eq!(Block::<CurrentNetwork>::block_reward(2), next_coinbase_record.value());
+
+    // Craft a transaction with a random fee.
+    let coinbase_transaction = &block.transactions()[0];
+    let available_balance = AleoAmount::from_i64(-coinbase_transaction.value_balance().0);
+    let fee = AleoAmount::from_i64(rng.gen_range(1..available_balance.0));
+    let records = coinbase_transaction.to_decrypted_records(&view_key.into()).collect::<Vec<_>>();
+    let ledger_proof = ledger.get_ledger_inclusion_proof(records[0].commitment()).unwrap();
+    let transfer_request = Request::new_transfer(
+        private_key,
+        records,
+        vec![ledger_proof, LedgerProof::default()],
+        recipient,
+        available_balance.sub(fee),
+        fee,
+        true,
+        rng,
+    )
+    .unwrap();
+    let (vm, _response) = VirtualMachine::new(ledger.latest_ledger_root())
+        .unwrap()
+        .execute(&transfer_request, rng)
+        .unwrap();
+    let transaction = vm.finalize().unwrap();
+
+    // Ensure the coinbase of the template pays the block reward and the transaction fees.
+    let template = ledger.get_block_template(address, true, &[transaction], rng).unwrap();
+    let fee_coinbase_record: Record<CurrentNetwork> = template.coinbase_record().clone().into();
+    assert_eq!(Block::<CurrentNetwork>::block_reward(2).add(fee), fee_coinbase_record.value());
+    assert_ne!(next_coinbase_record.commitment(), fee_coinbase_record.commitment());
+
+    // Ensure the coinbase without the transaction does not reuse the coinbase with its fees.
+    let template = ledger.get_block_template(address, true, &[], rng).unwrap();
+    let coinbase_record: Record<CurrentNetwork> = template.coinbase_record().clone().into();
+    assert_eq!(Block::<CurrentNetwork>::block_reward(2), coinbase_record.value());
+}
+
+fn test_subscribe<S: Storage>() {
+    let rng = &mut thread_rng();
+    let terminator = AtomicBool::new(false);
+
+    // Initialize a new ledger.
+    let ledger = create_new_ledger::<CurrentNetwork, S>();
+    let mut latest_block = ledger.subscribe();
+    assert_eq!((0, CurrentNetwork::genesis_block().hash()), *latest_block.borrow());
+
+    // Initialize a new account.
+    let account = Account::<CurrentNetwork>::new(&mut thread_rng());
+    let address = account.address();
+
+    // Ensure the subscriber is notified of the next block.
+    let (block, _) = ledger
+        .mine_next_block(address, true, &[], &terminator, rng)
+        .expect("Failed to mine");
+    ledger.add_next_block(&block).expect("Failed to add next block to ledger");
+    assert!(latest_block.has_changed().expect("Failed to check for changes"));
+    assert_eq!((1, block.hash()), *latest_block.borrow_and_update());
+
+    // Ensure the subscriber is notified of the reverted block.
+    ledger.revert_to_block_height(0).expect("Failed to remove the last block");
+    assert!(latest_block.has_changed().expect("Failed to check for changes"));
+    assert_eq!((0, CurrentNetwork::genesis_block().hash()), *latest_block.borrow_and_update());
+}
+
+fn test_block_cache<S: Storage>() {
+    let rng = &mut thread_rng();
+    let terminator = AtomicBool::new(false);
+
+    // Initialize a new ledger.
+    let ledger = create_new_ledger::<CurrentNetwork, S>();
+
+    // Initialize a new account.
+    let account = Account::<CurrentNetwork>::new(&mut thread_rng());
+    let address = account.address();
+
+    // Mine the next block.
+    let (block, _) = ledger
+        .mine_next_block(address, true, &[], &terminator, rng)
+        .expect("Failed to mine");
+    ledger.add_next_block(&block).expect("Failed to add next block to ledger");
+
+    // Ensure a repeated lookup is answered by the cache.
+    assert_eq!(block, ledger.get_block(1).unwrap());
+    let stats = ledger.cache_stats();
+    assert_eq!(block, ledger.get_block(1).unwrap());
+    assert_eq!(stats.blocks.hits + 1, ledger.cache_stats().blocks.hits);
+    assert_eq!(block.header(), &ledger.get_block_header(1).unwrap());
+
+    // Ensure the serialized blocks match their bincode serialization.
+    let genesis_block = CurrentNetwork::genesis_block();
+    let expected = vec![bincode::serialize(genesis_block).unwrap(), bincode::serialize(&block).unwrap()];
+    ledger.get_serialized_blocks(1, 1).unwrap();
+    let stats = ledger.cache_stats();
+    for _ in 0..2 {
+        let serialized_blocks = ledger.get_serialized_blocks(0, 1).unwrap();
+        assert_eq!(expected, serialized_blocks.iter().map(|bytes| bytes.to_vec()).collect::<Vec<_>>());
+    }
+
+    // Ensure a partially cached range is counted as read, and only the fully cached one as hits.
+    assert_eq!(stats.serialized_blocks.misses + 2, ledger.cache_stats().serialized_blocks.misses);
+    assert_eq!(stats.serialized_blocks.hits + 2, ledger.cache_stats().serialized_blocks.hits);
+
+    // Ensure a reverted block is evicted from the caches.
+    ledger.revert_to_block_height(0).expect("Failed to remove the last block");
+    assert!(ledger.get_block(1).is_err());
+    assert!(ledger.get_block_header(1).is_err());
+    assert!(ledger.get_serialized_blocks(1, 1).is_err());
+
+    // Ensure the replacement block is returned instead of the reverted one.
+    let other_account = Account::<CurrentNetwork>::new(&mut thread_rng());
+    let (other_block, _) = ledger
+        .mine_next_block(other_account.address(), true, &[], &terminator, rng)
+        .expect("Failed to mine");
+    ledger.add_next_block(&other_block).expect("Failed to add next block to ledger");
+    assert_eq!(other_block, ledger.get_block(1).unwrap());
+    assert_eq!(other_block.header(), &ledger.get_block_header(1).unwrap());
+
+    // Ensure disabled caches answer no lookups.
+    ledger.set_cache_config(LedgerCacheConfig {
+        blocks: 0,
+        block_headers: 0,
+        serialized_blocks: 0,
+    });
+    let stats = ledger.cache_stats();
+    assert_eq!(other_block, ledger.get_block(1).unwrap());
+    assert_eq!(other_block, ledger.get_block(1).unwrap());
+    assert_eq!(stats.blocks.hits, ledger.cache_stats().blocks.hits);
+}
+
+fn test_peer_book<S: Storage>() {
+    let path = temp_dir();
+    let peer_book = PeerBook::<CurrentNetwork>::open::<S, _>(&path).expect("Failed to initialize peer book");
+
+    let reliable: SocketAddr = "127.0.0.1:4130".parse().unwrap();
+    let unreliable: SocketAddr = "127.0.0.1:4131".parse().unwrap();
+    let banned: SocketAddr = "127.0.0.2:4132".parse().unwrap();
+
+    // A peer that has connected ranks above a peer that has failed.
+    peer_book.record_success(reliable, NodeType::Client).unwrap();
+    peer_book.record_latency(reliable, Duration::from_millis(100)).unwrap();
+    peer_book.record_failure(unreliable).unwrap();
+    peer_book.record_failure(unreliable).unwrap();
+    peer_book.add_peer(banned).unwrap();
+    assert_eq!(vec![reliable, banned, unreliable], peer_book.best_peers(10));
+
+    let entry = peer_book.get(&reliable).unwrap().unwrap();
+    assert_eq!(Some(NodeType::Client), entry.node_type);
+    assert_eq!((1, 0), (entry.num_successes, entry.num_failures));
+    assert_eq!(Some(100), entry.latency_in_millis);
+
+    // A banned peer is excluded from the candidates.
+    peer_book.ban(banned, Duration::from_secs(60), BanSource::Manual).unwrap();
+    assert!(peer_book.is_banned(banned.ip()));
+    assert_eq!(Some(BanSource::Manual), peer_book.get(&banned).unwrap().unwrap().ban_source);
+    assert!(!peer_book.is_banned(reliable.ip()));
+    assert_eq!(vec![reliable, unreliable], peer_book.best_peers(10));
+
+    // Pruning removes the lowest-scoring peers, but keeps the banned ones.
+    assert_eq!(1, peer_book.prune(2).unwrap());
+    assert!(peer_book.get(&unreliable).unwrap().is_none());
+    assert!(peer_book.get(&banned).unwrap().is_some());
+
+    // The peer book persists across restarts.
+    drop(peer_book);
+    let peer_book = PeerBook::<CurrentNetwork>::open::<S, _>(&path).expect("Failed to reopen peer book");
+    assert!(peer_book.is_banned(banned.ip()));
+    assert_eq!(vec![reliable], peer_book.best_peers(10));
+
+    peer_book.unban(banned.ip()).unwrap();
+    assert!(!peer_book.is_banned(banned.ip()));
+
+    // Repeat offenders are banned for exponentially longer, up to the maximum duration.
+    let (base, maximum) = (Duration::from_secs(60), Duration::from_secs(200));
+    assert_eq!(base, peer_book.ban_repeat_offender(banned, base, maximum).unwrap());
+    assert_eq!(base * 2, peer_book.ban_repeat_offender(banned, base, maximum).unwrap());
+    assert_eq!(maximum, peer_book.ban_repeat_offender(banned, base, maximum).unwrap());
+    assert_eq!(3, peer_book.get(&banned).unwrap().unwrap().num_bans);
+    assert_eq!(Some(BanSource::Misbehavior), peer_book.get(&banned).unwrap().unwrap().ban_source);
+    assert!(peer_book.is_banned(banned.ip()));
+
+    // Trusted peers are kept by pruning, until they are removed.
+    let trusted: SocketAddr = "127.0.0.1:4133".parse().unwrap();
+    peer_book.set_trusted(trusted, true).unwrap();
+    peer_book.record_failure(trusted).unwrap();
+    assert_eq!(vec![trusted], peer_book.trusted_peers());
+    assert_eq!(1, peer_book.prune(0).unwrap());
+    assert!(peer_book.get(&trusted).unwrap().is_some());
+    // With only banned and trusted peers left, there is nothing to prune.
+    assert_eq!(0, peer_book.prune(0).unwrap());
+
+    peer_book.set_trusted(trusted, false).unwrap();
+    assert!(peer_book.trusted_peers().is_empty());
+
+    // The identity of a peer is recorded on first contact, and a changed identity is only replaced on request.
+    let (identity, other_identity) = ([1u8; 32], [2u8; 32]);
+    assert!(peer_book.pin_identity(trusted, identity, false).unwrap());
+    assert!(peer_book.pin_identity(trusted, identity, false).unwrap());
+    assert!(!peer_book.pin_identity(trusted, other_identity, false).unwrap());
+    assert_eq!(Some(identity), peer_book.get(&trusted).unwrap().unwrap().identity);
+    assert!(!peer_book.pin_identity(trusted, other_identity, true).unwrap());
+    assert_eq!(Some(other_identity), peer_book.get(&trusted).unwrap().unwrap().identity);
+}
+
+test_storage_backends!(
+    test_genesis,
+    test_add_next_block,
+    test_remove_last_block,
+    test_remove_last_2_blocks,
+    test_get_block_locators,
+    test_transaction_fees,
+    test_prune_depth_below_fork_depth,
+    test_prune_blocks,
+    test_index_records,
+    test_block_template_coinbase,
+    test_subscribe,
+    test_block_cache,
+    test_peer_book,
+);
+
+test_persistent_storage_backends!(test_verify);
@@ -0,0 +1,32 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+
+///
+/// A backup of storage, which shares its unchanged files with the other backups in its directory.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupInfo {
+    /// The ID of the backup, which increases with each backup in its directory.
+    pub backup_id: u32,
+    /// The time the backup was created, in seconds since the Unix epoch.
+    pub timestamp: i64,
+    /// The size of the files of the backup in bytes, including the ones shared with other backups.
+    pub size: u64,
+    /// The number of files of the backup.
+    pub num_files: u32,
+}
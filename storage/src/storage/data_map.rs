@@ -0,0 +1,595 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(feature = "rocks")]
+use crate::storage::rocksdb;
+use crate::storage::{memory, BackupInfo, Map, StorageStats};
+
+use anyhow::{bail, Result};
+use bincode::Options;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    ops::{Bound, RangeBounds},
+    path::Path,
+};
+
+pub const PREFIX_LEN: usize = 4; // N::NETWORK_ID (u16) + MapId (u16)
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u16)]
+pub enum MapId {
+    BlockHeaders = 0,
+    BlockHeights,
+    BlockTransactions,
+    Commitments,
+    LedgerRoots,
+    Records,
+    SerialNumbers,
+    Transactions,
+    Transitions,
+    Shares,
+    PrunedHeight,
+    IndexedAddresses,
+    IndexedRecords,
+    IndexedSerialNumbers,
+    IndexedSpends,
+    Schema,
+    PeerBook,
+    #[cfg(test)]
+    Test,
+}
+
+impl MapId {
+    /// The IDs of every map in storage.
+    pub const ALL: &'static [MapId] = &[
+        MapId::BlockHeaders,
+        MapId::BlockHeights,
+        MapId::BlockTransactions,
+        MapId::Commitments,
+        MapId::LedgerRoots,
+        MapId::Records,
+        MapId::SerialNumbers,
+        MapId::Transactions,
+        MapId::Transitions,
+        MapId::Shares,
+        MapId::PrunedHeight,
+        MapId::IndexedAddresses,
+        MapId::IndexedRecords,
+        MapId::IndexedSerialNumbers,
+        MapId::IndexedSpends,
+        MapId::Schema,
+        MapId::PeerBook,
+        #[cfg(test)]
+        MapId::Test,
+    ];
+
+    /// Returns the map ID with the given numeric representation, if it exists.
+    pub fn from_u16(id: u16) -> Option<Self> {
+        Self::ALL.iter().copied().find(|map_id| *map_id as u16 == id)
+    }
+
+    /// Returns `true` if the keys of the map are serialized in big-endian, which preserves the order of integer keys.
+    pub fn has_ordered_keys(&self) -> bool {
+        match self {
+            Self::BlockHeights => true,
+            #[cfg(test)]
+            Self::Test => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the name of the map.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BlockHeaders => "block_headers",
+            Self::BlockHeights => "block_heights",
+            Self::BlockTransactions => "block_transactions",
+            Self::Commitments => "commitments",
+            Self::LedgerRoots => "ledger_roots",
+            Self::Records => "records",
+            Self::SerialNumbers => "serial_numbers",
+            Self::Transactions => "transactions",
+            Self::Transitions => "transitions",
+            Self::Shares => "shares",
+            Self::PrunedHeight => "pruned_height",
+            Self::IndexedAddresses => "indexed_addresses",
+            Self::IndexedRecords => "indexed_records",
+            Self::IndexedSerialNumbers => "indexed_serial_numbers",
+            Self::IndexedSpends => "indexed_spends",
+            Self::Schema => "schema",
+            Self::PeerBook => "peer_book",
+            #[cfg(test)]
+            Self::Test => "test",
+        }
+    }
+}
+
+/// Returns the options to serialize the keys of maps with ordered keys.
+fn ordered_key_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_big_endian()
+        .allow_trailing_bytes()
+}
+
+/// Serializes the given key of the given map, appending it to the given bytes.
+pub(crate) fn serialize_key<Q: Serialize + ?Sized>(map_id: MapId, key: &Q, bytes: &mut Vec<u8>) -> Result<()> {
+    match map_id.has_ordered_keys() {
+        true => ordered_key_options().serialize_into(bytes, key)?,
+        false => bincode::serialize_into(bytes, key)?,
+    }
+    Ok(())
+}
+
+/// Deserializes a key of the given map from the given bytes.
+pub(crate) fn deserialize_key<K: DeserializeOwned>(map_id: MapId, bytes: &[u8]) -> Result<K> {
+    match map_id.has_ordered_keys() {
+        true => Ok(ordered_key_options().deserialize(bytes)?),
+        false => Ok(bincode::deserialize(bytes)?),
+    }
+}
+
+///
+/// The bounds of an iteration over the raw keys of a map, which share a common prefix.
+///
+#[derive(Clone, Debug)]
+pub(crate) struct KeyRange {
+    /// The length of the prefix of the raw keys.
+    prefix_len: usize,
+    /// The lower bound of the raw keys, which is never unbounded.
+    start: Bound<Vec<u8>>,
+    /// The upper bound of the raw keys.
+    end: Bound<Vec<u8>>,
+    /// Indicates whether the raw keys are iterated in reverse order.
+    is_reverse: bool,
+}
+
+impl KeyRange {
+    /// Returns the range of every raw key with the given prefix.
+    pub(crate) fn full(prefix: &[u8], is_reverse: bool) -> Self {
+        Self {
+            prefix_len: prefix.len(),
+            start: Bound::Included(prefix.to_vec()),
+            end: Self::prefix_end(prefix),
+            is_reverse,
+        }
+    }
+
+    /// Returns the range of the raw keys with the given prefix, for the given range of keys of the given map.
+    pub(crate) fn new<Q, R>(prefix: &[u8], map_id: MapId, range: R, is_reverse: bool) -> Result<Self>
+    where
+        Q: Serialize + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let raw_key = |key: &Q| -> Result<Vec<u8>> {
+            let mut raw_key = prefix.to_vec();
+            serialize_key(map_id, key, &mut raw_key)?;
+            Ok(raw_key)
+        };
+
+        let start = match range.start_bound() {
+            Bound::Included(key) => Bound::Included(raw_key(key)?),
+            Bound::Excluded(key) => Bound::Excluded(raw_key(key)?),
+            Bound::Unbounded => Bound::Included(prefix.to_vec()),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(key) => Bound::Included(raw_key(key)?),
+            Bound::Excluded(key) => Bound::Excluded(raw_key(key)?),
+            Bound::Unbounded => Self::prefix_end(prefix),
+        };
+
+        Ok(Self {
+            prefix_len: prefix.len(),
+            start,
+            end,
+            is_reverse,
+        })
+    }
+
+    /// Returns the upper bound of the raw keys with the given prefix.
+    fn prefix_end(prefix: &[u8]) -> Bound<Vec<u8>> {
+        // Increment the prefix, carrying over any trailing maximum bytes.
+        let mut end = prefix.to_vec();
+        while let Some(byte) = end.pop() {
+            if byte < u8::MAX {
+                end.push(byte + 1);
+                return Bound::Excluded(end);
+            }
+        }
+        Bound::Unbounded
+    }
+
+    /// Returns the length of the prefix of the raw keys.
+    pub(crate) fn prefix_len(&self) -> usize {
+        self.prefix_len
+    }
+
+    /// Returns `true` if the raw keys are iterated in reverse order.
+    pub(crate) fn is_reverse(&self) -> bool {
+        self.is_reverse
+    }
+
+    /// Returns the lower bound of the raw keys.
+    pub(crate) fn start(&self) -> Bound<&[u8]> {
+        as_slice(&self.start)
+    }
+
+    /// Returns the upper bound of the raw keys.
+    pub(crate) fn end(&self) -> Bound<&[u8]> {
+        as_slice(&self.end)
+    }
+
+    /// Returns the position of the given raw key relative to the range.
+    pub(crate) fn position(&self, key: &[u8]) -> Ordering {
+        let is_before = match &self.start {
+            Bound::Included(start) => key < start.as_slice(),
+            Bound::Excluded(start) => key <= start.as_slice(),
+            Bound::Unbounded => false,
+        };
+        let is_after = match &self.end {
+            Bound::Included(end) => key > end.as_slice(),
+            Bound::Excluded(end) => key >= end.as_slice(),
+            Bound::Unbounded => false,
+        };
+
+        match (is_before, is_after) {
+            (true, _) => Ordering::Less,
+            (_, true) => Ordering::Greater,
+            _ => Ordering::Equal,
+        }
+    }
+
+    /// Returns the next raw key-value pair within the range from the given iterator,
+    /// which starts at the first raw key in the order of the iteration.
+    pub(crate) fn next_in_range<B: AsRef<[u8]>>(&self, iterator: &mut impl Iterator<Item = (B, B)>) -> Option<(B, B)> {
+        loop {
+            let (key, value) = iterator.next()?;
+            match (self.position(key.as_ref()), self.is_reverse) {
+                (Ordering::Equal, _) => return Some((key, value)),
+                // Skip the raw keys on an excluded bound the iteration started from.
+                (Ordering::Less, false) | (Ordering::Greater, true) => continue,
+                _ => return None,
+            }
+        }
+    }
+}
+
+/// Returns the given bound as a bound of a slice.
+fn as_slice(bound: &Bound<Vec<u8>>) -> Bound<&[u8]> {
+    match bound {
+        Bound::Included(bytes) => Bound::Included(bytes.as_slice()),
+        Bound::Excluded(bytes) => Bound::Excluded(bytes.as_slice()),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+/// Forwards the given expression to the map of the storage backend.
+macro_rules! dispatch {
+    ($self:expr, $map:ident => $expression:expr) => {
+        match $self {
+            #[cfg(feature = "rocks")]
+            Self::RocksDB($map) => $expression,
+            Self::Memory($map) => $expression,
+        }
+    };
+}
+
+///
+/// A map opened from one of the storage backends.
+///
+#[derive(Clone)]
+pub enum DataMap<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> {
+    #[cfg(feature = "rocks")]
+    RocksDB(rocksdb::DataMap<K, V>),
+    Memory(memory::DataMap<K, V>),
+}
+
+impl<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> DataMap<K, V> {
+    /// Returns the raw key-value pairs in the map, without the prefix of the keys.
+    pub(crate) fn raw_entries(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        dispatch!(self, map => map.raw_entries())
+    }
+
+    /// Returns `true` if the map holds any key-value pair.
+    pub(crate) fn has_entries(&self) -> bool {
+        dispatch!(self, map => map.has_entries())
+    }
+
+    /// Inserts the given raw key-value pair into the map, where the key is without its prefix.
+    pub(crate) fn insert_raw(&self, key: &[u8], value: &[u8], batch: Option<usize>) -> Result<()> {
+        dispatch!(self, map => map.insert_raw(key, value, batch))
+    }
+
+    /// Removes the given raw key from the map, where the key is without its prefix.
+    pub(crate) fn remove_raw(&self, key: &[u8], batch: Option<usize>) -> Result<()> {
+        dispatch!(self, map => map.remove_raw(key, batch))
+    }
+
+    /// Returns a snapshot of the storage the map was opened from, at its current state.
+    pub(crate) fn snapshot(&self) -> Snapshot<'_> {
+        match self {
+            #[cfg(feature = "rocks")]
+            Self::RocksDB(map) => Snapshot::RocksDB(map.snapshot()),
+            Self::Memory(map) => Snapshot::Memory(map.snapshot()),
+        }
+    }
+
+    /// Returns the value for the given key from the given snapshot, if it exists.
+    /// The snapshot must be taken from a map of the same storage.
+    pub(crate) fn get_at<Q>(&self, key: &Q, snapshot: &Snapshot) -> Result<Option<V>>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        match (self, snapshot) {
+            #[cfg(feature = "rocks")]
+            (Self::RocksDB(map), Snapshot::RocksDB(snapshot)) => map.get_at(key, snapshot),
+            (Self::Memory(map), Snapshot::Memory(snapshot)) => map.get_at(key, snapshot),
+            #[allow(unreachable_patterns)]
+            _ => bail!("The snapshot was taken from a different storage backend"),
+        }
+    }
+
+    /// Returns the key-value pairs in the given range of keys from the given snapshot.
+    /// The snapshot must be taken from a map of the same storage.
+    pub(crate) fn range_at<Q, R>(&self, range: R, snapshot: &Snapshot) -> Result<Vec<(K, V)>>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        match (self, snapshot) {
+            #[cfg(feature = "rocks")]
+            (Self::RocksDB(map), Snapshot::RocksDB(snapshot)) => map.range_at(range, snapshot),
+            (Self::Memory(map), Snapshot::Memory(snapshot)) => map.range_at(range, snapshot),
+            #[allow(unreachable_patterns)]
+            _ => bail!("The snapshot was taken from a different storage backend"),
+        }
+    }
+
+    /// Returns the statistics of the storage the map was opened from.
+    pub(crate) fn storage_stats(&self) -> Result<StorageStats> {
+        dispatch!(self, map => map.storage_stats())
+    }
+
+    /// Creates an incremental backup of the storage the map was opened from, in the given directory.
+    pub(crate) fn backup(&self, path: &Path, num_backups_to_keep: Option<usize>) -> Result<BackupInfo> {
+        match self {
+            #[cfg(feature = "rocks")]
+            Self::RocksDB(map) => map.backup(path, num_backups_to_keep),
+            Self::Memory(_) => bail!("Backups of in-memory storage are not supported"),
+        }
+    }
+
+    #[cfg(all(feature = "rocks", any(test, feature = "test")))]
+    pub fn storage(&self) -> &rocksdb::RocksDB {
+        match self {
+            Self::RocksDB(map) => map.storage(),
+            Self::Memory(_) => panic!("The data map was not opened from RocksDB"),
+        }
+    }
+}
+
+///
+/// A snapshot of the storage backend a map was opened from, which borrows the map it was taken from.
+///
+/// This allows state that holds maps, rather than storage, to read from several maps of the same storage
+/// at a single point in time, unaffected by any writes made to the storage afterwards.
+///
+pub enum Snapshot<'a> {
+    #[cfg(feature = "rocks")]
+    RocksDB(rocksdb::Snapshot<'a>),
+    Memory(memory::Snapshot),
+}
+
+impl<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> fmt::Debug for DataMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        dispatch!(self, map => fmt::Debug::fmt(map, f))
+    }
+}
+
+impl<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> Map<'a, K, V> for DataMap<K, V> {
+    type Iterator = Iter<'a, K, V>;
+    type Keys = Keys<'a, K>;
+    type Values = Values<'a, V>;
+
+    ///
+    /// Returns `true` if the given key exists in the map.
+    ///
+    fn contains_key<Q>(&self, key: &Q) -> Result<bool>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        dispatch!(self, map => map.contains_key(key))
+    }
+
+    ///
+    /// Returns the value for the given key from the map, if it exists.
+    ///
+    fn get<Q>(&self, key: &Q) -> Result<Option<V>>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        dispatch!(self, map => map.get(key))
+    }
+
+    ///
+    /// Inserts the given key-value pair into the map. Can be paired with a numeric
+    /// batch id, which defers the operation until `execute_batch` is called using
+    /// the same id.
+    ///
+    fn insert<Q>(&self, key: &Q, value: &V, batch: Option<usize>) -> Result<()>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        dispatch!(self, map => map.insert(key, value, batch))
+    }
+
+    ///
+    /// Removes the key-value pair for the given key from the map. Can be paired with a
+    /// numeric batch id, which defers the operation until `execute_batch` is called using
+    /// the same id.
+    ///
+    fn remove<Q>(&self, key: &Q, batch: Option<usize>) -> Result<()>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        dispatch!(self, map => map.remove(key, batch))
+    }
+
+    ///
+    /// Returns an iterator visiting each key-value pair in the map.
+    ///
+    fn iter(&'a self) -> Self::Iterator {
+        match self {
+            #[cfg(feature = "rocks")]
+            Self::RocksDB(map) => Iter::RocksDB(map.iter()),
+            Self::Memory(map) => Iter::Memory(map.iter()),
+        }
+    }
+
+    ///
+    /// Returns an iterator visiting each key-value pair in the map, in reverse order.
+    ///
+    fn iter_rev(&'a self) -> Self::Iterator {
+        match self {
+            #[cfg(feature = "rocks")]
+            Self::RocksDB(map) => Iter::RocksDB(map.iter_rev()),
+            Self::Memory(map) => Iter::Memory(map.iter_rev()),
+        }
+    }
+
+    ///
+    /// Returns an iterator visiting each key-value pair in the given range of keys.
+    ///
+    fn range<Q, R>(&'a self, range: R) -> Result<Self::Iterator>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        match self {
+            #[cfg(feature = "rocks")]
+            Self::RocksDB(map) => Ok(Iter::RocksDB(map.range(range)?)),
+            Self::Memory(map) => Ok(Iter::Memory(map.range(range)?)),
+        }
+    }
+
+    ///
+    /// Returns an iterator over each key in the map.
+    ///
+    fn keys(&'a self) -> Self::Keys {
+        match self {
+            #[cfg(feature = "rocks")]
+            Self::RocksDB(map) => Keys::RocksDB(map.keys()),
+            Self::Memory(map) => Keys::Memory(map.keys()),
+        }
+    }
+
+    ///
+    /// Returns an iterator over each value in the map.
+    ///
+    fn values(&'a self) -> Self::Values {
+        match self {
+            #[cfg(feature = "rocks")]
+            Self::RocksDB(map) => Values::RocksDB(map.values()),
+            Self::Memory(map) => Values::Memory(map.values()),
+        }
+    }
+
+    ///
+    /// Performs a refresh operation for implementations of `Map` that perform periodic operations.
+    /// Returns `true` if the sequence number of the database has increased.
+    ///
+    fn refresh(&self) -> bool {
+        dispatch!(self, map => map.refresh())
+    }
+
+    ///
+    /// Prepares an atomic batch of writes and returns its numeric id which can later be used to include
+    /// operations within it. `execute_batch` has to be called in order for any of the writes to actually
+    /// take place.
+    ///
+    fn prepare_batch(&self) -> usize {
+        dispatch!(self, map => map.prepare_batch())
+    }
+
+    ///
+    /// Atomically executes a write batch with the given id.
+    ///
+    fn execute_batch(&self, batch: usize) -> Result<()> {
+        dispatch!(self, map => map.execute_batch(batch))
+    }
+
+    ///
+    /// Discards a write batch with the given id.
+    ///
+    fn discard_batch(&self, batch: usize) -> Result<()> {
+        dispatch!(self, map => map.discard_batch(batch))
+    }
+}
+
+/// An iterator over all key-value pairs in a data map.
+pub enum Iter<'a, K: DeserializeOwned, V: DeserializeOwned> {
+    #[cfg(feature = "rocks")]
+    RocksDB(rocksdb::Iter<'a, K, V>),
+    Memory(memory::Iter<'a, K, V>),
+}
+
+impl<'a, K: DeserializeOwned, V: DeserializeOwned> Iterator for Iter<'a, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        dispatch!(self, iter => iter.next())
+    }
+}
+
+/// An iterator over the keys of a data map.
+pub enum Keys<'a, K: DeserializeOwned> {
+    #[cfg(feature = "rocks")]
+    RocksDB(rocksdb::Keys<'a, K>),
+    Memory(memory::Keys<'a, K>),
+}
+
+impl<'a, K: DeserializeOwned> Iterator for Keys<'a, K> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        dispatch!(self, keys => keys.next())
+    }
+}
+
+/// An iterator over the values of a data map.
+pub enum Values<'a, V: DeserializeOwned> {
+    #[cfg(feature = "rocks")]
+    RocksDB(rocksdb::Values<'a, V>),
+    Memory(memory::Values<'a, V>),
+}
+
+impl<'a, V: DeserializeOwned> Iterator for Values<'a, V> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        dispatch!(self, values => values.next())
+    }
+}
@@ -0,0 +1,382 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::storage::{
+    schema::{schema_version, SCHEMA_VERSION},
+    DataMap,
+    Map,
+    MapId,
+    Storage,
+    PREFIX_LEN,
+};
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryInto,
+    fmt,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+    str::FromStr,
+};
+
+/// The magic bytes at the start of a storage file.
+const MAGIC: [u8; 8] = *b"snarkos\0";
+/// The version of the storage file format.
+const FORMAT_VERSION: u16 = 1;
+/// The maximum size of the entries in a chunk, before compression.
+const CHUNK_SIZE: usize = 1 << 20;
+/// The maximum size of a chunk after compression, which exceeds its size before compression for incompressible entries.
+const MAXIMUM_COMPRESSED_CHUNK_SIZE: usize = CHUNK_SIZE + CHUNK_SIZE / 128;
+/// The compression level of the chunks compressed with zstd.
+const ZSTD_COMPRESSION_LEVEL: i32 = 3;
+
+/// An entry of a storage file, as a map ID, a key and a value.
+pub(crate) type Entry = (MapId, Vec<u8>, Vec<u8>);
+
+///
+/// The compression of the chunks in a storage file.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Compression {
+    None,
+    Lz4,
+    Zstd,
+}
+
+impl FromStr for Compression {
+    type Err = anyhow::Error;
+
+    fn from_str(compression: &str) -> Result<Self> {
+        match compression.to_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "lz4" => Ok(Self::Lz4),
+            "zstd" => Ok(Self::Zstd),
+            _ => Err(anyhow!("Unknown compression '{}', expected one of 'none', 'lz4' or 'zstd'", compression)),
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::Lz4 => write!(f, "lz4"),
+            Self::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+///
+/// The header of a storage file.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportHeader {
+    /// The version of the storage file format.
+    pub format_version: u16,
+    /// The network ID of the exported storage, which is the context of its maps.
+    pub network_id: u16,
+    /// The latest block height in the exported storage, if it holds blocks.
+    pub tip_height: Option<u32>,
+    /// The schema version of the exported storage.
+    pub schema_version: u32,
+    /// The compression of the chunks.
+    pub compression: Compression,
+    /// The IDs of the exported maps.
+    pub map_ids: Vec<u16>,
+}
+
+impl ExportHeader {
+    /// Initializes a new header for an export of the given maps in storage, with the given compression.
+    pub(crate) fn new<S: Storage>(storage: &S, network_id: u16, map_ids: &[MapId], compression: Compression) -> Result<Self> {
+        let block_heights: DataMap<u32, ()> = storage.open_map(MapId::BlockHeights)?;
+
+        Ok(Self {
+            format_version: FORMAT_VERSION,
+            network_id,
+            tip_height: block_heights.iter_rev().next().map(|(block_height, _)| block_height),
+            schema_version: schema_version(storage)?.unwrap_or(SCHEMA_VERSION),
+            compression,
+            map_ids: map_ids.iter().map(|map_id| *map_id as u16).collect(),
+        })
+    }
+}
+
+///
+/// A writer of storage files, which consist of a header followed by chunks of entries.
+/// Each chunk is compressed and prefixed with its lengths and the CRC32 of its compressed bytes.
+///
+pub(crate) struct ExportWriter {
+    writer: BufWriter<File>,
+    compression: Compression,
+    chunk: Vec<u8>,
+}
+
+impl ExportWriter {
+    /// Creates a storage file at the given path, and writes the given header.
+    pub(crate) fn create<P: AsRef<Path>>(path: P, header: &ExportHeader) -> Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        let header_bytes = bincode::serialize(header)?;
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&(header_bytes.len() as u32).to_le_bytes())?;
+        writer.write_all(&header_bytes)?;
+
+        Ok(Self {
+            writer,
+            compression: header.compression,
+            chunk: Vec::with_capacity(CHUNK_SIZE),
+        })
+    }
+
+    /// Writes the given entry, writing the current chunk first if the entry does not fit in it.
+    pub(crate) fn write_entry(&mut self, map_id: MapId, key: &[u8], value: &[u8]) -> Result<()> {
+        // Ensure the entry fits in a chunk, as the size of each chunk is bounded when it is read.
+        let entry_size = 10 + key.len() + value.len();
+        if entry_size > CHUNK_SIZE {
+            bail!("An entry of {} bytes in map {} exceeds the chunk size of the storage file", entry_size, map_id.name());
+        }
+        if self.chunk.len() + entry_size > CHUNK_SIZE {
+            self.write_chunk()?;
+        }
+
+        self.chunk.extend_from_slice(&(map_id as u16).to_le_bytes());
+        self.chunk.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        self.chunk.extend_from_slice(key);
+        self.chunk.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        self.chunk.extend_from_slice(value);
+        Ok(())
+    }
+
+    /// Writes the remaining entries, and flushes the storage file.
+    pub(crate) fn finish(mut self) -> Result<()> {
+        self.write_chunk()?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Compresses and writes the current chunk.
+    fn write_chunk(&mut self) -> Result<()> {
+        if self.chunk.is_empty() {
+            return Ok(());
+        }
+
+        let uncompressed_len = self.chunk.len();
+        let compressed = match self.compression {
+            Compression::None => std::mem::take(&mut self.chunk),
+            Compression::Lz4 => lz4_flex::compress(&self.chunk),
+            Compression::Zstd => zstd::bulk::compress(&self.chunk, ZSTD_COMPRESSION_LEVEL)?,
+        };
+
+        self.writer.write_all(&(uncompressed_len as u32).to_le_bytes())?;
+        self.writer.write_all(&(compressed.len() as u32).to_le_bytes())?;
+        self.writer.write_all(&crc32fast::hash(&compressed).to_le_bytes())?;
+        self.writer.write_all(&compressed)?;
+
+        self.chunk.clear();
+        Ok(())
+    }
+}
+
+///
+/// Reads the storage file at the given path, and passes each chunk of entries of the given maps to `write`.
+/// Storage files without a header are read in the legacy format of raw, length-prefixed key-value pairs.
+///
+pub(crate) fn read_export<P, F>(path: P, network_id: u16, map_ids: &[MapId], mut write: F) -> Result<()>
+where
+    P: AsRef<Path>,
+    F: FnMut(Vec<Entry>) -> Result<()>,
+{
+    let file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut magic = [0u8; 8];
+    if len < MAGIC.len() as u64 || reader.read_exact(&mut magic).is_err() || magic != MAGIC {
+        reader.seek(SeekFrom::Start(0))?;
+        // Storage files in the legacy format predate the schema version.
+        record_schema_version(0, map_ids, &mut write)?;
+        return read_legacy_export(reader, len, network_id, map_ids, write);
+    }
+
+    // Read the header.
+    let header_len = read_u32(&mut reader)? as usize;
+    let mut header_bytes = vec![0u8; header_len];
+    reader.read_exact(&mut header_bytes)?;
+    let header: ExportHeader = bincode::deserialize(&header_bytes)?;
+
+    if header.format_version > FORMAT_VERSION {
+        bail!("Storage file format version {} is not supported", header.format_version);
+    }
+    if header.network_id != network_id {
+        bail!("Storage file is for network {}, expected network {}", header.network_id, network_id);
+    }
+    if header.schema_version > SCHEMA_VERSION {
+        bail!("Storage file schema version {} is newer than the supported version {}", header.schema_version, SCHEMA_VERSION);
+    }
+    record_schema_version(header.schema_version, map_ids, &mut write)?;
+
+    match header.tip_height {
+        Some(tip_height) => info!("Importing a storage file with blocks up to {}", tip_height),
+        None => info!("Importing a storage file"),
+    }
+
+    let mut position = (MAGIC.len() + 4 + header_len) as u64;
+    let mut progress = 0;
+    let mut num_chunks = 0;
+
+    while position < len {
+        // Read the chunk.
+        let uncompressed_len = read_u32(&mut reader)? as usize;
+        let compressed_len = read_u32(&mut reader)? as usize;
+        let checksum = read_u32(&mut reader)?;
+
+        // Ensure the lengths are bounded by the chunk size, before allocating the chunk.
+        if uncompressed_len > CHUNK_SIZE || compressed_len > MAXIMUM_COMPRESSED_CHUNK_SIZE {
+            bail!("Chunk {} of the storage file exceeds the chunk size", num_chunks);
+        }
+        let mut compressed = vec![0u8; compressed_len];
+        reader.read_exact(&mut compressed)?;
+
+        if crc32fast::hash(&compressed) != checksum {
+            bail!("Chunk {} of the storage file is corrupted (checksum mismatch)", num_chunks);
+        }
+        let chunk = match header.compression {
+            Compression::None => compressed,
+            Compression::Lz4 => lz4_flex::decompress(&compressed, uncompressed_len)
+                .map_err(|error| anyhow!("Chunk {} of the storage file is corrupted ({})", num_chunks, error))?,
+            Compression::Zstd => zstd::bulk::decompress(&compressed, uncompressed_len)
+                .map_err(|error| anyhow!("Chunk {} of the storage file is corrupted ({})", num_chunks, error))?,
+        };
+        if chunk.len() != uncompressed_len {
+            bail!("Chunk {} of the storage file is corrupted (length mismatch)", num_chunks);
+        }
+
+        write(parse_chunk(&chunk, map_ids)?)?;
+
+        position += (12 + compressed_len) as u64;
+        num_chunks += 1;
+
+        // Report the progress at each percent.
+        let new_progress = position * 100 / len;
+        if new_progress > progress {
+            progress = new_progress;
+            info!("Imported {}% of the storage file", progress);
+        }
+    }
+
+    Ok(())
+}
+
+/// Records the given schema version of an outdated storage file, so the imported storage is migrated when it is next opened.
+fn record_schema_version<F>(schema_version: u32, map_ids: &[MapId], write: &mut F) -> Result<()>
+where
+    F: FnMut(Vec<Entry>) -> Result<()>,
+{
+    if schema_version >= SCHEMA_VERSION {
+        return Ok(());
+    }
+    // The migration of a subset of the maps would leave the other maps in an inconsistent schema.
+    if MapId::ALL.iter().any(|map_id| !map_ids.contains(map_id)) {
+        bail!("Storage file schema version {} is outdated, and its maps can only be imported together", schema_version);
+    }
+    write(vec![(MapId::Schema, bincode::serialize(&())?, bincode::serialize(&schema_version)?)])
+}
+
+/// Parses the entries of the given maps from the given chunk.
+fn parse_chunk(chunk: &[u8], map_ids: &[MapId]) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    let mut chunk = chunk;
+
+    while !chunk.is_empty() {
+        let map_id = u16::from_le_bytes(take(&mut chunk, 2)?.try_into()?);
+        let key_len = u32::from_le_bytes(take(&mut chunk, 4)?.try_into()?) as usize;
+        let key = take(&mut chunk, key_len)?;
+        let value_len = u32::from_le_bytes(take(&mut chunk, 4)?.try_into()?) as usize;
+        let value = take(&mut chunk, value_len)?;
+
+        if let Some(map_id) = MapId::from_u16(map_id).filter(|map_id| map_ids.contains(map_id)) {
+            entries.push((map_id, key.to_vec(), value.to_vec()));
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Reads the storage file in the legacy format, where each key is prefixed with its context and map ID.
+fn read_legacy_export<R: Read, F>(mut reader: R, len: u64, network_id: u16, map_ids: &[MapId], mut write: F) -> Result<()>
+where
+    F: FnMut(Vec<Entry>) -> Result<()>,
+{
+    let mut position = 0;
+    let mut entries = Vec::new();
+    let mut entries_size = 0;
+
+    // Reads a length-prefixed field, whose length is bounded by the remaining size of the storage file.
+    let read_field = |reader: &mut R, position: u64| -> Result<Vec<u8>> {
+        let field_len = read_u32(reader)? as u64;
+        if field_len > len.saturating_sub(position) {
+            bail!("Invalid length in the legacy storage file");
+        }
+        let mut field = vec![0u8; field_len as usize];
+        reader.read_exact(&mut field)?;
+        Ok(field)
+    };
+
+    while position < len {
+        let key = read_field(&mut reader, position)?;
+        let value = read_field(&mut reader, position + 4 + key.len() as u64)?;
+        position += (8 + key.len() + value.len()) as u64;
+
+        if key.len() < PREFIX_LEN {
+            bail!("Invalid key in the legacy storage file");
+        }
+        // The context of each key is the network ID.
+        let key_network_id = u16::from_le_bytes(key[0..2].try_into()?);
+        if key_network_id != network_id {
+            bail!("Storage file is for network {}, expected network {}", key_network_id, network_id);
+        }
+        let map_id = u16::from_le_bytes(key[PREFIX_LEN - 2..PREFIX_LEN].try_into()?);
+        if let Some(map_id) = MapId::from_u16(map_id).filter(|map_id| map_ids.contains(map_id)) {
+            entries_size += key.len() + value.len();
+            entries.push((map_id, key[PREFIX_LEN..].to_vec(), value));
+        }
+
+        if entries_size >= CHUNK_SIZE {
+            write(std::mem::take(&mut entries))?;
+            entries_size = 0;
+        }
+    }
+    write(entries)
+}
+
+/// Reads a little-endian `u32` from the given reader.
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Splits the given number of bytes off the front of the given slice.
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if bytes.len() < len {
+        bail!("Unexpected end of a chunk in the storage file");
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
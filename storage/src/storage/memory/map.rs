@@ -0,0 +1,304 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+
+use rand::{thread_rng, Rng};
+use std::fmt;
+
+#[derive(Clone)]
+pub struct DataMap<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> {
+    pub(super) storage: MemoryStorage,
+    pub(super) context: Vec<u8>,
+    pub(super) map_id: MapId,
+    pub(super) _phantom: PhantomData<(K, V)>,
+}
+
+impl<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> DataMap<K, V> {
+    #[inline]
+    fn create_prefixed_key<Q>(&self, key: &Q) -> Result<Vec<u8>>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let mut raw_key = self.context.clone();
+        serialize_key(self.map_id, key, &mut raw_key)?;
+
+        Ok(raw_key)
+    }
+
+    fn get_raw<Q>(&self, key: &Q) -> Result<Option<Vec<u8>>>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let raw_key = self.create_prefixed_key(key)?;
+        Ok(self.storage.entries.read().get(&raw_key).cloned())
+    }
+
+    /// Returns a copy of the raw key-value pairs in the given range, in the order of the iteration.
+    fn entries(&self, range: &KeyRange) -> Vec<(Vec<u8>, Vec<u8>)> {
+        entries_in_range(&self.storage.entries.read(), range)
+    }
+
+    /// Returns the value for the given key from the given snapshot of the storage, if it exists.
+    pub(crate) fn get_at<Q>(&self, key: &Q, snapshot: &Snapshot) -> Result<Option<V>>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let raw_key = self.create_prefixed_key(key)?;
+        match snapshot.get(&raw_key) {
+            Some(bytes) => Ok(Some(bincode::deserialize(bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the key-value pairs in the given range of keys from the given snapshot of the storage.
+    pub(crate) fn range_at<Q, R>(&self, range: R, snapshot: &Snapshot) -> Result<Vec<(K, V)>>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let range = KeyRange::new(&self.context, self.map_id, range, false)?;
+        Ok(Iter::new(entries_in_range(snapshot, &range), self.map_id).collect())
+    }
+
+    /// Returns the raw key-value pairs in the map, without the prefix of the keys.
+    pub(crate) fn raw_entries(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        self.entries(&KeyRange::full(&self.context, false))
+            .into_iter()
+            .map(|(key, value)| (key[PREFIX_LEN..].to_vec(), value))
+            .collect()
+    }
+
+    /// Returns `true` if the map holds any key-value pair.
+    pub(crate) fn has_entries(&self) -> bool {
+        let range = KeyRange::full(&self.context, false);
+        let entries = self.storage.entries.read();
+        let mut raw_iter = entries.range::<_, [u8]>((range.start(), Bound::Unbounded));
+        range.next_in_range(&mut raw_iter).is_some()
+    }
+
+    /// Inserts the given raw key-value pair into the map, where the key is without its prefix.
+    pub(crate) fn insert_raw(&self, key: &[u8], value: &[u8], batch: Option<usize>) -> Result<()> {
+        self.apply(Operation::Insert([&self.context, key].concat(), value.to_vec()), batch)
+    }
+
+    /// Removes the given raw key from the map, where the key is without its prefix.
+    pub(crate) fn remove_raw(&self, key: &[u8], batch: Option<usize>) -> Result<()> {
+        self.apply(Operation::Remove([&self.context, key].concat()), batch)
+    }
+
+    /// Applies the given operation, or defers it to the batch with the given id.
+    fn apply(&self, operation: Operation, batch: Option<usize>) -> Result<()> {
+        if let Some(batch_id) = batch {
+            self.storage.batches.lock().entry(batch_id).or_default().push(operation);
+            Ok(())
+        } else {
+            self.storage.write(vec![operation])
+        }
+    }
+
+    /// Returns a snapshot of the storage the map was opened from, at its current state.
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        self.storage.snapshot()
+    }
+
+    /// Returns the statistics of the storage the map was opened from.
+    pub(crate) fn storage_stats(&self) -> Result<StorageStats> {
+        self.storage.stats()
+    }
+
+    #[cfg(any(test, feature = "test"))]
+    pub fn storage(&self) -> &MemoryStorage {
+        &self.storage
+    }
+}
+
+/// Returns a copy of the raw key-value pairs of the given entries in the given range, in the order of the iteration.
+fn entries_in_range(entries: &OrdMap<Vec<u8>, Vec<u8>>, range: &KeyRange) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let mut raw_iter: Box<dyn Iterator<Item = (&Vec<u8>, &Vec<u8>)>> = match range.is_reverse() {
+        false => Box::new(entries.range::<_, [u8]>((range.start(), Bound::Unbounded))),
+        true => Box::new(entries.range::<_, [u8]>((Bound::Unbounded, range.end())).rev()),
+    };
+
+    let mut copy = Vec::new();
+    while let Some((key, value)) = range.next_in_range(&mut raw_iter) {
+        copy.push((key.clone(), value.clone()));
+    }
+    copy
+}
+
+impl<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> fmt::Debug for DataMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DataMap").field("context", &self.context).finish()
+    }
+}
+
+impl<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> Map<'a, K, V> for DataMap<K, V> {
+    type Iterator = Iter<'a, K, V>;
+    type Keys = Keys<'a, K>;
+    type Values = Values<'a, V>;
+
+    ///
+    /// Returns `true` if the given key exists in the map.
+    ///
+    fn contains_key<Q>(&self, key: &Q) -> Result<bool>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let raw_key = self.create_prefixed_key(key)?;
+        Ok(self.storage.entries.read().contains_key(&raw_key))
+    }
+
+    ///
+    /// Returns the value for the given key from the map, if it exists.
+    ///
+    fn get<Q>(&self, key: &Q) -> Result<Option<V>>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        match self.get_raw(key) {
+            Ok(Some(bytes)) => Ok(Some(bincode::deserialize(&bytes)?)),
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    ///
+    /// Inserts the given key-value pair into the map. Can be paired with a numeric
+    /// batch id, which defers the operation until `execute_batch` is called using
+    /// the same id.
+    ///
+    fn insert<Q>(&self, key: &Q, value: &V, batch: Option<usize>) -> Result<()>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let raw_key = self.create_prefixed_key(key)?;
+        let raw_value = bincode::serialize(value)?;
+
+        self.apply(Operation::Insert(raw_key, raw_value), batch)
+    }
+
+    ///
+    /// Removes the key-value pair for the given key from the map. Can be paired with a
+    /// numeric batch id, which defers the operation until `execute_batch` is called using
+    /// the same id.
+    ///
+    fn remove<Q>(&self, key: &Q, batch: Option<usize>) -> Result<()>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let raw_key = self.create_prefixed_key(key)?;
+
+        self.apply(Operation::Remove(raw_key), batch)
+    }
+
+    ///
+    /// Returns an iterator visiting each key-value pair in the map.
+    ///
+    fn iter(&'a self) -> Self::Iterator {
+        Iter::new(self.entries(&KeyRange::full(&self.context, false)), self.map_id)
+    }
+
+    ///
+    /// Returns an iterator visiting each key-value pair in the map, in reverse order.
+    ///
+    fn iter_rev(&'a self) -> Self::Iterator {
+        Iter::new(self.entries(&KeyRange::full(&self.context, true)), self.map_id)
+    }
+
+    ///
+    /// Returns an iterator visiting each key-value pair in the given range of keys.
+    ///
+    fn range<Q, R>(&'a self, range: R) -> Result<Self::Iterator>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let range = KeyRange::new(&self.context, self.map_id, range, false)?;
+        Ok(Iter::new(self.entries(&range), self.map_id))
+    }
+
+    ///
+    /// Returns an iterator over each key in the map.
+    ///
+    fn keys(&'a self) -> Self::Keys {
+        Keys::new(self.entries(&KeyRange::full(&self.context, false)), self.map_id)
+    }
+
+    ///
+    /// Returns an iterator over each value in the map.
+    ///
+    fn values(&'a self) -> Self::Values {
+        Values::new(self.entries(&KeyRange::full(&self.context, false)))
+    }
+
+    ///
+    /// Performs a refresh operation for implementations of `Map` that perform periodic operations.
+    /// This method is implemented here to catch up a read-only storage to its writable storage.
+    /// Returns `true` if the sequence number of the database has increased.
+    ///
+    fn refresh(&self) -> bool {
+        self.storage.catch_up()
+    }
+
+    ///
+    /// Prepares an atomic batch of writes and returns its numeric id which can later be used to include
+    /// operations within it. `execute_batch` has to be called in order for any of the writes to actually
+    /// take place.
+    ///
+    fn prepare_batch(&self) -> usize {
+        let mut id = thread_rng().gen();
+
+        while self.storage.batches.lock().contains_key(&id) {
+            id = thread_rng().gen();
+        }
+
+        id
+    }
+
+    ///
+    /// Atomically executes a write batch with the given id.
+    ///
+    fn execute_batch(&self, batch: usize) -> Result<()> {
+        // Bind the batch first, to release the lock before writing.
+        let operations = self.storage.batches.lock().remove(&batch);
+        if let Some(operations) = operations {
+            self.storage.write(operations)
+        } else {
+            bail!("There is no pending storage batch with id = {}", batch);
+        }
+    }
+
+    ///
+    /// Discards a write batch with the given id.
+    ///
+    fn discard_batch(&self, batch: usize) -> Result<()> {
+        if self.storage.batches.lock().remove(&batch).is_none() {
+            bail!("Attempted to discard a non-existent storage batch (id = {})", batch)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,283 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+mod iterator;
+pub use iterator::*;
+
+mod keys;
+pub use keys::*;
+
+mod map;
+pub use map::*;
+
+mod values;
+pub use values::*;
+
+use crate::storage::{
+    deserialize_key,
+    export::{read_export, Compression, ExportHeader, ExportWriter},
+    serialize_key,
+    KeyRange,
+    Map,
+    MapId,
+    MapStats,
+    Storage,
+    StorageStats,
+    PREFIX_LEN,
+};
+
+use anyhow::{bail, Result};
+use im::OrdMap;
+use parking_lot::{Mutex, RwLock};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        Weak,
+    },
+};
+
+/// The entries of an in-memory database. They are kept in a persistent map, so a snapshot of them
+/// is taken in constant time, and a write only copies the nodes it modifies while a snapshot exists.
+type Entries = Arc<RwLock<OrdMap<Vec<u8>, Vec<u8>>>>;
+
+/// A snapshot of the entries of an in-memory database, at the point in time it was taken.
+pub type Snapshot = OrdMap<Vec<u8>, Vec<u8>>;
+
+/// The in-memory databases of this process, along with the paths they were opened at.
+/// A database is dropped along with the last of its instances.
+static DATABASES: Mutex<Vec<(PathBuf, Weak<Database>)>> = parking_lot::const_mutex(Vec::new());
+
+/// The contents of an in-memory database, shared by the instances opened at the same path.
+#[derive(Default)]
+struct Database {
+    entries: Entries,
+    sequence_number: AtomicU64,
+}
+
+/// A write operation on an in-memory database.
+enum Operation {
+    Insert(Vec<u8>, Vec<u8>),
+    Remove(Vec<u8>),
+}
+
+///
+/// An instance of an in-memory database.
+///
+/// Instances opened at the same path share their contents while any of them remains open.
+/// A read-only instance observes a snapshot of the contents, which is caught up to its writable
+/// instance with `Map::refresh`, matching the semantics of a secondary RocksDB instance.
+///
+#[derive(Clone)]
+pub struct MemoryStorage {
+    database: Arc<Database>,
+    entries: Entries,
+    context: Vec<u8>,
+    batches: Arc<Mutex<HashMap<usize, Vec<Operation>>>>,
+    is_read_only: bool,
+    sequence_number: Arc<AtomicU64>,
+}
+
+impl MemoryStorage {
+    /// Returns the network ID of the storage, which is its context.
+    fn network_id(&self) -> u16 {
+        u16::from_le_bytes([self.context[0], self.context[1]])
+    }
+
+    /// Atomically applies the given operations to the database.
+    fn write(&self, operations: Vec<Operation>) -> Result<()> {
+        if self.is_read_only {
+            bail!("Attempted to write to a read-only in-memory storage");
+        }
+
+        let mut entries = self.entries.write();
+        for operation in operations {
+            match operation {
+                Operation::Insert(key, value) => entries.insert(key, value),
+                Operation::Remove(key) => entries.remove(&key),
+            };
+        }
+        self.database.sequence_number.fetch_add(1, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    /// Returns a snapshot of the current entries of the database.
+    fn snapshot(&self) -> Snapshot {
+        self.entries.read().clone()
+    }
+
+    /// Catches up a read-only instance to its writable instance.
+    /// Returns `true` if the sequence number of the database has increased.
+    fn catch_up(&self) -> bool {
+        if !self.is_read_only {
+            return false;
+        }
+
+        let entries = self.database.entries.read();
+        let sequence_number = self.database.sequence_number.load(Ordering::SeqCst);
+        if sequence_number > self.sequence_number.load(Ordering::SeqCst) {
+            *self.entries.write() = entries.clone();
+            self.sequence_number.store(sequence_number, Ordering::SeqCst);
+            return true;
+        }
+        false
+    }
+}
+
+impl Storage for MemoryStorage {
+    ///
+    /// Opens storage at the given `path` and `context`, without checking its schema version.
+    ///
+    fn open_unchecked<P: AsRef<Path>>(path: P, context: u16, is_read_only: bool) -> Result<Self> {
+        let context = context.to_le_bytes().to_vec();
+
+        let path = path.as_ref().to_path_buf();
+        let database = {
+            let mut databases = DATABASES.lock();
+            // Remove the databases whose instances have all been dropped.
+            databases.retain(|(_, database)| database.strong_count() > 0);
+            match databases
+                .iter()
+                .filter(|(database_path, _)| *database_path == path)
+                .find_map(|(_, database)| database.upgrade())
+            {
+                Some(database) => database,
+                None if is_read_only => bail!("There is no in-memory storage at {}", path.display()),
+                None => {
+                    let database = Arc::new(Database::default());
+                    databases.push((path, Arc::downgrade(&database)));
+                    database
+                }
+            }
+        };
+
+        // A read-only instance starts from a snapshot of the current contents.
+        let (entries, sequence_number) = match is_read_only {
+            true => {
+                let entries = database.entries.read();
+                let sequence_number = database.sequence_number.load(Ordering::SeqCst);
+                (Arc::new(RwLock::new(entries.clone())), sequence_number)
+            }
+            false => (database.entries.clone(), 0),
+        };
+
+        Ok(MemoryStorage {
+            database,
+            entries,
+            context,
+            batches: Default::default(),
+            is_read_only,
+            sequence_number: Arc::new(AtomicU64::new(sequence_number)),
+        })
+    }
+
+    ///
+    /// Opens a map with the given `context` from storage.
+    ///
+    fn open_map<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned>(
+        &self,
+        map_id: MapId,
+    ) -> Result<crate::storage::DataMap<K, V>> {
+        // Convert the new context into bytes.
+        let new_context = (map_id as u16).to_le_bytes();
+
+        // Combine contexts to create a new scope.
+        let mut context_bytes = self.context.clone();
+        context_bytes.extend_from_slice(&new_context);
+
+        Ok(crate::storage::DataMap::Memory(DataMap {
+            storage: self.clone(),
+            context: context_bytes,
+            map_id,
+            _phantom: PhantomData,
+        }))
+    }
+
+    ///
+    /// Imports the given maps from a file with the given path to reconstruct storage.
+    ///
+    fn import_maps<P: AsRef<Path>>(&self, path: P, map_ids: &[MapId]) -> Result<()> {
+        read_export(path, self.network_id(), map_ids, |entries| {
+            let operations = entries
+                .into_iter()
+                .map(|(map_id, key, value)| {
+                    let mut raw_key = self.context.clone();
+                    raw_key.extend_from_slice(&(map_id as u16).to_le_bytes());
+                    raw_key.extend_from_slice(&key);
+                    Operation::Insert(raw_key, value)
+                })
+                .collect();
+            self.write(operations)
+        })
+    }
+
+    ///
+    /// Exports the current state of the given maps to a single file at the specified location.
+    ///
+    fn export_maps<P: AsRef<Path>>(&self, path: P, map_ids: &[MapId], compression: Compression) -> Result<()> {
+        let header = ExportHeader::new(self, self.network_id(), map_ids, compression)?;
+        let mut writer = ExportWriter::create(path, &header)?;
+
+        let entries = self.entries.read();
+        for map_id in map_ids {
+            let mut prefix = self.context.clone();
+            prefix.extend_from_slice(&(*map_id as u16).to_le_bytes());
+
+            for (key, value) in entries.range(prefix.clone()..).take_while(|(key, _)| key.starts_with(&prefix)) {
+                writer.write_entry(*map_id, &key[PREFIX_LEN..], value)?;
+            }
+        }
+
+        writer.finish()
+    }
+
+    ///
+    /// Returns the number of keys and size of each map, as an in-memory storage has no files.
+    ///
+    fn stats(&self) -> Result<StorageStats> {
+        let entries = self.entries.read();
+        let maps = MapId::ALL
+            .iter()
+            .map(|map_id| {
+                let mut prefix = self.context.clone();
+                prefix.extend_from_slice(&(*map_id as u16).to_le_bytes());
+
+                let mut stats = MapStats {
+                    map_id: *map_id,
+                    num_keys: 0,
+                    size: 0,
+                };
+                for (key, value) in entries.range(prefix.clone()..).take_while(|(key, _)| key.starts_with(&prefix)) {
+                    stats.num_keys += 1;
+                    stats.size += (key.len() + value.len()) as u64;
+                }
+                stats
+            })
+            .collect();
+
+        Ok(StorageStats {
+            maps,
+            ..Default::default()
+        })
+    }
+}
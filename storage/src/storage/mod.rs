@@ -14,12 +14,26 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+mod backup;
+pub use backup::*;
+
+mod data_map;
+pub use data_map::*;
+
+pub mod export;
+
+pub mod memory;
+
 #[cfg(feature = "rocks")]
 pub mod rocksdb;
-#[cfg(feature = "rocks")]
-pub type DataMap<K, V> = crate::storage::rocksdb::DataMap<K, V>;
-#[cfg(feature = "rocks")]
-pub use crate::storage::rocksdb::MapId;
+
+pub mod schema;
+
+mod stats;
+pub use stats::*;
 
 pub mod traits;
 pub use traits::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,99 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use super::RocksDB;
+use crate::storage::BackupInfo;
+
+use anyhow::{anyhow, bail, Result};
+use rocksdb::backup::{BackupEngine, BackupEngineInfo, BackupEngineOptions, RestoreOptions};
+use std::path::Path;
+
+impl RocksDB {
+    ///
+    /// Creates an incremental backup of the storage in the given directory, while it remains in use,
+    /// and discards all but the given number of most recent backups, if any.
+    ///
+    pub fn backup<P: AsRef<Path>>(&self, path: P, num_backups_to_keep: Option<usize>) -> Result<BackupInfo> {
+        // A backup requires the primary instance, as it disables the deletion of files while they are copied.
+        if self.is_read_only {
+            bail!("Unable to back up a read-only storage instance");
+        }
+
+        let mut backup_engine = BackupEngine::open(&BackupEngineOptions::default(), path.as_ref())?;
+        // Flush the memtables beforehand, so the backup is self-contained in its SST files.
+        backup_engine.create_new_backup_flush(&*self.rocksdb, true)?;
+        if let Some(num_backups_to_keep) = num_backups_to_keep {
+            backup_engine.purge_old_backups(num_backups_to_keep.max(1))?;
+        }
+
+        // Ensure the files of the new backup are intact.
+        let backup = latest_backup(&backup_engine).ok_or_else(|| anyhow!("Missing the backup in {}", path.as_ref().display()))?;
+        backup_engine.verify_backup(backup.backup_id)?;
+
+        Ok(backup)
+    }
+
+    ///
+    /// Returns the backups in the given directory, from the oldest to the most recent.
+    ///
+    pub fn backups<P: AsRef<Path>>(path: P) -> Result<Vec<BackupInfo>> {
+        if !path.as_ref().exists() {
+            return Ok(vec![]);
+        }
+
+        let backup_engine = BackupEngine::open(&BackupEngineOptions::default(), path.as_ref())?;
+        let mut backups: Vec<_> = backup_engine.get_backup_info().iter().map(to_backup_info).collect();
+        backups.sort_by_key(|backup| backup.backup_id);
+        Ok(backups)
+    }
+
+    ///
+    /// Restores the backup with the given ID, or the most recent backup, from the given directory
+    /// to storage at the given path. The storage must not be in use, as its contents are replaced.
+    ///
+    pub fn restore_backup<P: AsRef<Path>, Q: AsRef<Path>>(backup_path: P, path: Q, backup_id: Option<u32>) -> Result<BackupInfo> {
+        let backup = match backup_id {
+            Some(backup_id) => Self::backups(&backup_path)?.into_iter().find(|backup| backup.backup_id == backup_id),
+            None => Self::backups(&backup_path)?.pop(),
+        };
+        let backup = match backup {
+            Some(backup) => backup,
+            None => bail!("There is no such backup in {}", backup_path.as_ref().display()),
+        };
+
+        let mut backup_engine = BackupEngine::open(&BackupEngineOptions::default(), backup_path.as_ref())?;
+        backup_engine.verify_backup(backup.backup_id)?;
+        // The write-ahead log is kept in the directory of the storage itself.
+        backup_engine.restore_from_backup(path.as_ref(), path.as_ref(), &RestoreOptions::default(), backup.backup_id)?;
+
+        Ok(backup)
+    }
+}
+
+/// Returns the most recent backup of the given backup engine, if any.
+fn latest_backup(backup_engine: &BackupEngine) -> Option<BackupInfo> {
+    backup_engine.get_backup_info().iter().map(to_backup_info).max_by_key(|backup| backup.backup_id)
+}
+
+/// Converts the information of a backup from its RocksDB representation.
+fn to_backup_info(info: &BackupEngineInfo) -> BackupInfo {
+    BackupInfo {
+        backup_id: info.backup_id,
+        timestamp: info.timestamp,
+        size: info.size,
+        num_files: info.num_files,
+    }
+}
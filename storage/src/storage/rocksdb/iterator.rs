@@ -19,13 +19,17 @@ use super::*;
 /// An iterator over all key-value pairs in a data map.
 pub struct Iter<'a, K, V> {
     db_iter: rocksdb::DBIterator<'a>,
+    range: KeyRange,
+    map_id: MapId,
     _phantom: PhantomData<(K, V)>,
 }
 
 impl<'a, K: DeserializeOwned, V: DeserializeOwned> Iter<'a, K, V> {
-    pub(super) fn new(db_iter: rocksdb::DBIterator<'a>) -> Self {
+    pub(super) fn new(db_iter: rocksdb::DBIterator<'a>, range: KeyRange, map_id: MapId) -> Self {
         Self {
             db_iter,
+            range,
+            map_id,
             _phantom: PhantomData,
         }
     }
@@ -35,8 +39,8 @@ impl<'a, K: DeserializeOwned, V: DeserializeOwned> Iterator for Iter<'a, K, V> {
     type Item = (K, V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (key, value) = self.db_iter.next()?;
-        let key = bincode::deserialize(&key[PREFIX_LEN..]).ok()?;
+        let (key, value) = self.range.next_in_range(&mut self.db_iter)?;
+        let key = deserialize_key(self.map_id, &key[self.range.prefix_len()..]).ok()?;
         let value = bincode::deserialize(&value).ok()?;
 
         Some((key, value))
@@ -19,13 +19,17 @@ use super::*;
 /// An iterator over the keys of a prefix.
 pub struct Keys<'a, K> {
     db_iter: rocksdb::DBIterator<'a>,
+    range: KeyRange,
+    map_id: MapId,
     _phantom: PhantomData<K>,
 }
 
 impl<'a, K: DeserializeOwned> Keys<'a, K> {
-    pub(crate) fn new(db_iter: rocksdb::DBIterator<'a>) -> Self {
+    pub(crate) fn new(db_iter: rocksdb::DBIterator<'a>, range: KeyRange, map_id: MapId) -> Self {
         Self {
             db_iter,
+            range,
+            map_id,
             _phantom: PhantomData,
         }
     }
@@ -35,8 +39,8 @@ impl<'a, K: DeserializeOwned> Iterator for Keys<'a, K> {
     type Item = K;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (key, _) = self.db_iter.next()?;
-        let key = bincode::deserialize(&key[PREFIX_LEN..]).ok()?;
+        let (key, _) = self.range.next_in_range(&mut self.db_iter)?;
+        let key = deserialize_key(self.map_id, &key[self.range.prefix_len()..]).ok()?;
 
         Some(key)
     }
@@ -16,33 +16,15 @@
 
 use super::*;
 
-pub const PREFIX_LEN: usize = 4; // N::NETWORK_ID (u16) + MapId (u16)
-
 use anyhow::bail;
 use rand::{thread_rng, Rng};
 use std::fmt;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-#[repr(u16)]
-pub enum MapId {
-    BlockHeaders = 0,
-    BlockHeights,
-    BlockTransactions,
-    Commitments,
-    LedgerRoots,
-    Records,
-    SerialNumbers,
-    Transactions,
-    Transitions,
-    Shares,
-    #[cfg(test)]
-    Test,
-}
-
 #[derive(Clone)]
 pub struct DataMap<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> {
     pub(super) storage: RocksDB,
     pub(super) context: Vec<u8>,
+    pub(super) map_id: MapId,
     pub(super) _phantom: PhantomData<(K, V)>,
 }
 
@@ -54,7 +36,7 @@ impl<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> DataMap<K
         Q: Serialize + ?Sized,
     {
         let mut raw_key = self.context.clone();
-        bincode::serialize_into(&mut raw_key, &key)?;
+        serialize_key(self.map_id, key, &mut raw_key)?;
 
         Ok(raw_key)
     }
@@ -65,21 +47,147 @@ impl<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> DataMap<K
         Q: Serialize + ?Sized,
     {
         let raw_key = self.create_prefixed_key(key)?;
-        match self.storage.rocksdb.get_pinned(&raw_key)? {
+        match self.storage.rocksdb.get_pinned_cf(self.column_family(), &raw_key)? {
             Some(data) => Ok(Some(data)),
             None => Ok(None),
         }
     }
 
+    /// Returns the column family of the map.
+    fn column_family(&self) -> &rocksdb::ColumnFamily {
+        // The column family exists, as it is checked when the map is opened.
+        self.storage.column_family(self.map_id).expect("Missing the column family of a data map")
+    }
+
+    /// Returns an iterator over the raw key-value pairs in the map, starting at the first raw key in the given range.
+    fn raw_iter(&self, range: &KeyRange) -> rocksdb::DBIterator<'_> {
+        self.storage
+            .rocksdb
+            .iterator_cf_opt(self.column_family(), iterator_options(range), iterator_mode(range))
+    }
+
+    /// Returns the value for the given key from the given snapshot of the storage, if it exists.
+    pub(crate) fn get_at<Q>(&self, key: &Q, snapshot: &Snapshot) -> Result<Option<V>>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let raw_key = self.create_prefixed_key(key)?;
+        let mut options = rocksdb::ReadOptions::default();
+        options.set_snapshot(snapshot);
+        match self.storage.rocksdb.get_pinned_cf_opt(self.column_family(), &raw_key, &options)? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the key-value pairs in the given range of keys from the given snapshot of the storage.
+    pub(crate) fn range_at<Q, R>(&self, range: R, snapshot: &Snapshot) -> Result<Vec<(K, V)>>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let range = KeyRange::new(&self.context, self.map_id, range, false)?;
+        let mut options = iterator_options(&range);
+        options.set_snapshot(snapshot);
+        // The iterator is consumed here, while the snapshot it reads from is borrowed.
+        let db_iter = self
+            .storage
+            .rocksdb
+            .iterator_cf_opt(self.column_family(), options, iterator_mode(&range));
+        Ok(Iter::new(db_iter, range, self.map_id).collect())
+    }
+
+    /// Returns the raw key-value pairs in the map, without the prefix of the keys.
+    pub(crate) fn raw_entries(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let range = KeyRange::full(&self.context, false);
+        let mut iterator = self.raw_iter(&range);
+
+        let mut entries = Vec::new();
+        while let Some((key, value)) = range.next_in_range(&mut iterator) {
+            entries.push((key[CONTEXT_LEN..].to_vec(), value.to_vec()));
+        }
+        entries
+    }
+
+    /// Returns `true` if the map holds any key-value pair.
+    pub(crate) fn has_entries(&self) -> bool {
+        let range = KeyRange::full(&self.context, false);
+        range.next_in_range(&mut self.raw_iter(&range)).is_some()
+    }
+
+    /// Inserts the given raw key-value pair into the map, where the key is without its prefix.
+    pub(crate) fn insert_raw(&self, key: &[u8], value: &[u8], batch: Option<usize>) -> Result<()> {
+        let raw_key = [&self.context, key].concat();
+
+        if let Some(batch_id) = batch {
+            self.storage.batches.lock().entry(batch_id).or_default().put_cf(self.column_family(), &raw_key, value);
+        } else {
+            self.storage.rocksdb.put_cf(self.column_family(), &raw_key, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the given raw key from the map, where the key is without its prefix.
+    pub(crate) fn remove_raw(&self, key: &[u8], batch: Option<usize>) -> Result<()> {
+        let raw_key = [&self.context, key].concat();
+
+        if let Some(batch_id) = batch {
+            self.storage.batches.lock().entry(batch_id).or_default().delete_cf(self.column_family(), &raw_key);
+        } else {
+            self.storage.rocksdb.delete_cf(self.column_family(), &raw_key)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns a snapshot of the storage the map was opened from, at its current state.
+    pub(crate) fn snapshot(&self) -> Snapshot<'_> {
+        self.storage.rocksdb.snapshot()
+    }
+
+    /// Returns the statistics of the storage the map was opened from.
+    pub(crate) fn storage_stats(&self) -> Result<StorageStats> {
+        self.storage.stats()
+    }
+
+    /// Creates an incremental backup of the storage the map was opened from, in the given directory.
+    pub(crate) fn backup(&self, path: &Path, num_backups_to_keep: Option<usize>) -> Result<BackupInfo> {
+        self.storage.backup(path, num_backups_to_keep)
+    }
+
     #[cfg(any(test, feature = "test"))]
     pub fn storage(&self) -> &RocksDB {
         &self.storage
     }
 }
 
+/// Returns the mode of an iteration over the raw keys in the given range.
+fn iterator_mode(range: &KeyRange) -> rocksdb::IteratorMode<'_> {
+    match (range.is_reverse(), range.start(), range.end()) {
+        (false, Bound::Included(start) | Bound::Excluded(start), _) => rocksdb::IteratorMode::From(start, rocksdb::Direction::Forward),
+        (true, _, Bound::Included(end) | Bound::Excluded(end)) => rocksdb::IteratorMode::From(end, rocksdb::Direction::Reverse),
+        (false, Bound::Unbounded, _) => rocksdb::IteratorMode::Start,
+        (true, _, Bound::Unbounded) => rocksdb::IteratorMode::End,
+    }
+}
+
+/// Returns the options of an iteration over the raw keys in the given range.
+fn iterator_options(range: &KeyRange) -> rocksdb::ReadOptions {
+    // The upper bound of a reverse iteration may lie beyond the prefix, which a prefix seek is unable to reach.
+    let mut options = rocksdb::ReadOptions::default();
+    options.set_total_order_seek(range.is_reverse());
+    options
+}
+
 impl<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> fmt::Debug for DataMap<K, V> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("DataMap").field("context", &self.context).finish()
+        f.debug_struct("DataMap")
+            .field("context", &self.context)
+            .field("map_id", &self.map_id)
+            .finish()
     }
 }
 
@@ -128,9 +236,9 @@ impl<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> Map<'
         let raw_value = bincode::serialize(value)?;
 
         if let Some(batch_id) = batch {
-            self.storage.batches.lock().entry(batch_id).or_default().put(&raw_key, &raw_value);
+            self.storage.batches.lock().entry(batch_id).or_default().put_cf(self.column_family(), &raw_key, &raw_value);
         } else {
-            self.storage.rocksdb.put(&raw_key, &raw_value)?;
+            self.storage.rocksdb.put_cf(self.column_family(), &raw_key, &raw_value)?;
         }
 
         Ok(())
@@ -149,9 +257,9 @@ impl<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> Map<'
         let raw_key = self.create_prefixed_key(key)?;
 
         if let Some(batch_id) = batch {
-            self.storage.batches.lock().entry(batch_id).or_default().delete(&raw_key);
+            self.storage.batches.lock().entry(batch_id).or_default().delete_cf(self.column_family(), &raw_key);
         } else {
-            self.storage.rocksdb.delete(&raw_key)?;
+            self.storage.rocksdb.delete_cf(self.column_family(), &raw_key)?;
         }
 
         Ok(())
@@ -161,21 +269,45 @@ impl<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> Map<'
     /// Returns an iterator visiting each key-value pair in the map.
     ///
     fn iter(&'a self) -> Self::Iterator {
-        Iter::new(self.storage.rocksdb.prefix_iterator(&self.context))
+        let range = KeyRange::full(&self.context, false);
+        Iter::new(self.raw_iter(&range), range, self.map_id)
+    }
+
+    ///
+    /// Returns an iterator visiting each key-value pair in the map, in reverse order.
+    ///
+    fn iter_rev(&'a self) -> Self::Iterator {
+        let range = KeyRange::full(&self.context, true);
+        Iter::new(self.raw_iter(&range), range, self.map_id)
+    }
+
+    ///
+    /// Returns an iterator visiting each key-value pair in the given range of keys.
+    ///
+    fn range<Q, R>(&'a self, range: R) -> Result<Self::Iterator>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let range = KeyRange::new(&self.context, self.map_id, range, false)?;
+        Ok(Iter::new(self.raw_iter(&range), range, self.map_id))
     }
 
     ///
     /// Returns an iterator over each key in the map.
     ///
     fn keys(&'a self) -> Self::Keys {
-        Keys::new(self.storage.rocksdb.prefix_iterator(&self.context))
+        let range = KeyRange::full(&self.context, false);
+        Keys::new(self.raw_iter(&range), range, self.map_id)
     }
 
     ///
     /// Returns an iterator over each value in the map.
     ///
     fn values(&'a self) -> Self::Values {
-        Values::new(self.storage.rocksdb.prefix_iterator(&self.context))
+        let range = KeyRange::full(&self.context, false);
+        Values::new(self.raw_iter(&range), range)
     }
 
     ///
@@ -14,37 +14,55 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+mod backup;
+
 mod iterator;
-use iterator::*;
+pub use iterator::*;
 
 mod keys;
-use keys::*;
+pub use keys::*;
 
 mod map;
 pub use map::*;
 
 mod values;
-use values::*;
+pub use values::*;
 
 #[cfg(test)]
 mod tests;
 
-use crate::storage::{Map, Storage};
+use crate::storage::{
+    deserialize_key,
+    BackupInfo,
+    export::{read_export, Compression, ExportHeader, ExportWriter},
+    serialize_key,
+    KeyRange,
+    Map,
+    MapId,
+    MapStats,
+    Storage,
+    StorageStats,
+    PREFIX_LEN,
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use parking_lot::Mutex;
 use serde::{de::DeserializeOwned, Serialize};
 use std::{
     borrow::Borrow,
     collections::HashMap,
     convert::TryInto,
-    fs::File,
-    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
     marker::PhantomData,
+    ops::{Bound, RangeBounds},
     path::Path,
     sync::Arc,
 };
 
+pub const CONTEXT_LEN: usize = 2; // N::NETWORK_ID (u16)
+
+/// The maximum number of entries moved in a single batch when migrating to column families.
+const MIGRATION_BATCH_SIZE: usize = 10_000;
+
 ///
 /// An instance of a RocksDB database.
 ///
@@ -56,34 +74,61 @@ pub struct RocksDB {
     is_read_only: bool,
 }
 
+/// A snapshot of a RocksDB database, which borrows the database it was taken from.
+pub type Snapshot<'a> = rocksdb::Snapshot<'a>;
+
+impl RocksDB {
+    /// Returns the column family of the given map.
+    fn column_family(&self, map_id: MapId) -> Result<&rocksdb::ColumnFamily> {
+        column_family(&self.rocksdb, map_id)
+    }
+
+    /// Returns the network ID of the storage, which is its context.
+    fn network_id(&self) -> u16 {
+        u16::from_le_bytes([self.context[0], self.context[1]])
+    }
+}
+
 impl Storage for RocksDB {
     ///
-    /// Opens storage at the given `path` and `context`.
+    /// Opens storage at the given `path` and `context`, without checking its schema version.
     ///
-    fn open<P: AsRef<Path>>(path: P, context: u16, is_read_only: bool) -> Result<Self> {
+    fn open_unchecked<P: AsRef<Path>>(path: P, context: u16, is_read_only: bool) -> Result<Self> {
         let context = context.to_le_bytes().to_vec();
 
         // Customize database options.
         let mut options = rocksdb::Options::default();
         options.set_compression_type(rocksdb::DBCompressionType::Lz4);
 
-        // Register the prefix length.
+        // Register the prefix length of the legacy layout, which stores every map in the default column family.
         let prefix_extractor = rocksdb::SliceTransform::create_fixed_prefix(PREFIX_LEN);
         options.set_prefix_extractor(prefix_extractor);
 
+        // Open each map in its own column family.
+        let column_families = MapId::ALL
+            .iter()
+            .map(|map_id| rocksdb::ColumnFamilyDescriptor::new(map_id.name(), column_family_options(*map_id)));
+
         let primary = path.as_ref().to_path_buf();
         let rocksdb = match is_read_only {
             true => {
                 // Construct the directory paths.
                 let reader = path.as_ref().join("reader");
                 // Open a secondary reader for the primary rocksdb.
-                let rocksdb = rocksdb::DB::open_as_secondary(&options, &primary, &reader)?;
+                let rocksdb = rocksdb::DB::open_cf_descriptors_as_secondary(&options, &primary, &reader, column_families)?;
                 Arc::new(rocksdb)
             }
             false => {
                 options.increase_parallelism(2);
                 options.create_if_missing(true);
-                Arc::new(rocksdb::DB::open(&options, &primary)?)
+                options.create_missing_column_families(true);
+
+                let rocksdb = rocksdb::DB::open_cf_descriptors(&options, &primary, column_families)?;
+
+                // Move any maps in the legacy layout into their column families.
+                migrate_to_column_families(&rocksdb)?;
+
+                Arc::new(rocksdb)
             }
         };
 
@@ -98,77 +143,184 @@ impl Storage for RocksDB {
     ///
     /// Opens a map with the given `context` from storage.
     ///
-    fn open_map<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned>(&self, map_id: MapId) -> Result<DataMap<K, V>> {
-        // Convert the new context into bytes.
-        let new_context = (map_id as u16).to_le_bytes();
-
-        // Combine contexts to create a new scope.
-        let mut context_bytes = self.context.clone();
-        context_bytes.extend_from_slice(&new_context);
-
-        Ok(DataMap {
+    fn open_map<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned>(
+        &self,
+        map_id: MapId,
+    ) -> Result<crate::storage::DataMap<K, V>> {
+        // Ensure the column family of the map exists.
+        self.column_family(map_id)?;
+
+        Ok(crate::storage::DataMap::RocksDB(DataMap {
             storage: self.clone(),
-            context: context_bytes,
+            context: self.context.clone(),
+            map_id,
             _phantom: PhantomData,
-        })
+        }))
     }
 
     ///
-    /// Imports a file with the given path to reconstruct storage.
+    /// Imports the given maps from a file with the given path to reconstruct storage.
     ///
-    fn import<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let file = File::open(path)?;
-        let mut reader = BufReader::new(file);
+    fn import_maps<P: AsRef<Path>>(&self, path: P, map_ids: &[MapId]) -> Result<()> {
+        read_export(path, self.network_id(), map_ids, |entries| {
+            let mut batch = rocksdb::WriteBatch::default();
+            for (map_id, key, value) in entries {
+                let mut raw_key = self.context.clone();
+                raw_key.extend_from_slice(&key);
+                batch.put_cf(self.column_family(map_id)?, &raw_key, &value);
+            }
+            Ok(self.rocksdb.write(batch)?)
+        })
+    }
 
-        let len = reader.seek(SeekFrom::End(0))?;
-        reader.rewind()?;
+    ///
+    /// Exports the current state of the given maps to a single file at the specified location.
+    ///
+    fn export_maps<P: AsRef<Path>>(&self, path: P, map_ids: &[MapId], compression: Compression) -> Result<()> {
+        let header = ExportHeader::new(self, self.network_id(), map_ids, compression)?;
+        let mut writer = ExportWriter::create(path, &header)?;
+
+        for map_id in map_ids {
+            let mut iterator = self.rocksdb.raw_iterator_cf(self.column_family(*map_id)?);
+            iterator.seek(&self.context);
+
+            while iterator.valid() {
+                match (iterator.key(), iterator.value()) {
+                    (Some(key), Some(value)) if key.starts_with(&self.context) => {
+                        writer.write_entry(*map_id, &key[CONTEXT_LEN..], value)?;
+                    }
+                    _ => break,
+                }
+                iterator.next();
+            }
+        }
 
-        let mut buf = vec![0u8; 16 * 1024];
+        writer.finish()
+    }
 
-        while reader.stream_position()? < len {
-            reader.read_exact(&mut buf[..4])?;
-            let key_len = u32::from_le_bytes(buf[..4].try_into().unwrap()) as usize;
+    ///
+    /// Returns the approximate number of keys and size of each map, along with the sizes of the files of storage.
+    ///
+    fn stats(&self) -> Result<StorageStats> {
+        let mut stats = StorageStats::default();
+
+        for map_id in MapId::ALL {
+            let column_family = self.column_family(*map_id)?;
+            let property = |name: &str| -> Result<u64> { Ok(self.rocksdb.property_int_value_cf(column_family, name)?.unwrap_or_default()) };
+
+            // The SST files hold the flushed entries, while the memtables hold the rest.
+            let sst_files_size = property("rocksdb.total-sst-files-size")?;
+            stats.maps.push(MapStats {
+                map_id: *map_id,
+                num_keys: property("rocksdb.estimate-num-keys")?,
+                size: sst_files_size + property("rocksdb.size-all-mem-tables")?,
+            });
+            stats.sst_files_size += sst_files_size;
+        }
 
-            if key_len + 4 > buf.len() {
-                buf.resize(key_len + 4, 0);
+        // The write-ahead log files are kept in the directory of the primary instance.
+        for entry in std::fs::read_dir(self.rocksdb.path())? {
+            let entry = entry?;
+            if entry.path().extension().map_or(false, |extension| extension == "log") {
+                stats.wal_files_size += entry.metadata()?.len();
             }
+        }
 
-            reader.read_exact(&mut buf[..key_len + 4])?;
-            let value_len = u32::from_le_bytes(buf[key_len..][..4].try_into().unwrap()) as usize;
-
-            if key_len + value_len > buf.len() {
-                buf.resize(key_len + value_len, 0);
-            }
+        Ok(stats)
+    }
+}
 
-            reader.read_exact(&mut buf[key_len..][..value_len])?;
+/// Returns the column family of the given map.
+fn column_family(rocksdb: &rocksdb::DB, map_id: MapId) -> Result<&rocksdb::ColumnFamily> {
+    rocksdb
+        .cf_handle(map_id.name())
+        .ok_or_else(|| anyhow!("Missing the column family of {:?}", map_id))
+}
 
-            self.rocksdb.put(&buf[..key_len], &buf[key_len..][..value_len])?;
+/// Returns the options of the column family of the given map.
+fn column_family_options(map_id: MapId) -> rocksdb::Options {
+    let mut options = rocksdb::Options::default();
+    options.set_compression_type(rocksdb::DBCompressionType::Lz4);
+
+    // Register the prefix length.
+    let prefix_extractor = rocksdb::SliceTransform::create_fixed_prefix(CONTEXT_LEN);
+    options.set_prefix_extractor(prefix_extractor);
+
+    match map_id {
+        // These maps are large and rarely read, so larger blocks compress better.
+        MapId::BlockTransactions | MapId::Transactions | MapId::Transitions => {
+            let mut table_options = rocksdb::BlockBasedOptions::default();
+            table_options.set_block_size(64 * 1024);
+            options.set_block_based_table_factory(&table_options);
+        }
+        // These maps are small and looked up for every transaction, so they favor point lookups.
+        MapId::SerialNumbers | MapId::Commitments | MapId::LedgerRoots => {
+            options.optimize_for_point_lookup(64);
+            options.set_compression_type(rocksdb::DBCompressionType::None);
         }
+        _ => (),
+    }
 
-        Ok(())
+    options
+}
+
+/// Splits a key of the legacy layout into its map ID and the key of its column family.
+fn split_legacy_key(key: &[u8]) -> Option<(MapId, Vec<u8>)> {
+    if key.len() < PREFIX_LEN {
+        return None;
     }
+    let map_id = MapId::from_u16(u16::from_le_bytes(key[CONTEXT_LEN..PREFIX_LEN].try_into().ok()?))?;
 
-    ///
-    /// Exports the current state of storage to a single file at the specified location.
-    ///
-    fn export<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let file = File::create(path)?;
-        let mut writer = BufWriter::new(file);
+    let mut new_key = key[..CONTEXT_LEN].to_vec();
+    new_key.extend_from_slice(&key[PREFIX_LEN..]);
+    Some((map_id, new_key))
+}
 
-        let mut iterator = self.rocksdb.raw_iterator();
-        iterator.seek_to_first();
+/// Moves the maps of the legacy layout, which stores every map in the default column family,
+/// into their own column families. Each batch moves its entries atomically, so an interrupted
+/// migration resumes on the next open. The migration fails on a key that belongs to no map.
+fn migrate_to_column_families(rocksdb: &rocksdb::DB) -> Result<()> {
+    // Iterate over every key, as the prefix extractor of the legacy layout bounds iterators to a single prefix.
+    let mut options = rocksdb::ReadOptions::default();
+    options.set_total_order_seek(true);
 
-        while iterator.valid() {
-            if let (Some(key), Some(value)) = (iterator.key(), iterator.value()) {
-                writer.write_all(&(key.len() as u32).to_le_bytes())?;
-                writer.write_all(key)?;
+    let mut iterator = rocksdb.raw_iterator_opt(options);
+    iterator.seek_to_first();
 
-                writer.write_all(&(value.len() as u32).to_le_bytes())?;
-                writer.write_all(value)?;
+    if !iterator.valid() {
+        return Ok(());
+    }
+    info!("Migrating the storage to a column family per map...");
+
+    let mut num_entries = 0;
+    let mut batch = rocksdb::WriteBatch::default();
+
+    while iterator.valid() {
+        if let (Some(key), Some(value)) = (iterator.key(), iterator.value()) {
+            match split_legacy_key(key) {
+                Some((map_id, new_key)) => {
+                    batch.put_cf(column_family(rocksdb, map_id)?, &new_key, value);
+                    batch.delete(key);
+                    num_entries += 1;
+                }
+                None => {
+                    // Persist the entries that have been moved so far, as the migration resumes from them.
+                    rocksdb.write(batch)?;
+                    return Err(anyhow!(
+                        "Unknown storage key {:?} in the legacy layout, aborting the migration",
+                        key
+                    ));
+                }
             }
-            iterator.next();
         }
 
-        Ok(())
+        if batch.len() >= MIGRATION_BATCH_SIZE {
+            rocksdb.write(std::mem::take(&mut batch))?;
+        }
+        iterator.next();
     }
+    rocksdb.write(batch)?;
+
+    info!("Migrated {} storage entries to their column families", num_entries);
+    Ok(())
 }
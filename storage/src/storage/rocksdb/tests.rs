@@ -20,216 +20,91 @@ fn temp_dir() -> std::path::PathBuf {
     tempfile::tempdir().expect("Failed to open temporary directory").into_path()
 }
 
-fn temp_file() -> std::path::PathBuf {
-    tempfile::NamedTempFile::new()
-        .expect("Failed to open temporary file")
-        .path()
-        .to_owned()
-}
-
-#[test]
-fn test_open() {
-    let _storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
-}
-
 #[test]
-fn test_open_map() {
-    let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
-    storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
-}
-
-#[test]
-fn test_insert_and_contains_key() {
-    let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
-    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
-
-    map.insert(&123456789, &"123456789".to_string(), None).expect("Failed to insert");
-    assert!(map.contains_key(&123456789).expect("Failed to call contains key"));
-    assert!(!map.contains_key(&000000000).expect("Failed to call contains key"));
-}
-
-#[test]
-fn test_insert_and_get() {
-    let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
-    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
-
-    map.insert(&123456789, &"123456789".to_string(), None).expect("Failed to insert");
-    assert_eq!(Some("123456789".to_string()), map.get(&123456789).expect("Failed to get"));
-    assert_eq!(None, map.get(&000000000).expect("Failed to get"));
-}
-
-#[test]
-fn test_insert_and_remove() {
-    let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
-    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
-
-    map.insert(&123456789, &"123456789".to_string(), None).expect("Failed to insert");
-    assert!(map.get(&123456789).expect("Failed to get").is_some());
-
-    map.remove(&123456789, None).expect("Failed to remove");
-    assert!(map.get(&123456789).expect("Failed to get").is_none());
-}
-
-#[test]
-fn test_insert_and_iter() {
-    let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
-    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
-    map.insert(&123456789, &"123456789".to_string(), None).expect("Failed to insert");
-
-    let mut iter = map.iter();
-    assert_eq!(Some((123456789, "123456789".to_string())), iter.next());
-    assert_eq!(None, iter.next());
-}
-
-#[test]
-fn test_insert_and_keys() {
-    let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
-    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
-    map.insert(&123456789, &"123456789".to_string(), None).expect("Failed to insert");
+fn test_migrate_to_column_families() {
+    let directory = temp_dir();
+    {
+        // Write two maps in the legacy layout.
+        let mut options = rocksdb::Options::default();
+        options.create_if_missing(true);
+        let rocksdb = rocksdb::DB::open(&options, &directory).expect("Failed to open rocksdb");
+
+        for (map_id, value) in [(MapId::Test, "test"), (MapId::Shares, "shares")] {
+            let mut key = 0u16.to_le_bytes().to_vec();
+            key.extend_from_slice(&(map_id as u16).to_le_bytes());
+            bincode::serialize_into(&mut key, &1u32).unwrap();
+            rocksdb.put(&key, bincode::serialize(value).unwrap()).expect("Failed to insert");
+        }
+    }
 
-    let mut keys = map.keys();
-    assert_eq!(Some(123456789), keys.next());
-    assert_eq!(None, keys.next());
-}
+    // Ensure the maps are moved into their column families on open.
+    let storage = RocksDB::open(&directory, 0, false).expect("Failed to open storage");
 
-#[test]
-fn test_insert_and_values() {
-    let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
     let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
-    map.insert(&123456789, &"123456789".to_string(), None).expect("Failed to insert");
-
-    let mut values = map.values();
-    assert_eq!(Some("123456789".to_string()), values.next());
-    assert_eq!(None, values.next());
+    assert_eq!(Some("test".to_string()), map.get(&1).expect("Failed to get"));
+    assert_eq!(vec![(1, "test".to_string())], map.iter().collect::<Vec<_>>());
+
+    let map = storage.open_map::<u32, String>(MapId::Shares).expect("Failed to open data map");
+    assert_eq!(Some("shares".to_string()), map.get(&1).expect("Failed to get"));
+    assert_eq!(vec![(1, "shares".to_string())], map.iter().collect::<Vec<_>>());
+
+    let mut options = rocksdb::ReadOptions::default();
+    options.set_total_order_seek(true);
+    let mut iterator = storage.rocksdb.raw_iterator_opt(options);
+    iterator.seek_to_first();
+    assert!(!iterator.valid());
 }
 
 #[test]
-fn test_reopen() {
+fn test_migrate_unknown_key() {
     let directory = temp_dir();
     {
-        let storage = RocksDB::open(directory.clone(), 0, false).expect("Failed to open storage");
-        let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
-        map.insert(&123456789, &"123456789".to_string(), None).expect("Failed to insert");
-    }
-    {
-        let storage = RocksDB::open(directory, 0, false).expect("Failed to open storage");
-        let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
-        assert_eq!(Some("123456789".to_string()), map.get(&123456789).expect("Failed to get"));
+        // Write a key in the legacy layout that belongs to no map.
+        let mut options = rocksdb::Options::default();
+        options.create_if_missing(true);
+        let rocksdb = rocksdb::DB::open(&options, &directory).expect("Failed to open rocksdb");
+
+        let mut key = 0u16.to_le_bytes().to_vec();
+        key.extend_from_slice(&u16::MAX.to_le_bytes());
+        rocksdb.put(&key, b"unknown").expect("Failed to insert");
     }
-}
 
-#[test]
-fn test_batch_insert_and_remove() {
-    let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
-    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
-
-    let batch = map.prepare_batch();
-
-    map.insert(&1, &"1".to_string(), Some(batch)).expect("Failed to insert");
-    assert!(map.get(&1).expect("Failed to get").is_none());
-
-    map.insert(&2, &"2".to_string(), Some(batch)).expect("Failed to insert");
-    assert!(map.get(&2).expect("Failed to get").is_none());
-
-    map.execute_batch(batch).expect("Failed to execute a batch");
-    assert!(map.get(&1).expect("Failed to get").is_some());
-    assert!(map.get(&2).expect("Failed to get").is_some());
-    assert!(map.execute_batch(batch).is_err());
-
-    let batch = map.prepare_batch();
-
-    map.remove(&1, Some(batch)).expect("Failed to remove");
-    assert!(map.get(&1).expect("Failed to get").is_some());
-    map.remove(&2, Some(batch)).expect("Failed to remove");
-    assert!(map.get(&2).expect("Failed to get").is_some());
-
-    map.execute_batch(batch).expect("Failed to execute a batch");
-    assert!(map.get(&1).expect("Failed to get").is_none());
-    assert!(map.get(&2).expect("Failed to get").is_none());
-    assert!(map.execute_batch(batch).is_err());
+    // Ensure the migration fails, rather than leaving the key behind.
+    assert!(RocksDB::open(&directory, 0, false).is_err());
 }
 
 #[test]
-fn test_multiple_batches() {
-    let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
-    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
-
-    let batch1 = map.prepare_batch();
-    let batch2 = map.prepare_batch();
-    let batch3 = map.prepare_batch();
-
-    map.insert(&1, &"1".to_string(), Some(batch1)).expect("Failed to insert");
-    map.insert(&2, &"2".to_string(), Some(batch1)).expect("Failed to insert");
-
-    map.insert(&3, &"3".to_string(), Some(batch2)).expect("Failed to insert");
-    map.insert(&4, &"4".to_string(), Some(batch2)).expect("Failed to insert");
-
-    map.insert(&5, &"5".to_string(), Some(batch3)).expect("Failed to insert");
-    map.insert(&6, &"6".to_string(), Some(batch3)).expect("Failed to insert");
-
-    for i in 1..=6 {
-        assert!(map.get(&i).expect("Failed to get").is_none());
-    }
-
-    map.execute_batch(batch3).expect("Failed to execute a batch");
-    assert!(map.get(&5).expect("Failed to get").is_some());
-    assert!(map.get(&6).expect("Failed to get").is_some());
-    assert!(map.execute_batch(batch3).is_err());
-
-    for i in 1..=4 {
-        assert!(map.get(&i).expect("Failed to get").is_none());
-    }
-
-    map.execute_batch(batch2).expect("Failed to execute a batch");
-    assert!(map.get(&3).expect("Failed to get").is_some());
-    assert!(map.get(&4).expect("Failed to get").is_some());
-    assert!(map.execute_batch(batch2).is_err());
-
-    assert!(map.get(&1).expect("Failed to get").is_none());
-    assert!(map.get(&2).expect("Failed to get").is_none());
-}
+fn test_backup_and_restore() {
+    let directory = temp_dir();
+    let backup_directory = temp_dir().join("backups");
 
-#[test]
-fn test_discard_batch() {
-    let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let storage = RocksDB::open(&directory, 0, false).expect("Failed to open storage");
     let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
 
-    let batch = map.prepare_batch();
-
-    map.insert(&1, &"1".to_string(), Some(batch)).expect("Failed to insert");
-    map.insert(&2, &"2".to_string(), Some(batch)).expect("Failed to insert");
-
-    assert!(map.get(&1).expect("Failed to get").is_none());
-    assert!(map.get(&2).expect("Failed to get").is_none());
-
-    assert!(map.discard_batch(batch).is_ok());
-    assert!(map.execute_batch(batch).is_err());
-
-    assert!(map.get(&1).expect("Failed to get").is_none());
-    assert!(map.get(&2).expect("Failed to get").is_none());
-}
-
-#[test]
-fn test_export_import() {
-    let file = temp_file();
-
-    {
-        let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
-        let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
-
-        for i in 0..100 {
-            map.insert(&i, &i.to_string(), None).expect("Failed to insert");
-        }
-
-        storage.export(&file).expect("Failed to export storage");
+    // Create a backup after each insertion, keeping only the 2 most recent ones.
+    for i in 0..3 {
+        map.insert(&i, &i.to_string(), None).expect("Failed to insert");
+        let backup = storage.backup(&backup_directory, Some(2)).expect("Failed to back up storage");
+        assert_eq!(i + 1, backup.backup_id);
     }
-
-    let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
-    storage.import(&file).expect("Failed to import storage");
-
-    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
-
-    for i in 0..100 {
-        assert_eq!(map.get(&i).expect("Failed to get"), Some(i.to_string()));
+    let backups = RocksDB::backups(&backup_directory).expect("Failed to list the backups");
+    assert_eq!(vec![2, 3], backups.iter().map(|backup| backup.backup_id).collect::<Vec<_>>());
+
+    // Ensure a read-only instance is unable to be backed up.
+    let reader = RocksDB::open(&directory, 0, true).expect("Failed to open storage");
+    assert!(reader.backup(&backup_directory, None).is_err());
+    drop(reader);
+
+    // Ensure the restored storage contains the entries of the restored backup.
+    let restored_directory = temp_dir().join("restored");
+    for (backup_id, num_entries) in [(Some(2), 2), (None, 3)] {
+        RocksDB::restore_backup(&backup_directory, &restored_directory, backup_id).expect("Failed to restore the backup");
+
+        let restored = RocksDB::open(&restored_directory, 0, false).expect("Failed to open storage");
+        let map = restored.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+        assert_eq!(num_entries, map.iter().count());
     }
+
+    // Ensure a missing backup is unable to be restored.
+    assert!(RocksDB::restore_backup(&backup_directory, &restored_directory, Some(1)).is_err());
 }
@@ -19,13 +19,15 @@ use super::*;
 /// An iterator over the values of a prefix.
 pub struct Values<'a, V> {
     db_iter: rocksdb::DBIterator<'a>,
+    range: KeyRange,
     _phantom: PhantomData<V>,
 }
 
 impl<'a, V: DeserializeOwned> Values<'a, V> {
-    pub(crate) fn new(db_iter: rocksdb::DBIterator<'a>) -> Self {
+    pub(crate) fn new(db_iter: rocksdb::DBIterator<'a>, range: KeyRange) -> Self {
         Self {
             db_iter,
+            range,
             _phantom: PhantomData,
         }
     }
@@ -35,7 +37,7 @@ impl<'a, V: DeserializeOwned> Iterator for Values<'a, V> {
     type Item = V;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (_, value) = self.db_iter.next()?;
+        let (_, value) = self.range.next_in_range(&mut self.db_iter)?;
         let value = bincode::deserialize(&value).ok()?;
 
         Some(value)
@@ -0,0 +1,154 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::storage::{DataMap, Map, MapId, Storage};
+
+use anyhow::{bail, Result};
+use std::convert::TryInto;
+
+/// The version of the storage schema written by this version of snarkOS.
+pub const SCHEMA_VERSION: u32 = 2;
+
+///
+/// An upgrade of the storage schema to the next version.
+///
+pub struct Migration<S: Storage> {
+    /// The schema version of the storage after the migration.
+    pub version: u32,
+    /// A description of the changes made by the migration.
+    pub description: &'static str,
+    /// Applies the changes of the migration to the given storage, within the given batch.
+    apply: fn(&S, usize) -> Result<()>,
+}
+
+/// Returns the migrations of the storage schema, ordered by version.
+fn migrations<S: Storage>() -> Vec<Migration<S>> {
+    vec![
+        Migration {
+            version: 1,
+            description: "Records the storage schema version",
+            // Storage created before the schema version was introduced only lacks the version itself.
+            apply: |_, _| Ok(()),
+        },
+        Migration {
+            version: 2,
+            description: "Encodes the block heights in big-endian order",
+            apply: |storage, batch| {
+                let block_heights: DataMap<u32, ()> = storage.open_map(MapId::BlockHeights)?;
+                let entries = block_heights.raw_entries();
+
+                // Remove every key before inserting any, as the reversed bytes of a block height
+                // may equal the bytes of another block height in the previous encoding.
+                for (key, _) in &entries {
+                    block_heights.remove_raw(key, Some(batch))?;
+                }
+                for (key, value) in &entries {
+                    let block_height: [u8; 4] = key.as_slice().try_into()?;
+                    block_heights.insert_raw(&u32::from_le_bytes(block_height).to_be_bytes(), value, Some(batch))?;
+                }
+                Ok(())
+            },
+        },
+    ]
+}
+
+/// Opens the map holding the schema version of the given storage.
+fn open_schema_map<S: Storage>(storage: &S) -> Result<DataMap<(), u32>> {
+    storage.open_map(MapId::Schema)
+}
+
+///
+/// Returns the schema version of the given storage, or `None` if the storage is new.
+/// Storage that holds data in any map without a schema version is at version 0.
+///
+pub fn schema_version<S: Storage>(storage: &S) -> Result<Option<u32>> {
+    match open_schema_map(storage)?.get(&())? {
+        Some(version) => Ok(Some(version)),
+        None => {
+            // The ledger, operator, prover and peer book storage each hold data in different maps.
+            for map_id in MapId::ALL {
+                let map: DataMap<(), ()> = storage.open_map(*map_id)?;
+                if map.has_entries() {
+                    return Ok(Some(0));
+                }
+            }
+            Ok(None)
+        }
+    }
+}
+
+///
+/// Returns the migrations that have yet to be applied to the given storage, ordered by version.
+///
+pub fn pending_migrations<S: Storage>(storage: &S) -> Result<Vec<Migration<S>>> {
+    match schema_version(storage)? {
+        Some(version) if version > SCHEMA_VERSION => {
+            bail!("Storage schema version {} is newer than the supported version {}", version, SCHEMA_VERSION)
+        }
+        Some(version) => Ok(migrations().into_iter().filter(|migration| migration.version > version).collect()),
+        None => Ok(vec![]),
+    }
+}
+
+///
+/// Applies the pending migrations to the given storage, and returns them.
+///
+/// Each migration is executed in its own batch along with its schema version,
+/// so an interrupted migration resumes from the last completed version.
+///
+pub fn migrate<S: Storage>(storage: &S) -> Result<Vec<Migration<S>>> {
+    let schema = open_schema_map(storage)?;
+
+    // New storage starts at the latest schema version.
+    if schema_version(storage)?.is_none() {
+        schema.insert(&(), &SCHEMA_VERSION, None)?;
+        return Ok(vec![]);
+    }
+
+    let migrations = pending_migrations(storage)?;
+    for migration in &migrations {
+        info!("Migrating the storage schema to version {} ({})", migration.version, migration.description);
+
+        let batch = schema.prepare_batch();
+        schema.insert(&(), &migration.version, Some(batch))?;
+
+        if let Err(error) = (migration.apply)(storage, batch) {
+            schema.discard_batch(batch)?;
+            return Err(error);
+        }
+        schema.execute_batch(batch)?;
+    }
+
+    Ok(migrations)
+}
+
+///
+/// Ensures the schema of the given storage is up to date, and migrates it if it is writable.
+///
+pub(crate) fn check_schema_version<S: Storage>(storage: &S, is_read_only: bool) -> Result<()> {
+    match schema_version(storage)? {
+        Some(SCHEMA_VERSION) => Ok(()),
+        Some(version) if version > SCHEMA_VERSION => {
+            bail!("Storage schema version {} is newer than the supported version {}", version, SCHEMA_VERSION)
+        }
+        // A reader is unable to migrate the storage, which is left to its writer.
+        Some(version) if is_read_only => {
+            bail!("Storage schema version {} is outdated, run `snarkos ledger migrate` to upgrade it", version)
+        }
+        None if is_read_only => Ok(()),
+        _ => migrate(storage).map(|_| ()),
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use super::MapId;
+
+///
+/// The approximate number of keys and size of a map in storage.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MapStats {
+    pub map_id: MapId,
+    /// The approximate number of keys in the map.
+    pub num_keys: u64,
+    /// The approximate size of the map in bytes, including the entries that are yet to be flushed to disk.
+    pub size: u64,
+}
+
+///
+/// The approximate space used by storage, per map and in total.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageStats {
+    /// The statistics of each map in storage.
+    pub maps: Vec<MapStats>,
+    /// The total size of the SST files in bytes, which hold the entries that were flushed to disk.
+    pub sst_files_size: u64,
+    /// The total size of the write-ahead log files in bytes.
+    pub wal_files_size: u64,
+}
+
+impl StorageStats {
+    /// Returns the approximate number of keys across all maps.
+    pub fn num_keys(&self) -> u64 {
+        self.maps.iter().map(|map| map.num_keys).sum()
+    }
+
+    /// Returns the approximate size of all maps in bytes.
+    pub fn size(&self) -> u64 {
+        self.maps.iter().map(|map| map.size).sum()
+    }
+}
@@ -0,0 +1,522 @@
+// Copyright (C) 2019-2022 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::storage::{
+    export::Compression,
+    schema::{pending_migrations, schema_version, SCHEMA_VERSION},
+    Map,
+    MapId,
+    Storage,
+};
+
+use std::ops::Bound;
+
+fn temp_dir() -> std::path::PathBuf {
+    tempfile::tempdir().expect("Failed to open temporary directory").into_path()
+}
+
+fn temp_file() -> std::path::PathBuf {
+    tempfile::NamedTempFile::new()
+        .expect("Failed to open temporary file")
+        .path()
+        .to_owned()
+}
+
+fn test_open<S: Storage>() {
+    let _storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+}
+
+fn test_open_map<S: Storage>() {
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+}
+
+fn test_insert_and_contains_key<S: Storage>() {
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+
+    map.insert(&123456789, &"123456789".to_string(), None).expect("Failed to insert");
+    assert!(map.contains_key(&123456789).expect("Failed to call contains key"));
+    assert!(!map.contains_key(&000000000).expect("Failed to call contains key"));
+}
+
+fn test_insert_and_get<S: Storage>() {
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+
+    map.insert(&123456789, &"123456789".to_string(), None).expect("Failed to insert");
+    assert_eq!(Some("123456789".to_string()), map.get(&123456789).expect("Failed to get"));
+    assert_eq!(None, map.get(&000000000).expect("Failed to get"));
+}
+
+fn test_insert_and_remove<S: Storage>() {
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+
+    map.insert(&123456789, &"123456789".to_string(), None).expect("Failed to insert");
+    assert!(map.get(&123456789).expect("Failed to get").is_some());
+
+    map.remove(&123456789, None).expect("Failed to remove");
+    assert!(map.get(&123456789).expect("Failed to get").is_none());
+}
+
+fn test_insert_and_iter<S: Storage>() {
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+    map.insert(&123456789, &"123456789".to_string(), None).expect("Failed to insert");
+
+    let mut iter = map.iter();
+    assert_eq!(Some((123456789, "123456789".to_string())), iter.next());
+    assert_eq!(None, iter.next());
+}
+
+fn test_insert_and_keys<S: Storage>() {
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+    map.insert(&123456789, &"123456789".to_string(), None).expect("Failed to insert");
+
+    let mut keys = map.keys();
+    assert_eq!(Some(123456789), keys.next());
+    assert_eq!(None, keys.next());
+}
+
+fn test_insert_and_values<S: Storage>() {
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+    map.insert(&123456789, &"123456789".to_string(), None).expect("Failed to insert");
+
+    let mut values = map.values();
+    assert_eq!(Some("123456789".to_string()), values.next());
+    assert_eq!(None, values.next());
+}
+
+fn test_reopen<S: Storage>() {
+    let directory = temp_dir();
+    {
+        let storage = S::open(directory.clone(), 0, false).expect("Failed to open storage");
+        let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+        map.insert(&123456789, &"123456789".to_string(), None).expect("Failed to insert");
+    }
+    {
+        let storage = S::open(directory, 0, false).expect("Failed to open storage");
+        let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+        assert_eq!(Some("123456789".to_string()), map.get(&123456789).expect("Failed to get"));
+    }
+}
+
+fn test_reopen_with_reader<S: Storage>() {
+    let directory = temp_dir();
+    let reader = {
+        let storage = S::open(directory.clone(), 0, false).expect("Failed to open storage");
+        let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+        map.insert(&1, &"1".to_string(), None).expect("Failed to insert");
+
+        S::open(directory.clone(), 0, true).expect("Failed to open read-only storage")
+    };
+
+    // Ensure the read-only instance outlives its writable instance, and observes the writes of the next one.
+    let reader_map = reader.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+    assert_eq!(Some("1".to_string()), reader_map.get(&1).expect("Failed to get"));
+    {
+        let storage = S::open(directory.clone(), 0, false).expect("Failed to open storage");
+        let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+        assert_eq!(Some("1".to_string()), map.get(&1).expect("Failed to get"));
+        map.insert(&2, &"2".to_string(), None).expect("Failed to insert");
+    }
+    assert!(reader_map.refresh());
+    assert_eq!(Some("2".to_string()), reader_map.get(&2).expect("Failed to get"));
+}
+
+fn test_read_only_refresh<S: Storage>() {
+    let directory = temp_dir();
+    let storage = S::open(&directory, 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+    map.insert(&1, &"1".to_string(), None).expect("Failed to insert");
+
+    let reader = S::open(&directory, 0, true).expect("Failed to open read-only storage");
+    let reader_map = reader.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+    assert_eq!(Some("1".to_string()), reader_map.get(&1).expect("Failed to get"));
+    assert!(reader_map.insert(&2, &"2".to_string(), None).is_err());
+
+    // Ensure the reader only observes new writes after a refresh.
+    map.insert(&2, &"2".to_string(), None).expect("Failed to insert");
+    assert!(reader_map.get(&2).expect("Failed to get").is_none());
+    assert!(reader_map.refresh());
+    assert_eq!(Some("2".to_string()), reader_map.get(&2).expect("Failed to get"));
+    assert!(!reader_map.refresh());
+    assert!(!map.refresh());
+}
+
+fn test_batch_insert_and_remove<S: Storage>() {
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+
+    let batch = map.prepare_batch();
+
+    map.insert(&1, &"1".to_string(), Some(batch)).expect("Failed to insert");
+    assert!(map.get(&1).expect("Failed to get").is_none());
+
+    map.insert(&2, &"2".to_string(), Some(batch)).expect("Failed to insert");
+    assert!(map.get(&2).expect("Failed to get").is_none());
+
+    map.execute_batch(batch).expect("Failed to execute a batch");
+    assert!(map.get(&1).expect("Failed to get").is_some());
+    assert!(map.get(&2).expect("Failed to get").is_some());
+    assert!(map.execute_batch(batch).is_err());
+
+    let batch = map.prepare_batch();
+
+    map.remove(&1, Some(batch)).expect("Failed to remove");
+    assert!(map.get(&1).expect("Failed to get").is_some());
+    map.remove(&2, Some(batch)).expect("Failed to remove");
+    assert!(map.get(&2).expect("Failed to get").is_some());
+
+    map.execute_batch(batch).expect("Failed to execute a batch");
+    assert!(map.get(&1).expect("Failed to get").is_none());
+    assert!(map.get(&2).expect("Failed to get").is_none());
+    assert!(map.execute_batch(batch).is_err());
+}
+
+fn test_multiple_batches<S: Storage>() {
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+
+    let batch1 = map.prepare_batch();
+    let batch2 = map.prepare_batch();
+    let batch3 = map.prepare_batch();
+
+    map.insert(&1, &"1".to_string(), Some(batch1)).expect("Failed to insert");
+    map.insert(&2, &"2".to_string(), Some(batch1)).expect("Failed to insert");
+
+    map.insert(&3, &"3".to_string(), Some(batch2)).expect("Failed to insert");
+    map.insert(&4, &"4".to_string(), Some(batch2)).expect("Failed to insert");
+
+    map.insert(&5, &"5".to_string(), Some(batch3)).expect("Failed to insert");
+    map.insert(&6, &"6".to_string(), Some(batch3)).expect("Failed to insert");
+
+    for i in 1..=6 {
+        assert!(map.get(&i).expect("Failed to get").is_none());
+    }
+
+    map.execute_batch(batch3).expect("Failed to execute a batch");
+    assert!(map.get(&5).expect("Failed to get").is_some());
+    assert!(map.get(&6).expect("Failed to get").is_some());
+    assert!(map.execute_batch(batch3).is_err());
+
+    for i in 1..=4 {
+        assert!(map.get(&i).expect("Failed to get").is_none());
+    }
+
+    map.execute_batch(batch2).expect("Failed to execute a batch");
+    assert!(map.get(&3).expect("Failed to get").is_some());
+    assert!(map.get(&4).expect("Failed to get").is_some());
+    assert!(map.execute_batch(batch2).is_err());
+
+    assert!(map.get(&1).expect("Failed to get").is_none());
+    assert!(map.get(&2).expect("Failed to get").is_none());
+}
+
+fn test_discard_batch<S: Storage>() {
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+
+    let batch = map.prepare_batch();
+
+    map.insert(&1, &"1".to_string(), Some(batch)).expect("Failed to insert");
+    map.insert(&2, &"2".to_string(), Some(batch)).expect("Failed to insert");
+
+    assert!(map.get(&1).expect("Failed to get").is_none());
+    assert!(map.get(&2).expect("Failed to get").is_none());
+
+    assert!(map.discard_batch(batch).is_ok());
+    assert!(map.execute_batch(batch).is_err());
+
+    assert!(map.get(&1).expect("Failed to get").is_none());
+    assert!(map.get(&2).expect("Failed to get").is_none());
+}
+
+fn test_export_import<S: Storage>() {
+    let file = temp_file();
+
+    {
+        let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+        let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+
+        for i in 0..100 {
+            map.insert(&i, &i.to_string(), None).expect("Failed to insert");
+        }
+
+        storage.export(&file).expect("Failed to export storage");
+    }
+
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    storage.import(&file).expect("Failed to import storage");
+
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+
+    for i in 0..100 {
+        assert_eq!(map.get(&i).expect("Failed to get"), Some(i.to_string()));
+    }
+}
+
+fn test_export_import_maps<S: Storage>() {
+    let file = temp_file();
+
+    {
+        let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+        let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+        map.insert(&1, &"1".to_string(), None).expect("Failed to insert");
+        let map = storage.open_map::<u32, String>(MapId::Shares).expect("Failed to open data map");
+        map.insert(&2, &"2".to_string(), None).expect("Failed to insert");
+
+        storage
+            .export_maps(&file, &[MapId::Test, MapId::Shares], Compression::Zstd)
+            .expect("Failed to export storage");
+    }
+
+    // Ensure only the given maps are imported.
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    storage.import_maps(&file, &[MapId::Test]).expect("Failed to import storage");
+
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+    assert_eq!(Some("1".to_string()), map.get(&1).expect("Failed to get"));
+    let map = storage.open_map::<u32, String>(MapId::Shares).expect("Failed to open data map");
+    assert_eq!(None, map.get(&2).expect("Failed to get"));
+
+    // Ensure a storage file for a different network is rejected.
+    let storage = S::open(temp_dir(), 1, false).expect("Failed to open storage");
+    assert!(storage.import(&file).is_err());
+}
+
+fn test_import_corrupted<S: Storage>() {
+    let file = temp_file();
+
+    {
+        let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+        let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+        for i in 0..100 {
+            map.insert(&i, &i.to_string(), None).expect("Failed to insert");
+        }
+        storage.export(&file).expect("Failed to export storage");
+    }
+
+    // Corrupt the last byte of the storage file.
+    let mut bytes = std::fs::read(&file).expect("Failed to read the storage file");
+    *bytes.last_mut().unwrap() ^= 0xff;
+    std::fs::write(&file, &bytes).expect("Failed to write the storage file");
+
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    assert!(storage.import(&file).is_err());
+
+    // Ensure an oversized chunk length is rejected before it is allocated.
+    let header_len = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+    let chunk_start = 12 + header_len;
+    bytes[chunk_start..chunk_start + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    std::fs::write(&file, &bytes).expect("Failed to write the storage file");
+
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    assert!(storage.import(&file).is_err());
+}
+
+fn test_import_legacy<S: Storage>() {
+    let file = temp_file();
+
+    // Write a storage file in the legacy format, where each key is prefixed with its network ID and map ID.
+    let mut bytes = Vec::new();
+    for i in 0u32..10 {
+        let key = [&0u16.to_le_bytes()[..], &(MapId::Shares as u16).to_le_bytes(), &i.to_le_bytes()].concat();
+        let value = bincode::serialize(&i.to_string()).expect("Failed to serialize");
+        bytes.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&key);
+        bytes.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&value);
+    }
+    std::fs::write(&file, &bytes).expect("Failed to write the storage file");
+
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    storage.import(&file).expect("Failed to import storage");
+    let map = storage.open_map::<u32, String>(MapId::Shares).expect("Failed to open data map");
+    for i in 0..10 {
+        assert_eq!(Some(i.to_string()), map.get(&i).expect("Failed to get"));
+    }
+
+    // Ensure a legacy storage file of another network is rejected.
+    let storage = S::open(temp_dir(), 1, false).expect("Failed to open storage");
+    assert!(storage.import(&file).is_err());
+    let map = storage.open_map::<u32, String>(MapId::Shares).expect("Failed to open data map");
+    assert!(map.get(&0).expect("Failed to get").is_none());
+}
+
+fn test_schema_version<S: Storage>() {
+    // Ensure new storage starts at the latest schema version.
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    assert_eq!(Some(SCHEMA_VERSION), schema_version(&storage).expect("Failed to get the schema version"));
+
+    // Ensure storage that holds data in a map other than the blocks without a schema version is at version 0.
+    {
+        let storage = S::open_unchecked(temp_dir(), 0, false).expect("Failed to open storage");
+        let shares = storage.open_map::<u32, u64>(MapId::Shares).expect("Failed to open data map");
+        shares.insert(&1, &1, None).expect("Failed to insert");
+        assert_eq!(Some(0), schema_version(&storage).expect("Failed to get the schema version"));
+    }
+
+    // Initialize storage that holds blocks without a schema version.
+    let directory = temp_dir();
+    {
+        let storage = S::open_unchecked(&directory, 0, false).expect("Failed to open storage");
+        let block_heights = storage.open_map::<u32, u32>(MapId::BlockHeights).expect("Failed to open data map");
+        // Insert the block heights in the little-endian encoding that predates the schema version.
+        for block_height in [0u32, 1, 1 << 24] {
+            let value = bincode::serialize(&block_height).expect("Failed to serialize");
+            block_heights.insert_raw(&block_height.to_le_bytes(), &value, None).expect("Failed to insert");
+        }
+
+        assert_eq!(Some(0), schema_version(&storage).expect("Failed to get the schema version"));
+        let migrations = pending_migrations(&storage).expect("Failed to get the pending migrations");
+        assert_eq!(vec![1, 2], migrations.iter().map(|migration| migration.version).collect::<Vec<_>>());
+    }
+
+    // Ensure the storage is migrated on open.
+    {
+        let storage = S::open(&directory, 0, false).expect("Failed to open storage");
+        assert_eq!(Some(SCHEMA_VERSION), schema_version(&storage).expect("Failed to get the schema version"));
+        assert!(pending_migrations(&storage).expect("Failed to get the pending migrations").is_empty());
+
+        // Ensure the block heights are migrated to the big-endian encoding.
+        let block_heights = storage.open_map::<u32, u32>(MapId::BlockHeights).expect("Failed to open data map");
+        let expected = vec![(0, 0), (1, 1), (1 << 24, 1 << 24)];
+        assert_eq!(expected, block_heights.iter().collect::<Vec<_>>());
+
+        // Write a newer schema version.
+        let schema = storage.open_map::<(), u32>(MapId::Schema).expect("Failed to open data map");
+        schema.insert(&(), &(SCHEMA_VERSION + 1), None).expect("Failed to insert");
+    }
+
+    // Ensure storage with a newer schema version fails to open.
+    assert!(S::open(&directory, 0, false).is_err());
+}
+
+fn test_snapshot<S: Storage>() {
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+    let other = storage.open_map::<u32, String>(MapId::Shares).expect("Failed to open data map");
+
+    map.insert(&1, &"1".to_string(), None).expect("Failed to insert");
+    other.insert(&1, &"1".to_string(), None).expect("Failed to insert");
+
+    let snapshot = map.snapshot();
+
+    // Write to both maps after the snapshot is taken.
+    let batch = map.prepare_batch();
+    map.remove(&1, Some(batch)).expect("Failed to remove");
+    map.insert(&2, &"2".to_string(), Some(batch)).expect("Failed to insert");
+    other.insert(&1, &"one".to_string(), Some(batch)).expect("Failed to insert");
+    map.execute_batch(batch).expect("Failed to execute a batch");
+
+    // Ensure the reads from the snapshot observe both maps before the writes.
+    assert_eq!(Some("1".to_string()), map.get_at(&1, &snapshot).expect("Failed to get"));
+    assert_eq!(None, map.get_at(&2, &snapshot).expect("Failed to get"));
+    assert_eq!(vec![(1, "1".to_string())], map.range_at(0..10, &snapshot).expect("Failed to get a range"));
+    assert_eq!(Some("1".to_string()), other.get_at(&1, &snapshot).expect("Failed to get"));
+
+    // Ensure the maps themselves observe the writes.
+    assert_eq!(vec![(2, "2".to_string())], map.iter().collect::<Vec<_>>());
+    assert_eq!(Some("one".to_string()), other.get(&1).expect("Failed to get"));
+
+    // Ensure a new snapshot observes the writes.
+    let snapshot = map.snapshot();
+    assert_eq!(vec![(2, "2".to_string())], map.range_at(0..10, &snapshot).expect("Failed to get a range"));
+}
+
+/// Returns the keys of the given key-value pairs.
+fn keys_of<V>(iter: impl Iterator<Item = (u32, V)>) -> Vec<u32> {
+    iter.map(|(key, _)| key).collect()
+}
+
+fn test_range_and_reverse<S: Storage>() {
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+
+    // The keys are chosen to be out of order in a little-endian encoding.
+    let keys = [1u32, 2, 256, 65536, 16777216];
+    for key in keys {
+        map.insert(&key, &key.to_string(), None).expect("Failed to insert");
+    }
+    // Insert into another map, to ensure the iteration stays within its own map.
+    let other = storage.open_map::<u32, String>(MapId::Shares).expect("Failed to open data map");
+    other.insert(&3, &"3".to_string(), None).expect("Failed to insert");
+
+    // Ensure the keys are iterated in order, in both directions.
+    assert_eq!(keys.to_vec(), keys_of(map.iter()));
+    assert_eq!(keys.iter().rev().copied().collect::<Vec<_>>(), keys_of(map.iter_rev()));
+
+    // Ensure the bounds of a range are respected.
+    assert_eq!(vec![2, 256], keys_of(map.range(2..65536).expect("Failed to get the range")));
+    assert_eq!(vec![2, 256, 65536], keys_of(map.range(2..=65536).expect("Failed to get the range")));
+    assert_eq!(vec![1, 2], keys_of(map.range(..=2).expect("Failed to get the range")));
+    assert_eq!(Vec::<u32>::new(), keys_of(map.range(3..256).expect("Failed to get the range")));
+    let range = (Bound::Excluded(2), Bound::Excluded(16777216));
+    assert_eq!(vec![256, 65536], keys_of(map.range(range).expect("Failed to get the range")));
+
+    // Ensure the iteration can start from a key that is not in the map.
+    assert_eq!(vec![256, 65536, 16777216], keys_of(map.iter_from(&3).expect("Failed to iterate")));
+    assert_eq!(vec![16777216], keys_of(map.iter_from(&16777216).expect("Failed to iterate")));
+}
+
+fn test_stats<S: Storage>() {
+    let storage = S::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>(MapId::Test).expect("Failed to open data map");
+
+    for i in 0..10 {
+        map.insert(&i, &i.to_string(), None).expect("Failed to insert");
+    }
+
+    // Ensure every map is accounted for, and the inserted keys are counted.
+    let stats = storage.stats().expect("Failed to get the storage stats");
+    assert_eq!(MapId::ALL.len(), stats.maps.len());
+
+    let map_stats = |map_id| *stats.maps.iter().find(|map| map.map_id == map_id).expect("Missing the map stats");
+    assert_eq!(10, map_stats(MapId::Test).num_keys);
+    assert!(map_stats(MapId::Test).size > 0);
+    assert_eq!(0, map_stats(MapId::Shares).num_keys);
+    assert!(stats.num_keys() >= 10);
+    assert!(stats.size() >= map_stats(MapId::Test).size);
+}
+
+test_storage_backends!(
+    test_open,
+    test_open_map,
+    test_insert_and_contains_key,
+    test_insert_and_get,
+    test_insert_and_remove,
+    test_insert_and_iter,
+    test_insert_and_keys,
+    test_insert_and_values,
+    test_reopen_with_reader,
+    test_read_only_refresh,
+    test_batch_insert_and_remove,
+    test_multiple_batches,
+    test_discard_batch,
+    test_export_import,
+    test_export_import_maps,
+    test_import_corrupted,
+    test_import_legacy,
+    test_range_and_reverse,
+    test_snapshot,
+    test_stats,
+);
+
+test_persistent_storage_backends!(test_reopen, test_schema_version);
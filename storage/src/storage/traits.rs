@@ -14,17 +14,33 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use super::{DataMap, MapId};
+use super::{export::Compression, schema::check_schema_version, DataMap, MapId, StorageStats};
 
 use anyhow::Result;
 use serde::{de::DeserializeOwned, Serialize};
-use std::{borrow::Borrow, path::Path};
+use std::{
+    borrow::Borrow,
+    ops::{Bound, RangeBounds},
+    path::Path,
+};
 
 pub trait Storage {
     ///
-    /// Opens storage at the given `path` and `context`.
+    /// Opens storage at the given `path` and `context`, and ensures its schema is up to date.
     ///
     fn open<P: AsRef<Path>>(path: P, context: u16, is_read_only: bool) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let storage = Self::open_unchecked(path, context, is_read_only)?;
+        check_schema_version(&storage, is_read_only)?;
+        Ok(storage)
+    }
+
+    ///
+    /// Opens storage at the given `path` and `context`, without checking its schema version.
+    ///
+    fn open_unchecked<P: AsRef<Path>>(path: P, context: u16, is_read_only: bool) -> Result<Self>
     where
         Self: Sized;
 
@@ -36,12 +52,32 @@ pub trait Storage {
     ///
     /// Imports a file with the given path to reconstruct storage.
     ///
-    fn import<P: AsRef<Path>>(&self, path: P) -> Result<()>;
+    fn import<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.import_maps(path, MapId::ALL)
+    }
+
+    ///
+    /// Imports the given maps from a file with the given path to reconstruct storage.
+    ///
+    fn import_maps<P: AsRef<Path>>(&self, path: P, map_ids: &[MapId]) -> Result<()>;
 
     ///
     /// Exports the current state of storage to a single file at the specified location.
     ///
-    fn export<P: AsRef<Path>>(&self, path: P) -> Result<()>;
+    fn export<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.export_maps(path, MapId::ALL, Compression::Lz4)
+    }
+
+    ///
+    /// Exports the current state of the given maps to a single file at the specified location,
+    /// compressing its chunks with the given compression.
+    ///
+    fn export_maps<P: AsRef<Path>>(&self, path: P, map_ids: &[MapId], compression: Compression) -> Result<()>;
+
+    ///
+    /// Returns the approximate number of keys and size of each map, along with the sizes of the files of storage.
+    ///
+    fn stats(&self) -> Result<StorageStats>;
 }
 
 pub trait Map<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> {
@@ -90,6 +126,33 @@ pub trait Map<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwn
     ///
     fn iter(&'a self) -> Self::Iterator;
 
+    ///
+    /// Returns an iterator visiting each key-value pair in the map, in reverse order.
+    ///
+    fn iter_rev(&'a self) -> Self::Iterator;
+
+    ///
+    /// Returns an iterator visiting each key-value pair in the given range of keys.
+    /// The keys are ordered by their serialization, which preserves the order of
+    /// integer keys in maps with ordered keys (see `MapId::has_ordered_keys`).
+    ///
+    fn range<Q, R>(&'a self, range: R) -> Result<Self::Iterator>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+        R: RangeBounds<Q>;
+
+    ///
+    /// Returns an iterator visiting each key-value pair in the map, starting from the given key.
+    ///
+    fn iter_from<Q>(&'a self, key: &Q) -> Result<Self::Iterator>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        self.range((Bound::Included(key), Bound::Unbounded))
+    }
+
     ///
     /// Returns an iterator over each key in the map.
     ///